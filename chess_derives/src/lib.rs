@@ -8,7 +8,7 @@ pub fn derive_execute_move(input: TokenStream) -> TokenStream {
     let DeriveInput { ident, .. } = parse_macro_input!(input);
     let output = quote! {
         impl ExecuteMove for #ident {
-            fn execute_move(&self, chess_move: ChessMove) -> Result<(), PieceError> {
+            fn execute_move(&mut self, chess_move: ChessMove) -> Result<(), PieceError> {
                 match chess_move {
                     Move(movement) => {self.move_piece(movement.from_position, movement.to_position)?;}
                     MoveWithTake(movement, take) => {
@@ -23,6 +23,12 @@ pub fn derive_execute_move(input: TokenStream) -> TokenStream {
                         self.move_piece(movement.from_position, movement.to_position)?;
                         self.promote_piece(promotion.position, promotion.piece_type)?;
                     }
+                    PromoteWithTake(movement, take, promotion) => {
+                        self.take_piece(take.position)?;
+                        self.move_piece(movement.from_position, movement.to_position)?;
+                        self.promote_piece(promotion.position, promotion.piece_type)?;
+                    }
+                    Drop(place) => {self.place_piece(place)?;}
                 }
                 return Ok(());
             }
@@ -0,0 +1,65 @@
+use chess_lib::{board::mailbox::Board, game::GameState, perft::perft, piece::Color};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Positions to benchmark, with a name and the perft depth to search each to.
+///
+/// The middlegame position ("kiwipete") is full of captures, checks, castling and en passant, and has more than twice as many moves as the start position, so is searched one half move shallower.
+const POSITIONS: [(&str, &str, u32); 2] = [
+    ("start", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR", 5),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+        4,
+    ),
+];
+
+fn states() -> impl Iterator<Item = (&'static str, GameState, u32)> {
+    POSITIONS.into_iter().map(|(name, placement, depth)| {
+        let board = Board::from_fen(placement).unwrap();
+        (name, GameState::from_board(board, Color::White), depth)
+    })
+}
+
+fn all_legal_moves(c: &mut Criterion) {
+    for (name, state, _) in states() {
+        c.bench_function(&format!("all_legal_moves {name}"), |b| {
+            b.iter(|| black_box(&state).all_legal_moves());
+        });
+    }
+}
+
+/// Perft that clones the state for every move instead of undoing it, to compare against [`perft`].
+fn perft_cloning(state: &GameState, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    state
+        .all_legal_moves()
+        .into_iter()
+        .map(|chess_move| {
+            let mut next = state.clone();
+            if next.make_move(chess_move).is_ok() {
+                perft_cloning(&next, depth - 1)
+            } else {
+                0
+            }
+        })
+        .sum()
+}
+
+fn perft_positions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+    for (name, state, depth) in states() {
+        group.bench_function(format!("{name} depth {depth}"), |b| {
+            b.iter(|| perft(black_box(&state), depth));
+        });
+        group.bench_function(format!("{name} depth {depth} cloning"), |b| {
+            b.iter(|| perft_cloning(black_box(&state), depth));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, all_legal_moves, perft_positions);
+criterion_main!(benches);
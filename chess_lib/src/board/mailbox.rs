@@ -1,16 +1,51 @@
-use crate::board::{Direction, Offset, Position};
-use crate::error::PieceError;
+use crate::board::{
+    action, piece_square, ChessMove, Direction, ExecuteMove, MovePiece, Offset, PlacePiece,
+    Position, PromotePiece, TakePiece,
+};
+use crate::error::{BoardError, FenError, PieceError, PositionOutOfBounds};
 use crate::piece::{Color, Piece, PieceType};
+use crate::variant::Variant;
 use array2d::Array2D;
 use log::{debug, info, trace, warn};
 use std::ops::{Index, IndexMut};
 
 use crate::board::layout::DEFAULT_BOARD;
 
+/// ANSI escape setting the background to the light square color.
+const ANSI_LIGHT: &str = "\x1b[48;5;180m\x1b[30m";
+/// ANSI escape setting the background to the dark square color.
+const ANSI_DARK: &str = "\x1b[48;5;137m\x1b[30m";
+/// ANSI escape resetting all colors.
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Offsets of every square a knight can jump to.
+pub(crate) const KNIGHT_OFFSETS: [Offset; 8] = [
+    Offset { x: 2, y: 1 },
+    Offset { x: -2, y: 1 },
+    Offset { x: -2, y: -1 },
+    Offset { x: 2, y: -1 },
+    Offset { x: 1, y: 2 },
+    Offset { x: -1, y: 2 },
+    Offset { x: -1, y: -2 },
+    Offset { x: 1, y: -2 },
+];
+
+/// Counts of the weaknesses and strengths in one color's pawn structure, see [`Board::pawn_structure`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct PawnStructure {
+    /// Pawns on a file already holding another friendly pawn, not counting the first pawn on each file.
+    pub doubled: u8,
+    /// Pawns with no friendly pawns on the files either side.
+    pub isolated: u8,
+    /// Pawns with no enemy pawns able to stop them promoting, see [`Board::passed_pawns`].
+    pub passed: u8,
+}
+
 /// Standard 8x8 chess board. Keeps track of positions of pieces.
 ///
 /// Has the capability to check the possible positions a piece could move to. It does not keep track of any game state, and therefore will not account for checks, pins or blocks.
 /// Can be indexed with a position, which will return either the piece at that position or None if no piece is present.
+/// Boards can be used as hash map keys. The `moved` flag of each piece is part of the hash, as it is for equality, so see [`zobrist::zobrist_hash`](crate::zobrist::zobrist_hash) for a key that only depends on the position.
 ///
 /// ```
 /// use chess_lib::{board::{*, mailbox::*}, piece::*};
@@ -19,7 +54,7 @@ use crate::board::layout::DEFAULT_BOARD;
 /// assert_eq!(b[Position::new(0, 0).unwrap()], Some(Piece::new(Color::White, PieceType::Rook)));
 /// assert_eq!(b[Position::new(0, 2).unwrap()], None);
 /// ```
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug, Hash)]
 pub struct Board {
     pieces: Array2D<Option<Piece>>,
 }
@@ -42,6 +77,179 @@ impl Board {
         }
     }
 
+    /// Creates a chess board with no pieces on it.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// assert_eq!(Board::empty().iter_pieces().count(), 0);
+    /// ```
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            pieces: Array2D::filled_with(None, 8, 8),
+        }
+    }
+
+    /// Creates a chess board from the piece placement field of a FEN string.
+    ///
+    /// Pieces standing on their starting square are marked as unmoved, all other pieces are marked as moved.
+    /// The resulting board is checked with [`Board::validate`].
+    ///
+    /// # Parameters
+    /// * `placement`: The piece placement field, starting from rank 8, e.g. `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR`.
+    /// # Errors
+    /// * Returns [`FenError::RankCount`] if there are not exactly 8 ranks.
+    /// * Returns [`FenError::FileCount`] if a rank does not describe exactly 8 squares.
+    /// * Returns [`FenError::InvalidPiece`] if an unrecognised character is found.
+    /// * Returns [`FenError::IllegalPosition`] if the board fails validation.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// let b = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").unwrap();
+    /// assert_eq!(b, Board::new());
+    /// assert!(Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKKNR").is_err());
+    /// ```
+    pub fn from_fen(placement: &str) -> Result<Self, FenError> {
+        info!("Creating board from FEN placement {placement}");
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::RankCount(ranks.len()));
+        }
+        let mut board = Self::empty();
+        for (y, rank) in (0..8).rev().zip(ranks) {
+            let mut x = 0;
+            for c in rank.chars() {
+                if let Some(skip) = c.to_digit(10).filter(|skip| (1..=8).contains(skip)) {
+                    x += skip;
+                    continue;
+                }
+                let Some(mut piece) = Piece::from_fen_char(c) else {
+                    return Err(FenError::InvalidPiece(c));
+                };
+                let Ok(position) = Position::new(x.try_into().unwrap_or(u8::MAX), y) else {
+                    return Err(FenError::FileCount(y));
+                };
+                piece.moved = DEFAULT_BOARD[(position.y.into(), position.x.into())] != Some(piece);
+                board[position] = Some(piece);
+                x += 1;
+            }
+            if x != 8 {
+                return Err(FenError::FileCount(y));
+            }
+        }
+        board.validate()?;
+        Ok(board)
+    }
+
+    /// Creates a chess board from a list of pieces and their positions, e.g. the output of [`Board::iter_pieces`].
+    ///
+    /// If two pieces are given for the same position, the later one overwrites the earlier one.
+    /// Pieces keep their `moved` flag as given. The board is not validated, see [`Board::validate`].
+    ///
+    /// # Parameters
+    /// * `pieces`: The pieces to place, along with their positions.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_pieces([
+    ///     (Position::new(4, 0).unwrap(), Piece::new(Color::White, PieceType::King)),
+    ///     (Position::new(4, 7).unwrap(), Piece::new(Color::Black, PieceType::King)),
+    /// ]);
+    /// assert_eq!(b.iter_pieces().count(), 2);
+    /// assert_eq!(Board::from_pieces(Board::new().iter_pieces()), Board::new());
+    /// ```
+    #[must_use]
+    pub fn from_pieces(pieces: impl IntoIterator<Item = (Position, Piece)>) -> Self {
+        let mut board = Self::empty();
+        for (position, piece) in pieces {
+            if let Some(previous) = board[position] {
+                warn!("Replacing {previous} at {position} with {piece}");
+            }
+            board[position] = Some(piece);
+        }
+        board
+    }
+
+    /// Returns the piece placement field of a FEN string describing the board, starting from rank 8.
+    ///
+    /// Whether pieces have moved is not part of FEN, so is lost.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// assert_eq!(Board::new().to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+    /// ```
+    #[must_use]
+    pub fn to_fen(&self) -> String {
+        let ranks: Vec<String> = (0..8)
+            .rev()
+            .map(|y| {
+                let mut rank = String::new();
+                let mut empty = 0;
+                for (_, piece) in self.rank(y).into_iter().flatten() {
+                    if let Some(piece) = piece {
+                        if empty > 0 {
+                            rank.push(char::from(b'0' + empty));
+                            empty = 0;
+                        }
+                        rank.push(piece.to_fen_char());
+                    } else {
+                        empty += 1;
+                    }
+                }
+                if empty > 0 {
+                    rank.push(char::from(b'0' + empty));
+                }
+                rank
+            })
+            .collect();
+        ranks.join("/")
+    }
+
+    /// Checks that the board is in a state that could occur in a game of chess.
+    ///
+    /// # Errors
+    /// * Returns [`BoardError::KingCount`] if either color does not have exactly one king.
+    /// * Returns [`BoardError::PieceCount`] if either color has more than 16 pieces.
+    /// * Returns [`BoardError::PawnOnBackRank`] if a pawn is on the first or last rank, where it could never have moved to.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let mut b = Board::new();
+    /// assert!(b.validate().is_ok());
+    /// b.take_piece(Position::new(4, 7).unwrap()).unwrap();
+    /// assert!(b.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), BoardError> {
+        for color in [Color::White, Color::Black] {
+            let kings = self
+                .pieces_of(color)
+                .iter()
+                .filter(|(_, piece)| piece.piece_type == PieceType::King)
+                .count();
+            if kings != 1 {
+                warn!("{color:?} has {kings} kings");
+                return Err(BoardError::KingCount(color, kings));
+            }
+            let pieces = self.pieces_of(color).len();
+            if pieces > 16 {
+                warn!("{color:?} has {pieces} pieces");
+                return Err(BoardError::PieceCount(color, pieces));
+            }
+        }
+        if let Some((position, _)) = self.iter_pieces().find(|(position, piece)| {
+            piece.piece_type == PieceType::Pawn && (position.y == 0 || position.y == 7)
+        }) {
+            warn!("Pawn on back rank at {position}");
+            return Err(BoardError::PawnOnBackRank(position));
+        }
+        Ok(())
+    }
+
     /// Moves piece from `from_position` to `to_position`.
     ///
     /// Does not check if move is possible.
@@ -63,11 +271,7 @@ impl Board {
             return Err(PieceError::Occupied(to_position, piece.piece_type));
         }
 
-        let Some(mut piece) = self[from_position] else {
-            return Err(PieceError::NotFound(
-                from_position,
-            ));
-        };
+        let mut piece = self.piece_at(from_position)?;
         piece.moved = true;
         self[from_position] = Some(piece);
         self[to_position] = self[from_position];
@@ -99,6 +303,112 @@ impl Board {
         }
     }
 
+    /// Castles by moving the king and rook together, marking both as moved.
+    ///
+    /// Either piece may move onto the square the other started on. The board is left unchanged if the castle cannot be made.
+    /// Does not check if castling is allowed.
+    ///
+    /// # Parameters
+    /// * `king_movement`: The movement of the king.
+    /// * `rook_movement`: The movement of the rook.
+    ///
+    /// # Errors
+    /// * Returns [`PieceError::NotFound`] error if there is no king at the king's starting square, or no rook of the same color at the rook's starting square.
+    /// * Returns [`PieceError::Occupied`] error if either destination is occupied by another piece.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action, mailbox::*}, piece::*};
+    ///
+    /// let mut b = Board::from_fen("4k3/8/8/8/8/8/8/4K2R").unwrap();
+    /// b.castle(
+    ///     action::Move { from_position: Position::new(4, 0).unwrap(), to_position: Position::new(6, 0).unwrap() },
+    ///     action::Move { from_position: Position::new(7, 0).unwrap(), to_position: Position::new(5, 0).unwrap() },
+    /// ).unwrap();
+    /// assert_eq!(b.king_position(Color::White), Some(Position::new(6, 0).unwrap()));
+    /// ```
+    pub fn castle(
+        &mut self,
+        king_movement: action::Move,
+        rook_movement: action::Move,
+    ) -> Result<(), PieceError> {
+        info!("Castling with {king_movement:?} and {rook_movement:?}");
+        let king = self[king_movement.from_position]
+            .filter(|piece| piece.piece_type == PieceType::King)
+            .ok_or(PieceError::NotFound(king_movement.from_position))?;
+        let rook = self[rook_movement.from_position]
+            .filter(|piece| piece.piece_type == PieceType::Rook && piece.color == king.color)
+            .ok_or(PieceError::NotFound(rook_movement.from_position))?;
+        for to_position in [king_movement.to_position, rook_movement.to_position] {
+            if let Some(piece) = self[to_position] {
+                if to_position != king_movement.from_position
+                    && to_position != rook_movement.from_position
+                {
+                    return Err(PieceError::Occupied(to_position, piece.piece_type));
+                }
+            }
+        }
+        self[king_movement.from_position] = None;
+        self[rook_movement.from_position] = None;
+        self[king_movement.to_position] = Some(Piece {
+            moved: true,
+            ..king
+        });
+        self[rook_movement.to_position] = Some(Piece {
+            moved: true,
+            ..rook
+        });
+        Ok(())
+    }
+
+    /// Removes every piece outside the rectangle with corners `min` and `max`, inclusive.
+    ///
+    /// Useful for building exercises on part of the board. Corners can be given in any order.
+    ///
+    /// # Parameters
+    /// * `min`: One corner of the rectangle to keep.
+    /// * `max`: The opposite corner of the rectangle to keep.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let mut b = Board::new();
+    /// b.clear_outside(Position::new(0, 0).unwrap(), Position::new(3, 3).unwrap());
+    /// assert_eq!(b.iter_pieces().count(), 8);
+    /// ```
+    pub fn clear_outside(&mut self, min: Position, max: Position) {
+        let x_range = min.x.min(max.x)..=min.x.max(max.x);
+        let y_range = min.y.min(max.y)..=min.y.max(max.y);
+        for y in 0..8 {
+            for x in 0..8 {
+                if !x_range.contains(&x) || !y_range.contains(&y) {
+                    self[Position { x, y }] = None;
+                }
+            }
+        }
+    }
+
+    /// Returns the board reflected vertically with every piece's color swapped.
+    ///
+    /// A piece on rank r is moved to rank 7 - r, so the position is the same but from the other side's point of view.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// let b = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3").unwrap();
+    /// assert_eq!(b.color_flipped(), Board::from_fen("4k3/4p3/8/8/8/8/8/4K3").unwrap());
+    /// ```
+    #[must_use]
+    pub fn color_flipped(&self) -> Board {
+        let mut board = Board::empty();
+        for (position, piece) in self.iter_pieces() {
+            board[position.mirror_rank()] = Some(Piece {
+                color: piece.color.opposite(),
+                ..piece
+            });
+        }
+        board
+    }
+
     /// Takes in the position of a piece, returns all possible positions it could move to.
     ///
     /// Order of returned vector is arbitrary, and should not be relied on (if checking against another vector for equality, should be sorted).
@@ -151,683 +461,4707 @@ impl Board {
         })
     }
 
-    /// Checks directions and returns vector of possible positions.
+    /// Returns a copy of every square on the board, for copying the board into other structures in one go.
+    ///
+    /// Squares are in the same order as [`Board::iter_pieces`], rank by rank from A1 to H8, so the square at `(x, y)` is at index `y * 8 + x`.
+    /// The array is a copy, so changing it does not change the board.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let squares = Board::new().pieces_array();
+    /// assert_eq!(squares[0], Some(Piece::new(Color::White, PieceType::Rook)));
+    /// assert_eq!(squares[60], Some(Piece::new(Color::Black, PieceType::King)));
+    /// assert_eq!(squares.iter().flatten().count(), 32);
+    /// ```
+    #[must_use]
+    pub fn pieces_array(&self) -> [Option<Piece>; 64] {
+        let mut squares = [None; 64];
+        let positions = (0..8).flat_map(|y| (0..8).map(move |x| Position { x, y }));
+        for (square, position) in squares.iter_mut().zip(positions) {
+            *square = self[position];
+        }
+        squares
+    }
+
+    /// Returns an iterator over every piece on the board along with its position.
+    ///
+    /// Pieces are yielded rank by rank, starting from A1 and ending at H8.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.iter_pieces().count(), 32);
+    /// ```
+    pub fn iter_pieces(&self) -> impl Iterator<Item = (Position, Piece)> + '_ {
+        (0..8)
+            .flat_map(|y| (0..8).map(move |x| Position { x, y }))
+            .filter_map(|position| self[position].map(|piece| (position, piece)))
+    }
+
+    /// Returns an iterator over every square on `rank`, from the a-file to the h-file, along with whatever is on it.
     ///
     /// # Parameters
-    /// * `position`: The position to check directions from.
-    /// * `directions`: Which directions to check. Order does not matter.
-    /// * `color`: Which color the piece being checked is (to determine which pieces can be taken).
-    fn check_directions(
+    /// * `rank`: The rank to scan, where 0 is the first rank.
+    /// # Errors
+    /// * Returns [`PositionOutOfBounds`] if `rank` is not less than 8.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.rank(1).unwrap().filter(|(_, piece)| piece.is_some()).count(), 8);
+    /// assert!(b.rank(4).unwrap().all(|(_, piece)| piece.is_none()));
+    /// assert!(b.rank(8).is_err());
+    /// ```
+    pub fn rank(
         &self,
-        position: Position,
-        directions: Vec<Direction>,
-        color: Color,
-    ) -> Vec<Position> {
-        debug!("Checking directions {directions:?} for piece at {position} with color {color:?}");
-        let mut out = vec![];
-        for direction in directions {
-            out.append(&mut self.check_direction(position, direction, color));
+        rank: u8,
+    ) -> Result<impl Iterator<Item = (Position, Option<Piece>)> + '_, PositionOutOfBounds> {
+        if rank >= 8 {
+            return Err(PositionOutOfBounds(0, isize::from(rank)));
         }
-        out
+        Ok((0..8).map(move |x| {
+            let position = Position { x, y: rank };
+            (position, self[position])
+        }))
     }
 
-    /// Checks direction and returns vector of possible positions.
+    /// Returns an iterator over every square on `file`, from the first rank to the eighth, along with whatever is on it.
     ///
     /// # Parameters
-    /// * `position`: The position to check the direction from.
-    /// * `direction`: Which direction to check. Order does not matter.
-    /// * `color`: Which color the piece being checked is (to determine which pieces can be taken).
-    fn check_direction(
+    /// * `file`: The file to scan, where 0 is the a-file.
+    /// # Errors
+    /// * Returns [`PositionOutOfBounds`] if `file` is not less than 8.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/8/8/4P3/4P3/4K3").unwrap();
+    /// let pawns = b
+    ///     .file(4)
+    ///     .unwrap()
+    ///     .filter(|(_, piece)| piece.is_some_and(|piece| piece.piece_type == PieceType::Pawn))
+    ///     .count();
+    /// assert_eq!(pawns, 2);
+    /// assert!(b.file(8).is_err());
+    /// ```
+    pub fn file(
         &self,
-        mut position: Position,
-        direction: Direction,
-        color: Color,
-    ) -> Vec<Position> {
-        debug!("Checking direction {direction:?} for piece at {position} with color {color:?}");
-        let mut positions: Vec<Position> = vec![];
-        let offset = match direction {
-            Direction::N => Offset { x: 0, y: 1 },
-            Direction::NE => Offset { x: 1, y: 1 },
-            Direction::E => Offset { x: 1, y: 0 },
-            Direction::SE => Offset { x: 1, y: -1 },
-            Direction::S => Offset { x: 0, y: -1 },
-            Direction::SW => Offset { x: -1, y: -1 },
-            Direction::W => Offset { x: -1, y: 0 },
-            Direction::NW => Offset { x: -1, y: 1 },
-        };
-        loop {
-            position = if let Ok(position) = position + offset {
-                position
-            } else {
-                break;
-            };
-            let Some(piece) = self[position] else {
-                positions.push(position);
-                continue;
-            };
-            if piece.color == color {
-                trace!("Reached piece of own color at {position}");
-                return positions;
-            }
-            trace!("Reached piece of opposite color at {position}");
-            positions.push(position);
-            return positions;
+        file: u8,
+    ) -> Result<impl Iterator<Item = (Position, Option<Piece>)> + '_, PositionOutOfBounds> {
+        if file >= 8 {
+            return Err(PositionOutOfBounds(isize::from(file), 0));
         }
-        trace!("Reached edge of board at {position}");
-        positions
+        Ok((0..8).map(move |y| {
+            let position = Position { x: file, y };
+            (position, self[position])
+        }))
     }
 
-    /// Returns vector of possible positions pawn could move to.
+    /// Returns whether `other` has the same piece type and color on every square as this board.
+    ///
+    /// Unlike the derived [`PartialEq`], the `moved` flag of each piece is ignored, so boards reached by different move orders compare equal.
+    /// Castling rights and en passant are not part of the board, so must be compared separately when checking for repetition.
     ///
     /// # Parameters
-    /// * `position`: The postition to check movement from.
-    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
-    /// * `moved`: Whether the pawn has been moved.
-    fn check_pawn(&self, position: Position, color: Color, moved: bool) -> Vec<Position> {
-        let mut positions = vec![];
-        if !moved {
-            if let Ok(position) = position
-                + (Offset {
-                    x: 0,
-                    y: 2 * color as i8,
-                })
-            {
-                if self.check_position(position, color, false, false) {
-                    positions.push(position);
-                };
-            };
-        };
-        if let Ok(position) = position
-            + (Offset {
-                x: 0,
-                y: color as i8,
-            })
-        {
-            if self.check_position(position, color, false, false) {
-                positions.push(position);
-            };
-        };
-        if let Ok(position) = position
-            + (Offset {
-                x: 1,
-                y: color as i8,
-            })
-        {
-            if self.check_position(position, color, true, true) {
-                positions.push(position);
-            };
-        };
-        if let Ok(position) = position
-            + (Offset {
-                x: -1,
-                y: color as i8,
+    /// * `other`: The board to compare with.
+    ///
+    /// ```
+    /// use chess_lib::board::{*, mailbox::*};
+    ///
+    /// let b = Board::new();
+    /// let mut other = Board::new();
+    /// other.move_piece(Position::new(6, 0).unwrap(), Position::new(5, 2).unwrap()).unwrap();
+    /// other.move_piece(Position::new(5, 2).unwrap(), Position::new(6, 0).unwrap()).unwrap();
+    /// assert_ne!(b, other);
+    /// assert!(b.same_position(&other));
+    /// ```
+    #[must_use]
+    pub fn same_position(&self, other: &Board) -> bool {
+        (0..8).all(|y| {
+            (0..8).all(|x| {
+                let position = Position { x, y };
+                let kind =
+                    |piece: Option<Piece>| piece.map(|piece| (piece.color, piece.piece_type));
+                kind(self[position]) == kind(other[position])
             })
-        {
-            if self.check_position(position, color, true, true) {
-                positions.push(position);
-            };
-        };
+        })
+    }
 
-        positions
+    /// Returns the position of every piece of `color`, along with the piece itself.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pieces to return.
+    #[must_use]
+    pub fn pieces_of(&self, color: Color) -> Vec<(Position, Piece)> {
+        self.iter_pieces()
+            .filter(|(_, piece)| piece.color == color)
+            .collect()
     }
 
-    /// Returns vector of possible positions knight could move to.
+    /// Returns the type of every piece `color` has on the board, sorted from pawns to king.
     ///
     /// # Parameters
-    /// * `position`: The position to check movement from.
-    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
-    fn check_knight(&self, position: Position, color: Color) -> Vec<Position> {
-        let mut positions = vec![];
-        let offsets = [
-            Offset { x: 2, y: 1 },
-            Offset { x: -2, y: 1 },
-            Offset { x: -2, y: -1 },
-            Offset { x: 2, y: -1 },
-            Offset { x: 1, y: 2 },
-            Offset { x: -1, y: 2 },
-            Offset { x: -1, y: -2 },
-            Offset { x: 1, y: -2 },
-        ];
-        for offset in offsets {
-            if let Ok(position) = position + offset {
-                if self.check_position(position, color, true, false) {
-                    positions.push(position);
-                }
-            }
-        }
-        positions
+    /// * `color`: The color of the pieces to list.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/8/8/8/1P6/R3K3").unwrap();
+    /// assert_eq!(
+    ///     b.material_list(Color::White),
+    ///     vec![PieceType::Pawn, PieceType::Rook, PieceType::King]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn material_list(&self, color: Color) -> Vec<PieceType> {
+        let mut material: Vec<PieceType> = self
+            .pieces_of(color)
+            .into_iter()
+            .map(|(_, piece)| piece.piece_type)
+            .collect();
+        material.sort();
+        material
     }
 
-    /// Returns vector of possible positions king could move to.
+    /// Returns the types of `color`'s pieces that are on `start` but missing from this board, sorted from pawns to king.
+    ///
+    /// Pieces are matched by type rather than position, so a promoted pawn shows as a captured pawn unless the piece it promoted to has since been taken.
     ///
-    /// Does NOT check for checks.
     /// # Parameters
-    /// * `position`: The position to check movement from.
-    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
-    fn check_king(&self, position: Position, color: Color) -> Vec<Position> {
-        let mut positions = vec![];
-        let offsets = [
-            Offset { x: 1, y: 1 },
-            Offset { x: -1, y: 1 },
-            Offset { x: -1, y: -1 },
-            Offset { x: 1, y: -1 },
-            Offset { x: 1, y: 0 },
-            Offset { x: -1, y: 0 },
-            Offset { x: 0, y: -1 },
-            Offset { x: 0, y: 1 },
-        ];
-        for offset in offsets {
-            if let Ok(position) = position + offset {
-                if self.check_position(position, color, true, false) {
-                    positions.push(position);
+    /// * `start`: The board the game started from, usually [`Board::new`].
+    /// * `color`: The color of the captured pieces.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("rnb1kbnr/ppp1pppp/8/8/8/8/PPP1PPPP/RNBQKBNR").unwrap();
+    /// assert_eq!(b.captured_pieces(&Board::new(), Color::White), vec![PieceType::Pawn]);
+    /// assert_eq!(
+    ///     b.captured_pieces(&Board::new(), Color::Black),
+    ///     vec![PieceType::Pawn, PieceType::Queen]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn captured_pieces(&self, start: &Board, color: Color) -> Vec<PieceType> {
+        let mut remaining = self.material_list(color);
+        start
+            .material_list(color)
+            .into_iter()
+            .filter(|piece_type| {
+                let Some(index) = remaining
+                    .iter()
+                    .position(|remaining| remaining == piece_type)
+                else {
+                    return true;
+                };
+                remaining.remove(index);
+                false
+            })
+            .collect()
+    }
+
+    /// Returns the position of every piece of `color`, without the pieces themselves.
+    ///
+    /// Positions are ordered from A1 to H8, rank by rank.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pieces to return.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::new();
+    /// let squares = b.occupied_squares(Color::Black);
+    /// assert_eq!(squares.len(), 16);
+    /// assert_eq!(squares[0], Position::new(0, 6).unwrap());
+    /// ```
+    #[must_use]
+    pub fn occupied_squares(&self, color: Color) -> Vec<Position> {
+        self.iter_pieces()
+            .filter(|(_, piece)| piece.color == color)
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Returns an iterator over the squares along `direction` from `from`, stopping at the edge of the board.
+    ///
+    /// `from` itself is not included, and squares are yielded whether or not they are occupied.
+    ///
+    /// # Parameters
+    /// * `from`: The position the ray starts from.
+    /// * `direction`: The direction the ray travels in.
+    ///
+    /// ```
+    /// use chess_lib::board::{*, mailbox::*};
+    ///
+    /// let b = Board::new();
+    /// let ray: Vec<Position> = b.ray(Position::new(5, 2).unwrap(), Direction::SE).collect();
+    /// assert_eq!(ray, vec![Position::new(6, 1).unwrap(), Position::new(7, 0).unwrap()]);
+    /// ```
+    #[allow(clippy::unused_self)]
+    pub fn ray(&self, from: Position, direction: Direction) -> impl Iterator<Item = Position> {
+        let offset = direction.offset();
+        std::iter::successors(Some(from), move |&current| (current + offset).ok()).skip(1)
+    }
+
+    /// Returns the positions of all pieces of `color` that attack `position`.
+    ///
+    /// A piece attacks a square if it could take an enemy piece standing on it, so pawns only attack diagonally and the square does not need to be occupied.
+    /// Any piece already on `position` is ignored, so passing the color of that piece returns its defenders.
+    ///
+    /// # Parameters
+    /// * `position`: The position being attacked.
+    /// * `color`: The color of the attacking pieces.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::new();
+    /// let mut defenders = b.attackers_of(Position::new(3, 1).unwrap(), Color::White);
+    /// defenders.sort();
+    /// assert_eq!(defenders, vec![
+    ///     Position::new(1, 0).unwrap(),
+    ///     Position::new(2, 0).unwrap(),
+    ///     Position::new(3, 0).unwrap(),
+    ///     Position::new(4, 0).unwrap(),
+    /// ]);
+    /// assert!(b.attackers_of(Position::new(3, 1).unwrap(), Color::Black).is_empty());
+    /// ```
+    #[must_use]
+    pub fn attackers_of(&self, position: Position, color: Color) -> Vec<Position> {
+        use Direction::{E, N, NE, NW, S, SE, SW, W};
+        debug!("Finding {color:?} attackers of {position}");
+        let mut attackers = vec![];
+        for direction in [N, NE, E, SE, S, SW, W, NW] {
+            let offset = direction.offset();
+            let diagonal = offset.x != 0 && offset.y != 0;
+            for (current, distance) in self.ray(position, direction).zip(1..) {
+                let Some(piece) = self[current] else {
+                    continue;
+                };
+                if piece.color == color
+                    && match piece.piece_type {
+                        PieceType::Queen => true,
+                        PieceType::Rook => !diagonal,
+                        PieceType::Bishop => diagonal,
+                        PieceType::King => distance == 1,
+                        // Looking back from the target, an attacking pawn sits one rank behind it from its own side.
+                        PieceType::Pawn => {
+                            distance == 1 && diagonal && offset.y == -color.forward_offset().y
+                        }
+                        PieceType::Knight => false,
+                    }
+                {
+                    trace!("Found attacker at {current}");
+                    attackers.push(current);
+                }
+                break;
+            }
+        }
+        for offset in KNIGHT_OFFSETS {
+            if let Ok(current) = position + offset {
+                if let Some(piece) = self[current] {
+                    if piece.color == color && piece.piece_type == PieceType::Knight {
+                        trace!("Found attacker at {current}");
+                        attackers.push(current);
+                    }
+                }
+            }
+        }
+        attackers
+    }
+
+    /// Returns the positions of `color`'s sliding pieces that attack `position` through exactly one other piece.
+    ///
+    /// The piece in between can be of either color, so this finds batteries (e.g. a rook behind a rook) as well as skewers and pins.
+    /// Any piece already on `position` is ignored, as in [`Board::attackers_of`].
+    ///
+    /// # Parameters
+    /// * `position`: The position being attacked.
+    /// * `color`: The color of the attacking pieces.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("3rk3/8/8/8/8/8/3R4/3RK3").unwrap();
+    /// let d8 = Position::new(3, 7).unwrap();
+    /// assert_eq!(b.attackers_of(d8, Color::White), vec![Position::new(3, 1).unwrap()]);
+    /// assert_eq!(b.xray_attackers(d8, Color::White), vec![Position::new(3, 0).unwrap()]);
+    /// ```
+    #[must_use]
+    pub fn xray_attackers(&self, position: Position, color: Color) -> Vec<Position> {
+        use Direction::{E, N, NE, NW, S, SE, SW, W};
+        debug!("Finding {color:?} x-ray attackers of {position}");
+        let mut attackers = vec![];
+        for direction in [N, NE, E, SE, S, SW, W, NW] {
+            let offset = direction.offset();
+            let diagonal = offset.x != 0 && offset.y != 0;
+            let mut pieces = self
+                .ray(position, direction)
+                .filter_map(|current| self[current].map(|piece| (current, piece)));
+            let Some((current, piece)) = pieces.nth(1) else {
+                continue;
+            };
+            if piece.color == color
+                && match piece.piece_type {
+                    PieceType::Queen => true,
+                    PieceType::Rook => !diagonal,
+                    PieceType::Bishop => diagonal,
+                    _ => false,
+                }
+            {
+                trace!("Found x-ray attacker at {current}");
+                attackers.push(current);
+            }
+        }
+        attackers
+    }
+
+    /// Returns the positions of `color`'s pieces that are hanging.
+    ///
+    /// A piece is hanging if it is attacked by the enemy and is either undefended, or can be taken by an enemy piece worth less than itself (so a defended queen attacked by a pawn is still hanging).
+    /// Kings are never reported, since an attacked king is in check rather than hanging.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pieces to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let mut b = Board::new();
+    /// assert!(b.hanging_pieces(Color::Black).is_empty());
+    /// b.move_piece(Position::new(6, 7).unwrap(), Position::new(3, 2).unwrap()).unwrap();
+    /// assert_eq!(b.hanging_pieces(Color::Black), vec![Position::new(3, 2).unwrap()]);
+    /// ```
+    #[must_use]
+    pub fn hanging_pieces(&self, color: Color) -> Vec<Position> {
+        debug!("Finding hanging pieces for {color:?}");
+        self.pieces_of(color)
+            .into_iter()
+            .filter(|&(position, piece)| {
+                if piece.piece_type == PieceType::King {
+                    return false;
+                }
+                let Some(cheapest_attacker) = self
+                    .attackers_of(position, color.opposite())
+                    .into_iter()
+                    .filter_map(|attacker| self[attacker])
+                    .map(|attacker| attacker.piece_type.value())
+                    .min()
+                else {
+                    return false;
+                };
+                cheapest_attacker < piece.piece_type.value()
+                    || self.attackers_of(position, color).is_empty()
+            })
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Returns every absolute pin against `color`, i.e. pieces that cannot leave the line between their king and an enemy slider without exposing the king to check.
+    ///
+    /// Each pin is returned as the position of the pinned piece, the position of the king it is pinned to, and the direction from the king towards the pinning piece.
+    /// Relative pins, to pieces other than the king, are not included. Returns an empty vector if `color` has no king.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pinned pieces.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3").unwrap();
+    /// assert_eq!(
+    ///     b.absolute_pins(Color::White),
+    ///     vec![(Position::new(4, 1).unwrap(), Position::new(4, 0).unwrap(), Direction::N)]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn absolute_pins(&self, color: Color) -> Vec<(Position, Position, Direction)> {
+        use Direction::{E, N, NE, NW, S, SE, SW, W};
+        let Some(king) = self.king_position(color) else {
+            return vec![];
+        };
+        let mut pins = vec![];
+        for direction in [N, NE, E, SE, S, SW, W, NW] {
+            let offset = direction.offset();
+            let diagonal = offset.x != 0 && offset.y != 0;
+            let mut pinned = None;
+            for current in self.ray(king, direction) {
+                let Some(piece) = self[current] else {
+                    continue;
+                };
+                if piece.color == color {
+                    if pinned.is_some() {
+                        break;
+                    }
+                    pinned = Some(current);
+                    continue;
+                }
+                let pins_along = match piece.piece_type {
+                    PieceType::Queen => true,
+                    PieceType::Rook => !diagonal,
+                    PieceType::Bishop => diagonal,
+                    _ => false,
+                };
+                if let Some(pinned) = pinned.filter(|_| pins_along) {
+                    trace!("Found piece at {pinned} pinned by {current}");
+                    pins.push((pinned, king, direction));
                 }
+                break;
             }
         }
+        pins
+    }
+
+    /// Takes in the position of a piece, returns the positions it could move to without breaking an absolute pin.
+    ///
+    /// A pinned piece may only move along the line between its king and the pinning piece, including taking the pinning piece. A piece that is not pinned is not restricted, so all of [`Board::check_positions`] is returned.
+    /// Returns an empty vector if there is no piece at `position`.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the piece to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3").unwrap();
+    /// assert!(b.pinned_piece_moves(Position::new(4, 1).unwrap()).is_empty());
+    /// ```
+    #[must_use]
+    pub fn pinned_piece_moves(&self, position: Position) -> Vec<Position> {
+        let Some(piece) = self[position] else {
+            return vec![];
+        };
+        let Ok(mut positions) = self.check_positions(position) else {
+            return vec![];
+        };
+        let Some(&(_, _, direction)) = self
+            .absolute_pins(piece.color)
+            .iter()
+            .find(|(pinned, _, _)| *pinned == position)
+        else {
+            return positions;
+        };
+        let mut line = self.check_direction(position, direction, piece.color);
+        line.append(&mut self.check_direction(position, direction.opposite(), piece.color));
+        positions.retain(|to_position| line.contains(to_position));
         positions
     }
 
-    /// Checks whether a position can be moved to.
+    /// Returns the material `by_color` can expect to win by starting a sequence of captures on `target`, using static exchange evaluation.
+    ///
+    /// Each side captures with its least valuable attacker, and can stop capturing whenever continuing would lose material. The first capture is always made, so the result can be negative.
+    /// Pieces revealed behind an attacker once it has captured join the exchange, see [`Board::xray_attackers`].
+    /// Returns 0 if there is no enemy piece on `target` or `by_color` cannot take it.
     ///
     /// # Parameters
-    /// * `position`: The position to check movement from.
-    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
-    /// * `can_take`: Whether other pieces can be taken.
-    /// * `must_take`: Whether other pieces must be taken.
-    fn check_position(
-        &self,
-        position: Position,
-        color: Color,
-        can_take: bool,
-        must_take: bool,
-    ) -> bool {
-        debug!("Checking {position}");
-        let  Some(piece) = self[position] else {
-            return !must_take; // Return true for empty square unless must take is true.
+    /// * `target`: The position of the piece being captured.
+    /// * `by_color`: The color making the first capture.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// // Pawn takes an undefended knight
+    /// let b = Board::from_fen("4k3/8/8/3n4/4P3/8/8/4K3").unwrap();
+    /// assert_eq!(b.see(Position::new(3, 4).unwrap(), Color::White), 300);
+    /// // Queen takes a pawn defended by a pawn
+    /// let b = Board::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3").unwrap();
+    /// assert_eq!(b.see(Position::new(3, 4).unwrap(), Color::White), -800);
+    /// ```
+    #[must_use]
+    pub fn see(&self, target: Position, by_color: Color) -> i32 {
+        let Some(victim) = self[target].filter(|piece| piece.color != by_color) else {
+            return 0;
         };
-        if piece.color == color {
-            false
-        } else {
-            can_take // Return true if piece can take
+        let mut board = self.clone();
+        let mut captured_values = vec![];
+        let mut on_target = victim.piece_type.value();
+        let mut side = by_color;
+        while let Some((attacker_position, attacker_value)) = board
+            .attackers_of(target, side)
+            .into_iter()
+            .filter_map(|position| {
+                board[position].map(|piece| (position, piece.piece_type.value()))
+            })
+            .min_by_key(|&(_, value)| value)
+        {
+            trace!("{side:?} takes {on_target} on {target} from {attacker_position}");
+            captured_values.push(on_target);
+            on_target = attacker_value;
+            board[attacker_position] = None;
+            side = side.opposite();
         }
+        let Some((first, rest)) = captured_values.split_first() else {
+            return 0;
+        };
+        // Work backwards, each side only captures if it gains material
+        first
+            - rest
+                .iter()
+                .rev()
+                .fold(0, |gain, &value| (value - gain).max(0))
     }
-}
 
-impl Default for Board {
-    fn default() -> Self {
-        Self::new()
+    /// Returns whether the piece at `position` is trapped, i.e. has no safe square to move to.
+    ///
+    /// A square is safe if it is not attacked by the enemy, or it is defended and every enemy attacker is worth more than the piece.
+    /// Taking an enemy piece worth at least as much as the piece is always safe, since any recapture is at worst an even trade.
+    /// Returns false if there is no piece at `position`.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the piece to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("2k5/B1p5/1p6/8/8/8/8/4K3").unwrap();
+    /// assert!(b.is_trapped(Position::new(0, 6).unwrap()));
+    /// assert!(!Board::new().is_trapped(Position::new(1, 0).unwrap()));
+    /// ```
+    #[must_use]
+    pub fn is_trapped(&self, position: Position) -> bool {
+        self[position].is_some() && self.safe_squares(position).is_empty()
     }
-}
 
-impl Index<Position> for Board {
-    type Output = Option<Piece>;
+    /// Statically evaluates the board from the perspective of `for_color`, in centipawns.
+    ///
+    /// Each piece scores its material value plus a bonus for the square it stands on, see [`piece_square::PIECE_SQUARE_TABLES`]. Kings only score their square bonus.
+    /// The evaluation is `for_color`'s score minus the opponent's, so it is positive when `for_color` is ahead.
+    ///
+    /// # Parameters
+    /// * `for_color`: The color to evaluate for.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// assert_eq!(Board::new().evaluate(Color::White), 0);
+    /// let b = Board::from_fen("4k3/8/8/8/8/8/8/3QK3").unwrap();
+    /// assert!(b.evaluate(Color::White) > 800);
+    /// ```
+    #[must_use]
+    pub fn evaluate(&self, for_color: Color) -> i32 {
+        self.iter_pieces()
+            .map(|(position, piece)| {
+                let material = if piece.piece_type == PieceType::King {
+                    0
+                } else {
+                    piece.piece_type.value()
+                };
+                let score = material + piece_square::value(piece, position);
+                if piece.color == for_color {
+                    score
+                } else {
+                    -score
+                }
+            })
+            .sum()
+    }
 
-    #[inline]
-    fn index(&self, index: Position) -> &Self::Output {
-        &self.pieces[(index.y.into(), index.x.into())]
+    /// Returns how active `color`'s pieces are, the number of safe squares each piece can move to weighted by its type.
+    ///
+    /// Safe squares are as described in [`Board::is_trapped`]. Minor pieces are weighted most, since a square is worth more to a knight or bishop than to a queen, and kings are not counted.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pieces to score.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.activity_score(Color::White), b.activity_score(Color::Black));
+    /// ```
+    #[must_use]
+    pub fn activity_score(&self, color: Color) -> i32 {
+        self.pieces_of(color)
+            .into_iter()
+            .map(|(position, piece)| {
+                let weight = match piece.piece_type {
+                    PieceType::Pawn | PieceType::Queen => 1,
+                    PieceType::Knight => 4,
+                    PieceType::Bishop => 3,
+                    PieceType::Rook => 2,
+                    PieceType::King => 0,
+                };
+                weight * i32::try_from(self.safe_squares(position).len()).unwrap_or(i32::MAX)
+            })
+            .sum()
     }
-}
 
-impl IndexMut<Position> for Board {
-    #[inline]
-    fn index_mut(&mut self, index: Position) -> &mut Self::Output {
-        &mut self.pieces[(index.y.into(), index.x.into())]
+    /// Returns the piece at `position`, for when there must be a piece there.
+    ///
+    /// Same as indexing the board, but an empty square is an error so callers can use `?`.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the piece.
+    /// # Errors
+    /// * Returns [`PieceError::NotFound`] if the square is empty.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.piece_at(Position::new(3, 0).unwrap()).unwrap(), Piece::new(Color::White, PieceType::Queen));
+    /// assert!(b.piece_at(Position::new(3, 3).unwrap()).is_err());
+    /// ```
+    pub fn piece_at(&self, position: Position) -> Result<Piece, PieceError> {
+        self[position].ok_or(PieceError::NotFound(position))
     }
-}
 
-#[cfg(test)]
-mod position_tests {
-    use super::*;
+    /// Returns the position of `color`'s king, or `None` if it has no king.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.king_position(Color::Black), Some(Position::new(4, 7).unwrap()));
+    /// ```
+    #[must_use]
+    pub fn king_position(&self, color: Color) -> Option<Position> {
+        self.iter_pieces()
+            .find(|(_, piece)| piece.color == color && piece.piece_type == PieceType::King)
+            .map(|(position, _)| position)
+    }
 
-    #[test]
-    fn test_offset_positive_n() {
-        assert_eq!(
-            Position { x: 6, y: 6 },
-            (Position { x: 6, y: 5 } + Offset { x: 0, y: 1 }).unwrap()
-        );
+    /// Returns the number of king moves between the two kings, or `None` if either king is missing.
+    ///
+    /// ```
+    /// use chess_lib::board::{*, mailbox::*};
+    ///
+    /// let mut b = Board::new();
+    /// assert_eq!(b.king_distance(), Some(7));
+    /// b.take_piece(Position::new(4, 7).unwrap()).unwrap();
+    /// assert_eq!(b.king_distance(), None);
+    /// ```
+    #[must_use]
+    pub fn king_distance(&self) -> Option<u8> {
+        let white = self.king_position(Color::White)?;
+        let black = self.king_position(Color::Black)?;
+        Some(white.distance(black))
     }
 
-    #[test]
-    fn test_offset_positive_ne() {
-        assert_eq!(
-            Position { x: 6, y: 6 },
-            (Position { x: 5, y: 5 } + Offset { x: 1, y: 1 }).unwrap()
-        );
+    /// Returns whether `color` has the opposition when it is `turn`'s move.
+    ///
+    /// The kings are in opposition when they are on the same rank, file or diagonal with an odd number of squares between them.
+    /// The side that does not have to move then holds the opposition, as the opponent's king must give way.
+    ///
+    /// # Parameters
+    /// * `color`: The color to check.
+    /// * `turn`: The color whose turn it is to move.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/4K3/8/8/8/8/8").unwrap();
+    /// assert!(b.has_opposition(Color::White, Color::Black));
+    /// assert!(!b.has_opposition(Color::Black, Color::Black));
+    /// let b = Board::from_fen("4k3/8/8/4K3/8/8/8/8").unwrap();
+    /// assert!(!b.has_opposition(Color::White, Color::Black));
+    /// ```
+    #[must_use]
+    pub fn has_opposition(&self, color: Color, turn: Color) -> bool {
+        if color == turn {
+            return false;
+        }
+        let (Some(king), Some(enemy_king)) = (
+            self.king_position(color),
+            self.king_position(color.opposite()),
+        ) else {
+            return false;
+        };
+        let dx = king.x.abs_diff(enemy_king.x);
+        let dy = king.y.abs_diff(enemy_king.y);
+        let aligned = dx == 0 || dy == 0 || dx == dy;
+        // An odd number of squares between the kings means an even distance
+        aligned && king.distance(enemy_king) % 2 == 0
     }
 
-    #[test]
-    fn test_offset_negative_s() {
-        assert_eq!(
-            Position { x: 6, y: 5 },
-            (Position { x: 6, y: 6 } + Offset { x: 0, y: -1 }).unwrap()
-        );
+    /// Returns whether a pawn of `by_color` attacks `position`.
+    ///
+    /// Only the two diagonal squares a pawn could attack from are checked, matching how [`Board::attackers_of`] treats pawns.
+    ///
+    /// # Parameters
+    /// * `position`: The position being attacked.
+    /// * `by_color`: The color of the attacking pawns.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3").unwrap();
+    /// assert!(b.is_attacked_by_pawn(Position::new(3, 4).unwrap(), Color::White));
+    /// assert!(b.is_attacked_by_pawn(Position::new(5, 4).unwrap(), Color::White));
+    /// assert!(!b.is_attacked_by_pawn(Position::new(4, 4).unwrap(), Color::White));
+    /// ```
+    #[must_use]
+    pub fn is_attacked_by_pawn(&self, position: Position, by_color: Color) -> bool {
+        let backward = -by_color.forward_offset().y;
+        [-1, 1].into_iter().any(|x| {
+            (position + Offset { x, y: backward }).is_ok_and(|square| {
+                self[square].is_some_and(|piece| {
+                    piece.color == by_color && piece.piece_type == PieceType::Pawn
+                })
+            })
+        })
+    }
+
+    /// Returns whether any piece of `by_color` attacks `position`.
+    ///
+    /// # Parameters
+    /// * `position`: The position being attacked.
+    /// * `by_color`: The color of the attacking pieces.
+    #[must_use]
+    pub fn is_square_attacked(&self, position: Position, by_color: Color) -> bool {
+        self.is_attacked_by_pawn(position, by_color)
+            || !self.attackers_of(position, by_color).is_empty()
+    }
+
+    /// Returns the positions `color`'s king can move to without stepping onto an attacked square.
+    ///
+    /// The king is taken off the board before checking for attacks, so it cannot hide from a slider by blocking the attack through its own square.
+    /// Castling is not included. Returns an empty vector if `color` has no king.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the king.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4r1k1/8/8/8/8/8/8/4K3").unwrap();
+    /// assert!(!b.safe_king_positions(Color::White).contains(&Position::new(4, 1).unwrap()));
+    /// ```
+    #[must_use]
+    pub fn safe_king_positions(&self, color: Color) -> Vec<Position> {
+        let Some(king) = self.king_position(color) else {
+            return vec![];
+        };
+        let mut board = self.clone();
+        board[king] = None;
+        self.check_king(king, color)
+            .into_iter()
+            .filter(|&position| !board.is_square_attacked(position, color.opposite()))
+            .collect()
+    }
+
+    /// Returns how many more white pieces than black pieces attack `square`, so positive means white controls it.
+    ///
+    /// # Parameters
+    /// * `square`: The position to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.square_control(Position::new(4, 2).unwrap()), 2);
+    /// assert_eq!(b.square_control(Position::new(4, 3).unwrap()), 0);
+    /// ```
+    #[must_use]
+    pub fn square_control(&self, square: Position) -> i32 {
+        let count =
+            |color| i32::try_from(self.attackers_of(square, color).len()).unwrap_or(i32::MAX);
+        count(Color::White) - count(Color::Black)
+    }
+
+    /// Returns whether `color`'s king is attacked.
+    ///
+    /// Returns false if `color` has no king.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the king to check.
+    #[must_use]
+    pub fn is_in_check(&self, color: Color) -> bool {
+        self.king_position(color)
+            .is_some_and(|position| self.is_square_attacked(position, color.opposite()))
+    }
+
+    /// Returns a score for how well `color`'s pawns shield its king.
+    ///
+    /// The result is a weighted score rather than a count of pawns. Friendly pawns within two ranks in front of the king, on its file and the files either side, are looked at.
+    /// Each file scores 2 for a pawn directly in front of the king, or 1 if its nearest pawn is two ranks in front, so a second pawn on the same file adds nothing and the most a king can score is 6.
+    /// An unmoved pawn shield, e.g. f2, g2 and h2 in front of a king on g1, scores the maximum, and pushing a shield pawn lowers the score.
+    /// Kings on an edge file have fewer shield squares. Returns 0 if `color` has no king.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the king to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/8/8/6P1/5P1P/6K1").unwrap();
+    /// assert_eq!(b.king_shield(Color::White), 5);
+    /// assert_eq!(b.king_shield(Color::Black), 0);
+    /// ```
+    #[must_use]
+    pub fn king_shield(&self, color: Color) -> u8 {
+        let Some(king_position) = self.king_position(color) else {
+            return 0;
+        };
+        let forward = color.forward_offset().y;
+        let is_pawn = |x, y| {
+            (king_position + Offset { x, y })
+                .ok()
+                .and_then(|position| self[position])
+                .is_some_and(|piece| piece.color == color && piece.piece_type == PieceType::Pawn)
+        };
+        let mut shield = 0;
+        for x in -1..=1 {
+            if is_pawn(x, forward) {
+                shield += 2;
+            } else if is_pawn(x, 2 * forward) {
+                shield += 1;
+            }
+        }
+        shield
+    }
+
+    /// Returns the files with no pawns of either color on them, where 0 is the a-file.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// let b = Board::from_fen("4k3/ppp1pppp/8/8/8/8/PPP1PPPP/4K3").unwrap();
+    /// assert_eq!(b.open_files(), vec![3]);
+    /// ```
+    #[must_use]
+    pub fn open_files(&self) -> Vec<u8> {
+        (0..8)
+            .filter(|&x| {
+                !self.file_has_pawn(x, Color::White) && !self.file_has_pawn(x, Color::Black)
+            })
+            .collect()
+    }
+
+    /// Returns the files with no pawns of `color` on them, where 0 is the a-file.
+    ///
+    /// Open files are included, since they have no pawns of either color.
+    /// [`Board::half_open_files`] is the same except that open files are left out, so the two differ exactly by [`Board::open_files`].
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pawns to look for.
+    #[must_use]
+    pub fn semi_open_files(&self, color: Color) -> Vec<u8> {
+        (0..8).filter(|&x| !self.file_has_pawn(x, color)).collect()
+    }
+
+    /// Returns the files with no pawns of `color` but at least one enemy pawn, where 0 is the a-file.
+    ///
+    /// Half-open and semi-open usually mean the same thing, but here they differ: unlike [`Board::semi_open_files`], open files are not included.
+    /// This keeps the two kinds apart for evaluation, e.g. a rook on an open file can be scored separately from one facing an enemy pawn.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pawns to look for.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/pppp1ppp/8/8/8/8/PPP2PPP/4K3").unwrap();
+    /// assert_eq!(b.half_open_files(Color::White), vec![3]);
+    /// assert!(b.half_open_files(Color::Black).is_empty());
+    /// ```
+    #[must_use]
+    pub fn half_open_files(&self, color: Color) -> Vec<u8> {
+        (0..8)
+            .filter(|&x| !self.file_has_pawn(x, color) && self.file_has_pawn(x, color.opposite()))
+            .collect()
+    }
+
+    /// Returns the positions of `color`'s passed pawns.
+    ///
+    /// A pawn is passed if there are no enemy pawns in front of it on its own file or the files either side, so no enemy pawn can stop it promoting.
+    /// Positions are ordered from A1 to H8, rank by rank.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pawns to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/p7/8/4P3/8/8/P7/4K3").unwrap();
+    /// assert_eq!(b.passed_pawns(Color::White), vec![Position::new(4, 4).unwrap()]);
+    /// ```
+    #[must_use]
+    pub fn passed_pawns(&self, color: Color) -> Vec<Position> {
+        self.pieces_of(color)
+            .into_iter()
+            .filter(|(_, piece)| piece.piece_type == PieceType::Pawn)
+            .map(|(position, _)| position)
+            .filter(|position| {
+                !self.iter_pieces().any(|(other, piece)| {
+                    let in_front = match color {
+                        Color::White => other.y > position.y,
+                        Color::Black => other.y < position.y,
+                    };
+                    piece.color != color
+                        && piece.piece_type == PieceType::Pawn
+                        && in_front
+                        && other.x.abs_diff(position.x) <= 1
+                })
+            })
+            .collect()
+    }
+
+    /// Returns each of `color`'s passed pawns along with the number of ranks it needs to advance to promote.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pawns to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/4P3/8/8/8/8/4K3").unwrap();
+    /// assert_eq!(b.passed_pawn_distance_to_promotion(Color::White), vec![(Position::new(4, 5).unwrap(), 2)]);
+    /// ```
+    #[must_use]
+    pub fn passed_pawn_distance_to_promotion(&self, color: Color) -> Vec<(Position, u8)> {
+        self.passed_pawns(color)
+            .into_iter()
+            .map(|position| {
+                let distance = match color {
+                    Color::White => 7 - position.y,
+                    Color::Black => position.y,
+                };
+                (position, distance)
+            })
+            .collect()
+    }
+
+    /// Returns the positions of `color`'s backward pawns.
+    ///
+    /// A pawn is backward if no friendly pawn on the files either side is level with or behind it, so it can never be defended by another pawn, and the square in front of it is attacked by an enemy pawn.
+    /// Positions are ordered from A1 to H8, rank by rank.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pawns to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/2p5/2P1P3/3P4/8/4K3").unwrap();
+    /// assert_eq!(b.backward_pawns(Color::White), vec![Position::new(3, 2).unwrap()]);
+    /// ```
+    #[must_use]
+    pub fn backward_pawns(&self, color: Color) -> Vec<Position> {
+        let pawns = self.pawn_positions(color);
+        let forward = color.forward_offset();
+        pawns
+            .iter()
+            .copied()
+            .filter(|position| {
+                let supported = pawns.iter().any(|other| {
+                    let level_or_behind = match color {
+                        Color::White => other.y <= position.y,
+                        Color::Black => other.y >= position.y,
+                    };
+                    other.x.abs_diff(position.x) == 1 && level_or_behind
+                });
+                let Ok(stop) = *position + forward else {
+                    return false;
+                };
+                let stop_attacked = [-1, 1].into_iter().any(|x| {
+                    (stop + Offset { x, ..forward }).is_ok_and(|square| {
+                        self[square].is_some_and(|piece| {
+                            piece.color != color && piece.piece_type == PieceType::Pawn
+                        })
+                    })
+                });
+                !supported && stop_attacked
+            })
+            .collect()
+    }
+
+    /// Returns `color`'s pawn chains, groups of pawns linked diagonally so each defends or is defended by the next.
+    ///
+    /// Pawns not diagonally next to another friendly pawn are not part of any chain and are left out.
+    /// Pawns in each chain are ordered from A1 to H8, rank by rank, and chains are ordered by their first pawn.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pawns to group.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/8/2P5/1P6/P6P/4K3").unwrap();
+    /// assert_eq!(
+    ///     b.pawn_chains(Color::White),
+    ///     vec![vec![
+    ///         Position::new(0, 1).unwrap(),
+    ///         Position::new(1, 2).unwrap(),
+    ///         Position::new(2, 3).unwrap(),
+    ///     ]]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn pawn_chains(&self, color: Color) -> Vec<Vec<Position>> {
+        let pawns = self.pawn_positions(color);
+        let mut visited = vec![];
+        let mut chains = vec![];
+        for &start in &pawns {
+            if visited.contains(&start) {
+                continue;
+            }
+            visited.push(start);
+            let mut chain = vec![start];
+            let mut i = 0;
+            while i < chain.len() {
+                let current = chain[i];
+                for &other in &pawns {
+                    if !visited.contains(&other)
+                        && other.x.abs_diff(current.x) == 1
+                        && other.y.abs_diff(current.y) == 1
+                    {
+                        visited.push(other);
+                        chain.push(other);
+                    }
+                }
+                i += 1;
+            }
+            if chain.len() > 1 {
+                chain.sort_by_key(|position| (position.y, position.x));
+                chains.push(chain);
+            }
+        }
+        chains
+    }
+
+    /// Returns the squares from which a `color` piece of type `piece_type` would give check to the enemy king.
+    ///
+    /// Found by generating the attacks of a `piece_type` from the enemy king's square, since a piece attacks the king exactly when the king would attack it as that piece.
+    /// Squares occupied by `color`'s own pieces are not included, since no other piece could move there, but squares occupied by the enemy are.
+    /// A king can never give check, so is given no squares. Returns an empty vector if the enemy has no king.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the checking piece.
+    /// * `piece_type`: The type of the checking piece.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("7k/8/8/8/8/8/8/K7").unwrap();
+    /// let mut squares = b.check_squares(Color::White, PieceType::Knight);
+    /// squares.sort();
+    /// assert_eq!(squares, vec![Position::new(5, 6).unwrap(), Position::new(6, 5).unwrap()]);
+    /// ```
+    #[must_use]
+    pub fn check_squares(&self, color: Color, piece_type: PieceType) -> Vec<Position> {
+        use Direction::{E, N, NE, NW, S, SE, SW, W};
+        let Some(king_position) = self.king_position(color.opposite()) else {
+            return vec![];
+        };
+        match piece_type {
+            PieceType::Pawn => [-1, 1]
+                .into_iter()
+                .filter_map(|x| {
+                    (king_position
+                        + Offset {
+                            x,
+                            y: -color.forward_offset().y,
+                        })
+                    .ok()
+                })
+                .filter(|&position| self.check_position(position, color, true, false))
+                .collect(),
+            PieceType::Knight => self.check_knight(king_position, color),
+            PieceType::Bishop => self.check_directions(king_position, vec![NE, SE, SW, NW], color),
+            PieceType::Rook => self.check_directions(king_position, vec![N, E, S, W], color),
+            PieceType::Queen => {
+                self.check_directions(king_position, vec![N, NE, E, SE, S, SW, W, NW], color)
+            }
+            PieceType::King => vec![],
+        }
+    }
+
+    /// Takes in the position of a piece, returns all the moves it could make.
+    ///
+    /// Moves are pseudo legal, so may leave the king in check. Castling and en passant depend on the state of the game and are not included, see [`Board::castling_moves`] and [`Board::en_passant_move`].
+    /// Pawns reaching the last rank generate a separate promotion for each piece type they could promote to.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the piece to check.
+    /// # Errors
+    /// * Returns [`PieceError::NotFound`] error if piece does not exist.
+    ///
+    /// ```
+    /// use chess_lib::board::{*, mailbox::*};
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.moves_from(Position::new(1, 0).unwrap()).unwrap().len(), 2);
+    /// assert!(b.moves_from(Position::new(3, 3).unwrap()).is_err());
+    /// ```
+    pub fn moves_from(&self, position: Position) -> Result<Vec<ChessMove>, PieceError> {
+        self.moves_from_promoting(position, Variant::Standard.promotion_candidates())
+    }
+
+    /// Same as [`Board::moves_from`], but pawns reaching the last rank promote to each of `promotions` instead of the standard pieces.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the piece to check.
+    /// * `promotions`: The piece types a pawn can promote to, see [`Variant::promotion_candidates`].
+    /// # Errors
+    /// * Returns [`PieceError::NotFound`] error if piece does not exist.
+    pub(crate) fn moves_from_promoting(
+        &self,
+        position: Position,
+        promotions: &[PieceType],
+    ) -> Result<Vec<ChessMove>, PieceError> {
+        let piece = self.piece_at(position)?;
+        let mut moves = vec![];
+        for to_position in self.check_positions(position)? {
+            self.push_moves(&mut moves, piece, position, to_position, promotions);
+        }
+        Ok(moves)
+    }
+
+    /// Returns every move `color` could make that takes an enemy piece, including promotions that take.
+    ///
+    /// Cheaper than filtering [`Board::moves_from`], since a move is only built for squares holding an enemy piece.
+    /// Moves are pseudo legal, so may leave the king in check. En passant depends on the state of the game and is not included, see [`GameState::capture_moves`](crate::game::GameState::capture_moves).
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `color`: The color making the captures.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3").unwrap();
+    /// assert_eq!(b.capture_moves(Color::White).len(), 1);
+    /// assert!(Board::new().capture_moves(Color::White).is_empty());
+    /// ```
+    #[must_use]
+    pub fn capture_moves(&self, color: Color) -> Vec<ChessMove> {
+        self.capture_moves_promoting(color, Variant::Standard.promotion_candidates())
+    }
+
+    /// Same as [`Board::capture_moves`], but pawns reaching the last rank promote to each of `promotions` instead of the standard pieces.
+    ///
+    /// # Parameters
+    /// * `color`: The color making the captures.
+    /// * `promotions`: The piece types a pawn can promote to, see [`Variant::promotion_candidates`].
+    pub(crate) fn capture_moves_promoting(
+        &self,
+        color: Color,
+        promotions: &[PieceType],
+    ) -> Vec<ChessMove> {
+        let mut moves = vec![];
+        for (position, piece) in self.pieces_of(color) {
+            for to_position in self.check_positions(position).unwrap_or_default() {
+                if self[to_position].is_some_and(|target| target.color != color) {
+                    self.push_moves(&mut moves, piece, position, to_position, promotions);
+                }
+            }
+        }
+        moves
+    }
+
+    /// Returns every legal move `color` can make to get out of check.
+    ///
+    /// Only moves that could answer the check are generated: king moves to safe squares, captures of the checking piece, and blocks on the squares between a sliding checker and the king.
+    /// In double check only the king can move. Unlike most move generation on the board these moves are fully legal, since pins are checked by playing each candidate on a copy of the board.
+    /// Castling out of check is never legal, so is not included. Returns an empty vector if `color` is not in check or has no king.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `color`: The color in check.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// // The bishop on b4 checks the king, which can step aside, be shielded by the knight, or have the rook take the bishop
+    /// let b = Board::from_fen("4k3/8/8/8/Rb6/8/8/1N2K3").unwrap();
+    /// assert_eq!(b.check_evasions(Color::White, None).len(), 7);
+    /// assert!(Board::new().check_evasions(Color::White, None).is_empty());
+    /// ```
+    #[must_use]
+    pub fn check_evasions(&self, color: Color, en_passant: Option<Position>) -> Vec<ChessMove> {
+        self.check_evasions_promoting(color, en_passant, Variant::Standard.promotion_candidates())
+    }
+
+    /// Same as [`Board::check_evasions`], but pawns reaching the last rank promote to each of `promotions` instead of the standard pieces.
+    ///
+    /// # Parameters
+    /// * `color`: The color in check.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    /// * `promotions`: The piece types a pawn can promote to, see [`Variant::promotion_candidates`].
+    pub(crate) fn check_evasions_promoting(
+        &self,
+        color: Color,
+        en_passant: Option<Position>,
+        promotions: &[PieceType],
+    ) -> Vec<ChessMove> {
+        use Direction::{E, N, NE, NW, S, SE, SW, W};
+        let Some(king) = self.king_position(color) else {
+            return vec![];
+        };
+        let checkers = self.attackers_of(king, color.opposite());
+        let (Some(king_piece), false) = (self[king], checkers.is_empty()) else {
+            return vec![];
+        };
+        debug!(
+            "Generating evasions from {} checkers of {king}",
+            checkers.len()
+        );
+        let mut moves = vec![];
+        for to_position in self.safe_king_positions(color) {
+            self.push_moves(&mut moves, king_piece, king, to_position, promotions);
+        }
+        let [checker] = checkers[..] else {
+            return moves;
+        };
+        let mut targets = vec![checker];
+        if self[checker].is_some_and(|piece| {
+            matches!(
+                piece.piece_type,
+                PieceType::Queen | PieceType::Rook | PieceType::Bishop
+            )
+        }) {
+            if let Some(direction) = [N, NE, E, SE, S, SW, W, NW]
+                .into_iter()
+                .find(|&direction| self.ray(king, direction).any(|current| current == checker))
+            {
+                targets.extend(
+                    self.ray(king, direction)
+                        .take_while(|&current| current != checker),
+                );
+            }
+        }
+        let mut candidates = vec![];
+        for (position, piece) in self.pieces_of(color) {
+            if piece.piece_type == PieceType::King {
+                continue;
+            }
+            for to_position in self.check_positions(position).unwrap_or_default() {
+                if targets.contains(&to_position) {
+                    self.push_moves(&mut candidates, piece, position, to_position, promotions);
+                }
+            }
+            if piece.piece_type == PieceType::Pawn {
+                candidates
+                    .extend(en_passant.and_then(|target| self.en_passant_move(position, target)));
+            }
+        }
+        candidates.retain(|chess_move| {
+            let mut board = self.clone();
+            board.execute_move(*chess_move).is_ok() && !board.is_in_check(color)
+        });
+        moves.append(&mut candidates);
+        moves
+    }
+
+    /// Returns every legal move `color` can make under the rules of antichess.
+    ///
+    /// If any piece can be taken then only captures are legal, otherwise every move is. The king is an ordinary piece, so moves are never restricted by check, and pawns can also promote to a king.
+    /// Castling is not allowed in antichess, so is never included.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `color`: The color to move.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// // The king must take the pawn, even though it walks into the rook
+    /// let b = Board::from_fen("r3k3/8/8/8/8/8/1p6/K7").unwrap();
+    /// assert_eq!(b.antichess_legal_moves(Color::White, None).len(), 1);
+    /// ```
+    #[must_use]
+    pub fn antichess_legal_moves(
+        &self,
+        color: Color,
+        en_passant: Option<Position>,
+    ) -> Vec<ChessMove> {
+        let promotions = Variant::Antichess.promotion_candidates();
+        let mut moves = vec![];
+        for (position, _) in self.pieces_of(color) {
+            moves.extend(
+                self.moves_from_promoting(position, promotions)
+                    .unwrap_or_default(),
+            );
+            moves.extend(en_passant.and_then(|target| self.en_passant_move(position, target)));
+        }
+        if moves
+            .iter()
+            .any(|chess_move| chess_move.taken_position().is_some())
+        {
+            moves.retain(|chess_move| chess_move.taken_position().is_some());
+        }
+        moves
+    }
+
+    /// Returns the number of legal moves `color` can make, including castling and en passant.
+    ///
+    /// Gives the same count as generating every legal move, but no moves are built. Legality is checked by playing each move on a single copy of the board and putting the squares back afterwards, rather than copying the board for every move.
+    /// Each promotion counts once for every piece type a pawn can promote to in standard chess, see [`Variant::promotion_candidates`].
+    ///
+    /// # Parameters
+    /// * `color`: The color to move.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    /// * `kingside`: Whether `color` still has the right to castle kingside.
+    /// * `queenside`: Whether `color` still has the right to castle queenside.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// assert_eq!(Board::new().legal_move_count(Color::White, None, true, true), 20);
+    /// // Only the king can move, and only to the two squares off the rook's rank and file
+    /// let b = Board::from_fen("4k3/8/8/8/8/8/6r1/7K").unwrap();
+    /// assert_eq!(b.legal_move_count(Color::White, None, false, false), 1);
+    /// ```
+    #[must_use]
+    pub fn legal_move_count(
+        &self,
+        color: Color,
+        en_passant: Option<Position>,
+        kingside: bool,
+        queenside: bool,
+    ) -> usize {
+        self.legal_move_count_promoting(
+            color,
+            en_passant,
+            kingside,
+            queenside,
+            Variant::Standard.promotion_candidates(),
+        )
+    }
+
+    /// Same as [`Board::legal_move_count`], but each promotion counts once for every piece type in `promotions` instead of the standard pieces.
+    ///
+    /// # Parameters
+    /// * `color`: The color to move.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    /// * `kingside`: Whether `color` still has the right to castle kingside.
+    /// * `queenside`: Whether `color` still has the right to castle queenside.
+    /// * `promotions`: The piece types a pawn can promote to, see [`Variant::promotion_candidates`].
+    pub(crate) fn legal_move_count_promoting(
+        &self,
+        color: Color,
+        en_passant: Option<Position>,
+        kingside: bool,
+        queenside: bool,
+        promotions: &[PieceType],
+    ) -> usize {
+        let mut count = self.castling_moves(color, kingside, queenside).len();
+        self.visit_legal_moves(color, en_passant, promotions.len(), |moves| {
+            count += moves;
+            true
+        });
+        count
+    }
+
+    /// Returns an iterator over every legal move `color` can make, including castling and en passant.
+    ///
+    /// Moves are generated a piece at a time as the iterator is consumed, so stopping early skips the rest, and ordering or filtering can be applied with iterator adaptors without collecting first.
+    /// When in check only moves that could get out of check are generated, see [`Board::check_evasions`].
+    /// Order of moves is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `color`: The color to move.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    /// * `kingside`: Whether `color` still has the right to castle kingside.
+    /// * `queenside`: Whether `color` still has the right to castle queenside.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::new();
+    /// assert_eq!(b.legal_moves_iter(Color::White, None, true, true).count(), 20);
+    /// let captures = b.legal_moves_iter(Color::White, None, true, true).filter(|chess_move| chess_move.taken_position().is_some());
+    /// assert_eq!(captures.count(), 0);
+    /// ```
+    pub fn legal_moves_iter(
+        &self,
+        color: Color,
+        en_passant: Option<Position>,
+        kingside: bool,
+        queenside: bool,
+    ) -> impl Iterator<Item = ChessMove> + '_ {
+        self.legal_moves_iter_promoting(
+            color,
+            en_passant,
+            kingside,
+            queenside,
+            Variant::Standard.promotion_candidates(),
+        )
+    }
+
+    /// Same as [`Board::legal_moves_iter`], but pawns reaching the last rank promote to each of `promotions` instead of the standard pieces.
+    ///
+    /// # Parameters
+    /// * `color`: The color to move.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    /// * `kingside`: Whether `color` still has the right to castle kingside.
+    /// * `queenside`: Whether `color` still has the right to castle queenside.
+    /// * `promotions`: The piece types a pawn can promote to, see [`Variant::promotion_candidates`].
+    pub(crate) fn legal_moves_iter_promoting<'a>(
+        &'a self,
+        color: Color,
+        en_passant: Option<Position>,
+        kingside: bool,
+        queenside: bool,
+        promotions: &'a [PieceType],
+    ) -> impl Iterator<Item = ChessMove> + 'a {
+        let in_check = self.is_in_check(color);
+        let (evasions, pieces) = if in_check {
+            (
+                self.check_evasions_promoting(color, en_passant, promotions),
+                vec![],
+            )
+        } else {
+            (vec![], self.pieces_of(color))
+        };
+        let castling = if in_check {
+            vec![]
+        } else {
+            self.castling_moves(color, kingside, queenside)
+        };
+        let mut board = self.clone();
+        pieces
+            .into_iter()
+            .flat_map(move |(position, _)| {
+                let mut moves = self
+                    .moves_from_promoting(position, promotions)
+                    .unwrap_or_default();
+                moves.extend(en_passant.and_then(|target| self.en_passant_move(position, target)));
+                moves
+            })
+            .filter(move |&chess_move| board.is_legal_for(chess_move, color))
+            .chain(evasions)
+            .chain(castling)
+    }
+
+    /// Returns whether `color` has any legal move, stopping at the first one found.
+    ///
+    /// Cheaper than counting or generating every legal move, so is what checkmate and stalemate detection use.
+    /// Castling is never needed, since whenever castling is legal so is the king's step towards the rook.
+    ///
+    /// # Parameters
+    /// * `color`: The color to move.
+    /// * `en_passant`: The en passant target square, if the last move was a double pawn push.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// assert!(Board::new().has_legal_move(Color::White, None));
+    /// let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8").unwrap();
+    /// assert!(!stalemate.has_legal_move(Color::Black, None));
+    /// ```
+    #[must_use]
+    pub fn has_legal_move(&self, color: Color, en_passant: Option<Position>) -> bool {
+        // Only whether a move exists matters, not how many promotions it gives
+        !self.visit_legal_moves(color, en_passant, 1, |_| false)
+    }
+
+    /// Takes in the position of a piece, returns all the moves it could make in a fixed order.
+    ///
+    /// Same as [`Board::moves_from`], but moves are sorted by destination square, rank first then file, so a1, b1, ..., h1, a2, ...
+    /// Promotions to the same square are ordered knight, bishop, rook then queen.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the piece to check.
+    /// # Errors
+    /// * Returns [`PieceError::NotFound`] error if piece does not exist.
+    ///
+    /// ```
+    /// use chess_lib::board::{*, mailbox::*};
+    ///
+    /// let b = Board::new();
+    /// let moves = b.moves_from_sorted(Position::new(1, 0).unwrap()).unwrap();
+    /// assert_eq!(moves[0].movement().to_position, Position::new(0, 2).unwrap());
+    /// assert_eq!(moves[1].movement().to_position, Position::new(2, 2).unwrap());
+    /// ```
+    pub fn moves_from_sorted(&self, position: Position) -> Result<Vec<ChessMove>, PieceError> {
+        let mut moves = self.moves_from(position)?;
+        moves.sort_by_key(|chess_move| {
+            let to_position = chess_move.movement().to_position;
+            let promotion = match chess_move {
+                ChessMove::Promote(_, promote) | ChessMove::PromoteWithTake(_, _, promote) => {
+                    Some(promote.piece_type)
+                }
+                _ => None,
+            };
+            (to_position.y, to_position.x, promotion)
+        });
+        Ok(moves)
+    }
+
+    /// Returns the castling moves available to `color`.
+    ///
+    /// Castling requires the king and rook to be on their starting squares with only empty squares between them, and the king must not be in check or pass through or land on an attacked square.
+    /// The board does not track whether castling rights have been lost, so they must be passed in.
+    ///
+    /// # Parameters
+    /// * `color`: The color to castle.
+    /// * `kingside`: Whether `color` still has the right to castle kingside.
+    /// * `queenside`: Whether `color` still has the right to castle queenside.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R").unwrap();
+    /// assert_eq!(b.castling_moves(Color::White, true, true).len(), 2);
+    /// assert_eq!(b.castling_moves(Color::Black, false, true).len(), 1);
+    /// ```
+    #[must_use]
+    pub fn castling_moves(&self, color: Color, kingside: bool, queenside: bool) -> Vec<ChessMove> {
+        let y = if color == Color::White { 0 } else { 7 };
+        let king_position = Position { x: 4, y };
+        let mut moves = vec![];
+        if self[king_position].map(|piece| (piece.color, piece.piece_type))
+            != Some((color, PieceType::King))
+            || self.is_square_attacked(king_position, color.opposite())
+        {
+            return moves;
+        }
+        for (allowed, rook_x, king_to_x, rook_to_x) in [(kingside, 7, 6, 5), (queenside, 0, 2, 3)] {
+            let rook_position = Position { x: rook_x, y };
+            if !allowed
+                || self[rook_position].map(|piece| (piece.color, piece.piece_type))
+                    != Some((color, PieceType::Rook))
+            {
+                continue;
+            }
+            let mut between = if rook_x > 4 { 5..rook_x } else { rook_x + 1..4 };
+            if between.any(|x| self[Position { x, y }].is_some()) {
+                trace!("Castling blocked for rook at {rook_position}");
+                continue;
+            }
+            let mut path = if king_to_x > 4 {
+                5..=king_to_x
+            } else {
+                king_to_x..=3
+            };
+            if path.any(|x| self.is_square_attacked(Position { x, y }, color.opposite())) {
+                trace!("Castling through attacked square for rook at {rook_position}");
+                continue;
+            }
+            moves.push(ChessMove::Castle(
+                action::Move {
+                    from_position: king_position,
+                    to_position: Position { x: king_to_x, y },
+                },
+                action::Move {
+                    from_position: rook_position,
+                    to_position: Position { x: rook_to_x, y },
+                },
+            ));
+        }
+        moves
+    }
+
+    /// Returns the en passant capture the pawn at `position` can make onto `target`, if any.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the capturing pawn.
+    /// * `target`: The en passant target square, i.e. the square the enemy pawn skipped over.
+    #[must_use]
+    pub fn en_passant_move(&self, position: Position, target: Position) -> Option<ChessMove> {
+        let pawn = self[position].filter(|piece| piece.piece_type == PieceType::Pawn)?;
+        let captured_position = Position {
+            x: target.x,
+            y: position.y,
+        };
+        let is_diagonal_step = [-1, 1].into_iter().any(|x| {
+            position
+                + Offset {
+                    x,
+                    ..pawn.color.forward_offset()
+                }
+                == Ok(target)
+        });
+        let captured = self[captured_position]?;
+        if !is_diagonal_step
+            || self[target].is_some()
+            || captured.color == pawn.color
+            || captured.piece_type != PieceType::Pawn
+        {
+            return None;
+        }
+        Some(ChessMove::MoveWithTake(
+            action::Move {
+                from_position: position,
+                to_position: target,
+            },
+            action::Take {
+                position: captured_position,
+            },
+        ))
+    }
+
+    /// Returns the positions of the pieces removed by an explosion on `center`, as in atomic chess.
+    ///
+    /// The explosion removes the piece on `center`, along with every piece except pawns on the eight squares around it, whatever their color.
+    /// Empty squares are not included. Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `center`: The position the capture was made on.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/2pnb3/3Q4/8/8/4K3").unwrap();
+    /// let mut exploded = b.explosion(Position::new(3, 4).unwrap());
+    /// exploded.sort();
+    /// assert_eq!(exploded, vec![
+    ///     Position::new(3, 3).unwrap(),
+    ///     Position::new(3, 4).unwrap(),
+    ///     Position::new(4, 4).unwrap(),
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn explosion(&self, center: Position) -> Vec<Position> {
+        use Direction::{E, N, NE, NW, S, SE, SW, W};
+        let surrounding = [N, NE, E, SE, S, SW, W, NW]
+            .into_iter()
+            .filter_map(|direction| (center + direction.offset()).ok())
+            .filter(|&position| {
+                self[position].is_some_and(|piece| piece.piece_type != PieceType::Pawn)
+            });
+        self[center]
+            .map(|_| center)
+            .into_iter()
+            .chain(surrounding)
+            .collect()
+    }
+
+    /// Returns a drawing of the board as a bordered grid, labelled with file letters and rank numbers.
+    ///
+    /// White pieces are uppercase and black pieces are lowercase, as in FEN. The board is drawn with `perspective`'s pieces at the bottom.
+    ///
+    /// # Parameters
+    /// * `perspective`: The color whose side of the board is drawn at the bottom.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let art = Board::new().to_ascii_art(Color::White);
+    /// let lines: Vec<&str> = art.lines().collect();
+    /// assert_eq!(lines[0], "  +---+---+---+---+---+---+---+---+");
+    /// assert_eq!(lines[1], "8 | r | n | b | q | k | b | n | r |");
+    /// assert_eq!(lines[17], "    a   b   c   d   e   f   g   h");
+    /// ```
+    #[must_use]
+    pub fn to_ascii_art(&self, perspective: Color) -> String {
+        let (ranks, files): (Vec<u8>, Vec<u8>) = match perspective {
+            Color::White => ((0..8).rev().collect(), (0..8).collect()),
+            Color::Black => ((0..8).collect(), (0..8).rev().collect()),
+        };
+        let border = format!("  {}+\n", "+---".repeat(8));
+        let mut art = border.clone();
+        for &y in &ranks {
+            art.push(char::from(b'1' + y));
+            art.push_str(" |");
+            for &x in &files {
+                let c = self[Position { x, y }].map_or(' ', Piece::to_fen_char);
+                art.push(' ');
+                art.push(c);
+                art.push_str(" |");
+            }
+            art.push('\n');
+            art.push_str(&border);
+        }
+        art.push_str("   ");
+        for &x in &files {
+            art.push(' ');
+            art.push(char::from(b'a' + x));
+            art.push_str("  ");
+        }
+        art.truncate(art.trim_end().len());
+        art.push('\n');
+        art
+    }
+
+    /// Returns a drawing of the board for a terminal, with colored squares and Unicode pieces.
+    ///
+    /// Squares are colored with ANSI escape codes, see [`Position::is_light`], and pieces are drawn with [`Piece::to_unicode`].
+    /// White is at the bottom, and ranks and files are labelled.
+    ///
+    /// ```
+    /// use chess_lib::board::mailbox::*;
+    ///
+    /// let drawing = Board::new().to_ansi();
+    /// assert_eq!(drawing.lines().count(), 9);
+    /// assert!(drawing.contains('♔'));
+    /// ```
+    #[must_use]
+    pub fn to_ansi(&self) -> String {
+        let mut drawing = String::new();
+        for y in (0..8).rev() {
+            drawing.push(char::from(b'1' + y));
+            drawing.push(' ');
+            for x in 0..8 {
+                let position = Position { x, y };
+                drawing.push_str(if position.is_light() {
+                    ANSI_LIGHT
+                } else {
+                    ANSI_DARK
+                });
+                drawing.push(' ');
+                drawing.push(self[position].map_or(' ', Piece::to_unicode));
+                drawing.push(' ');
+            }
+            drawing.push_str(ANSI_RESET);
+            drawing.push('\n');
+        }
+        drawing.push_str("  ");
+        for x in 0..8 {
+            drawing.push(' ');
+            drawing.push(char::from(b'a' + x));
+            drawing.push(' ');
+        }
+        drawing.push('\n');
+        drawing
+    }
+
+    /// Pushes the moves `piece` can make from `position` to `to_position` onto `moves`.
+    ///
+    /// Takes whatever is at `to_position`, and pawns reaching the last rank generate a separate promotion for each piece type in `promotions`.
+    fn push_moves(
+        &self,
+        moves: &mut Vec<ChessMove>,
+        piece: Piece,
+        position: Position,
+        to_position: Position,
+        promotions: &[PieceType],
+    ) {
+        let last_rank = if piece.color == Color::White { 7 } else { 0 };
+        let movement = action::Move {
+            from_position: position,
+            to_position,
+        };
+        let take = self[to_position].map(|_| action::Take {
+            position: to_position,
+        });
+        if piece.piece_type == PieceType::Pawn && to_position.y == last_rank {
+            for &piece_type in promotions {
+                let promotion = action::Promote {
+                    position: to_position,
+                    piece_type,
+                };
+                moves.push(match take {
+                    Some(take) => ChessMove::PromoteWithTake(movement, take, promotion),
+                    None => ChessMove::Promote(movement, promotion),
+                });
+            }
+        } else {
+            moves.push(match take {
+                Some(take) => ChessMove::MoveWithTake(movement, take),
+                None => ChessMove::Move(movement),
+            });
+        }
+    }
+
+    /// Calls `visit` for each legal move `color` can make apart from castling, stopping as soon as it returns false.
+    ///
+    /// A pawn reaching the last rank is visited once with `promotions`, the number of piece types it can promote to, every other move with 1.
+    /// Returns false if stopped early.
+    fn visit_legal_moves(
+        &self,
+        color: Color,
+        en_passant: Option<Position>,
+        promotions: usize,
+        mut visit: impl FnMut(usize) -> bool,
+    ) -> bool {
+        let last_rank = if color == Color::White { 7 } else { 0 };
+        let mut board = self.clone();
+        for (position, piece) in self.pieces_of(color) {
+            for to_position in self.check_positions(position).unwrap_or_default() {
+                let movement = action::Move {
+                    from_position: position,
+                    to_position,
+                };
+                let chess_move = match self[to_position] {
+                    Some(_) => ChessMove::MoveWithTake(
+                        movement,
+                        action::Take {
+                            position: to_position,
+                        },
+                    ),
+                    None => ChessMove::Move(movement),
+                };
+                if board.is_legal_for(chess_move, color) {
+                    let moves = if piece.piece_type == PieceType::Pawn && to_position.y == last_rank
+                    {
+                        promotions
+                    } else {
+                        1
+                    };
+                    if !visit(moves) {
+                        return false;
+                    }
+                }
+            }
+            if let Some(chess_move) =
+                en_passant.and_then(|target| self.en_passant_move(position, target))
+            {
+                if board.is_legal_for(chess_move, color) && !visit(1) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns whether playing `chess_move` leaves `color`'s king safe, putting the board back how it was afterwards.
+    ///
+    /// Only for moves that do not castle, which change at most three squares.
+    fn is_legal_for(&mut self, chess_move: ChessMove, color: Color) -> bool {
+        let movement = chess_move.movement();
+        let taken = chess_move.taken_position().unwrap_or(movement.to_position);
+        let saved = [movement.from_position, movement.to_position, taken]
+            .map(|position| (position, self[position]));
+        let legal = self.execute_move(chess_move).is_ok() && !self.is_in_check(color);
+        for (position, piece) in saved {
+            self[position] = piece;
+        }
+        legal
+    }
+
+    /// Returns whether `color` has bishops on both light and dark squares.
+    ///
+    /// Two bishops on the same color of square, e.g. after an underpromotion, do not count as a pair.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the bishops.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// assert!(Board::new().has_bishop_pair(Color::White));
+    /// let b = Board::from_fen("4k3/8/8/8/8/8/8/2B1KB2").unwrap();
+    /// assert!(b.has_bishop_pair(Color::White));
+    /// assert!(!b.has_bishop_pair(Color::Black));
+    /// ```
+    #[must_use]
+    pub fn has_bishop_pair(&self, color: Color) -> bool {
+        let (mut light, mut dark) = (false, false);
+        for (position, piece) in self.iter_pieces() {
+            if piece.color == color && piece.piece_type == PieceType::Bishop {
+                if position.is_light() {
+                    light = true;
+                } else {
+                    dark = true;
+                }
+            }
+        }
+        light && dark
+    }
+
+    /// Returns the number of doubled, isolated and passed pawns `color` has.
+    ///
+    /// # Parameters
+    /// * `color`: The color of the pawns to count.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::*, piece::*};
+    ///
+    /// let b = Board::from_fen("4k3/8/8/8/8/2P5/2P4P/4K3").unwrap();
+    /// let structure = b.pawn_structure(Color::White);
+    /// assert_eq!(structure, PawnStructure { doubled: 1, isolated: 3, passed: 3 });
+    /// ```
+    #[must_use]
+    pub fn pawn_structure(&self, color: Color) -> PawnStructure {
+        let is_pawn = |piece: Option<Piece>| {
+            piece.is_some_and(|piece| piece.color == color && piece.piece_type == PieceType::Pawn)
+        };
+        let mut files = [0u8; 8];
+        for (x, count) in (0..8).zip(files.iter_mut()) {
+            *count = self
+                .file(x)
+                .into_iter()
+                .flatten()
+                .filter(|&(_, piece)| is_pawn(piece))
+                .map(|_| 1)
+                .sum();
+        }
+        let mut structure = PawnStructure {
+            passed: u8::try_from(self.passed_pawns(color).len()).unwrap_or(u8::MAX),
+            ..PawnStructure::default()
+        };
+        for (x, &count) in files.iter().enumerate() {
+            structure.doubled += count.saturating_sub(1);
+            let left = x.checked_sub(1).map_or(0, |left| files[left]);
+            let right = files.get(x + 1).copied().unwrap_or(0);
+            if left == 0 && right == 0 {
+                structure.isolated += count;
+            }
+        }
+        structure
+    }
+
+    /// Returns the positions of `color`'s pawns, ordered from A1 to H8, rank by rank.
+    fn pawn_positions(&self, color: Color) -> Vec<Position> {
+        self.pieces_of(color)
+            .into_iter()
+            .filter(|(_, piece)| piece.piece_type == PieceType::Pawn)
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Returns whether there is a pawn of `color` on file `x`.
+    fn file_has_pawn(&self, x: u8, color: Color) -> bool {
+        self.file(x).into_iter().flatten().any(|(_, piece)| {
+            piece.is_some_and(|piece| piece.color == color && piece.piece_type == PieceType::Pawn)
+        })
+    }
+
+    /// Returns the squares the piece at `position` can move to safely.
+    ///
+    /// A square is safe if it is not attacked by the enemy, or it is defended and every enemy attacker is worth more than the piece.
+    /// Taking an enemy piece worth at least as much as the piece is always safe.
+    fn safe_squares(&self, position: Position) -> Vec<Position> {
+        let Some(piece) = self[position] else {
+            return vec![];
+        };
+        let value = piece.piece_type.value();
+        let mut squares: Vec<Position> = self
+            .moves_from(position)
+            .unwrap_or_default()
+            .iter()
+            .map(|chess_move| chess_move.movement().to_position)
+            .filter(|&to_position| {
+                if self[to_position].is_some_and(|target| target.piece_type.value() >= value) {
+                    return true;
+                }
+                let Some(cheapest_attacker) = self
+                    .attackers_of(to_position, piece.color.opposite())
+                    .into_iter()
+                    .filter_map(|attacker| self[attacker])
+                    .map(|attacker| attacker.piece_type.value())
+                    .min()
+                else {
+                    return true;
+                };
+                cheapest_attacker > value
+                    && self
+                        .attackers_of(to_position, piece.color)
+                        .into_iter()
+                        .any(|defender| defender != position)
+            })
+            .collect();
+        squares.sort();
+        squares.dedup();
+        squares
+    }
+
+    /// Checks directions and returns vector of possible positions.
+    ///
+    /// # Parameters
+    /// * `position`: The position to check directions from.
+    /// * `directions`: Which directions to check. Order does not matter.
+    /// * `color`: Which color the piece being checked is (to determine which pieces can be taken).
+    fn check_directions(
+        &self,
+        position: Position,
+        directions: Vec<Direction>,
+        color: Color,
+    ) -> Vec<Position> {
+        debug!("Checking directions {directions:?} for piece at {position} with color {color:?}");
+        let mut out = vec![];
+        for direction in directions {
+            out.append(&mut self.check_direction(position, direction, color));
+        }
+        out
+    }
+
+    /// Checks direction and returns vector of possible positions.
+    ///
+    /// # Parameters
+    /// * `position`: The position to check the direction from.
+    /// * `direction`: Which direction to check. Order does not matter.
+    /// * `color`: Which color the piece being checked is (to determine which pieces can be taken).
+    fn check_direction(
+        &self,
+        position: Position,
+        direction: Direction,
+        color: Color,
+    ) -> Vec<Position> {
+        debug!("Checking direction {direction:?} for piece at {position} with color {color:?}");
+        let mut positions: Vec<Position> = vec![];
+        for position in self.ray(position, direction) {
+            let Some(piece) = self[position] else {
+                positions.push(position);
+                continue;
+            };
+            if piece.color == color {
+                trace!("Reached piece of own color at {position}");
+                return positions;
+            }
+            trace!("Reached piece of opposite color at {position}");
+            positions.push(position);
+            return positions;
+        }
+        trace!("Reached edge of board");
+        positions
+    }
+
+    /// Returns vector of possible positions pawn could move to.
+    ///
+    /// # Parameters
+    /// * `position`: The postition to check movement from.
+    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
+    /// * `moved`: Whether the pawn has been moved.
+    fn check_pawn(&self, position: Position, color: Color, moved: bool) -> Vec<Position> {
+        let mut positions = vec![];
+        let forward = color.forward_offset();
+        if let Ok(single_position) = position + forward {
+            if self.check_position(single_position, color, false, false) {
+                positions.push(single_position);
+                // Pawns can only move two squares if the square in front of them is also empty.
+                if !moved {
+                    if let Ok(double_position) = position
+                        + (Offset {
+                            x: 0,
+                            y: 2 * forward.y,
+                        })
+                    {
+                        if self.check_position(double_position, color, false, false) {
+                            positions.push(double_position);
+                        }
+                    }
+                }
+            };
+        };
+        if let Ok(position) = position + (Offset { x: 1, ..forward }) {
+            if self.check_position(position, color, true, true) {
+                positions.push(position);
+            };
+        };
+        if let Ok(position) = position + (Offset { x: -1, ..forward }) {
+            if self.check_position(position, color, true, true) {
+                positions.push(position);
+            };
+        };
+
+        positions
+    }
+
+    /// Returns vector of possible positions knight could move to.
+    ///
+    /// # Parameters
+    /// * `position`: The position to check movement from.
+    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
+    fn check_knight(&self, position: Position, color: Color) -> Vec<Position> {
+        let mut positions = vec![];
+        for offset in KNIGHT_OFFSETS {
+            if let Ok(position) = position + offset {
+                if self.check_position(position, color, true, false) {
+                    positions.push(position);
+                }
+            }
+        }
+        positions
+    }
+
+    /// Returns vector of possible positions king could move to.
+    ///
+    /// Does NOT check for checks.
+    /// # Parameters
+    /// * `position`: The position to check movement from.
+    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
+    fn check_king(&self, position: Position, color: Color) -> Vec<Position> {
+        let mut positions = vec![];
+        let offsets = [
+            Offset { x: 1, y: 1 },
+            Offset { x: -1, y: 1 },
+            Offset { x: -1, y: -1 },
+            Offset { x: 1, y: -1 },
+            Offset { x: 1, y: 0 },
+            Offset { x: -1, y: 0 },
+            Offset { x: 0, y: -1 },
+            Offset { x: 0, y: 1 },
+        ];
+        for offset in offsets {
+            if let Ok(position) = position + offset {
+                if self.check_position(position, color, true, false) {
+                    positions.push(position);
+                }
+            }
+        }
+        positions
+    }
+
+    /// Checks whether a position can be moved to.
+    ///
+    /// # Parameters
+    /// * `position`: The position to check movement from.
+    /// * `color`: The color that the pawn is (to determine which pieces can be taken).
+    /// * `can_take`: Whether other pieces can be taken.
+    /// * `must_take`: Whether other pieces must be taken.
+    fn check_position(
+        &self,
+        position: Position,
+        color: Color,
+        can_take: bool,
+        must_take: bool,
+    ) -> bool {
+        debug!("Checking {position}");
+        let  Some(piece) = self[position] else {
+            return !must_take; // Return true for empty square unless must take is true.
+        };
+        if piece.color == color {
+            false
+        } else {
+            can_take // Return true if piece can take
+        }
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MovePiece for Board {
+    fn move_piece(
+        &mut self,
+        from_position: Position,
+        to_position: Position,
+    ) -> Result<(), PieceError> {
+        Board::move_piece(self, from_position, to_position)
+    }
+}
+
+impl TakePiece for Board {
+    fn take_piece(&mut self, position: Position) -> Result<(), PieceError> {
+        Board::take_piece(self, position)
+    }
+}
+
+impl PromotePiece for Board {
+    fn promote_piece(
+        &mut self,
+        position: Position,
+        piece_type: PieceType,
+    ) -> Result<(), PieceError> {
+        info!("Promoting piece at {position} to {piece_type:?}");
+        let Some(piece) = &mut self[position] else {
+            return Err(PieceError::NotFound(position));
+        };
+        piece.piece_type = piece_type;
+        Ok(())
+    }
+}
+
+impl PlacePiece for Board {
+    fn place_piece(&mut self, place: action::Place) -> Result<(), PieceError> {
+        info!(
+            "Placing {:?} {:?} at {}",
+            place.color, place.piece_type, place.position
+        );
+        if let Some(piece) = self[place.position] {
+            return Err(PieceError::Occupied(place.position, piece.piece_type));
+        }
+        // Pawns placed on their starting rank can still move two squares
+        let start_rank = if place.color == Color::White { 1 } else { 6 };
+        self[place.position] = Some(Piece {
+            color: place.color,
+            piece_type: place.piece_type,
+            moved: place.piece_type != PieceType::Pawn || place.position.y != start_rank,
+        });
+        Ok(())
+    }
+}
+
+impl ExecuteMove for Board {
+    fn execute_move(&mut self, chess_move: ChessMove) -> Result<(), PieceError> {
+        debug!("Executing {chess_move:?}");
+        match chess_move {
+            ChessMove::Move(movement) => {
+                self.move_piece(movement.from_position, movement.to_position)?;
+            }
+            ChessMove::MoveWithTake(movement, take) => {
+                // The taken piece is not always on the destination square, e.g. en passant
+                self.take_piece(take.position)?;
+                self.move_piece(movement.from_position, movement.to_position)?;
+            }
+            ChessMove::Castle(king_movement, rook_movement) => {
+                self.castle(king_movement, rook_movement)?;
+            }
+            ChessMove::Promote(movement, promotion) => {
+                self.move_piece(movement.from_position, movement.to_position)?;
+                self.promote_piece(promotion.position, promotion.piece_type)?;
+            }
+            ChessMove::PromoteWithTake(movement, take, promotion) => {
+                self.take_piece(take.position)?;
+                self.move_piece(movement.from_position, movement.to_position)?;
+                self.promote_piece(promotion.position, promotion.piece_type)?;
+            }
+            ChessMove::Drop(place) => {
+                self.place_piece(place)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Index<Position> for Board {
+    type Output = Option<Piece>;
+
+    #[inline]
+    fn index(&self, index: Position) -> &Self::Output {
+        &self.pieces[(index.y.into(), index.x.into())]
+    }
+}
+
+impl IndexMut<Position> for Board {
+    #[inline]
+    fn index_mut(&mut self, index: Position) -> &mut Self::Output {
+        &mut self.pieces[(index.y.into(), index.x.into())]
+    }
+}
+
+#[cfg(test)]
+mod position_tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_positive_n() {
+        assert_eq!(
+            Position { x: 6, y: 6 },
+            (Position { x: 6, y: 5 } + Offset { x: 0, y: 1 }).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_offset_positive_ne() {
+        assert_eq!(
+            Position { x: 6, y: 6 },
+            (Position { x: 5, y: 5 } + Offset { x: 1, y: 1 }).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_offset_negative_s() {
+        assert_eq!(
+            Position { x: 6, y: 5 },
+            (Position { x: 6, y: 6 } + Offset { x: 0, y: -1 }).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_offset_negative_sw() {
+        assert_eq!(
+            Position { x: 5, y: 5 },
+            (Position { x: 6, y: 6 } + Offset { x: -1, y: -1 }).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod board_tests {
+    use super::*;
+
+    mod move_piece {
+        use super::*;
+
+        #[test]
+        fn move_queen() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 3, y: 0 }, Position { x: 5, y: 5 })
+                .unwrap();
+            assert_eq!(board[Position { x: 3, y: 0 }], None);
+            assert_eq!(
+                board[Position { x: 5, y: 5 }].unwrap(),
+                Piece {
+                    color: Color::White,
+                    piece_type: PieceType::Queen,
+                    moved: true
+                }
+            )
+        }
+    }
+
+    mod piece_at {
+        use super::*;
+
+        #[test]
+        fn occupied() {
+            let board = Board::new();
+            assert_eq!(
+                board.piece_at(Position { x: 4, y: 7 }).unwrap(),
+                Piece::new(Color::Black, PieceType::King)
+            );
+        }
+
+        #[test]
+        fn empty() {
+            let board = Board::new();
+            assert!(matches!(
+                board.piece_at(Position { x: 4, y: 4 }),
+                Err(PieceError::NotFound(Position { x: 4, y: 4 }))
+            ));
+        }
+    }
+
+    mod castle {
+        use super::*;
+        use crate::board::action;
+
+        fn movement(from: (u8, u8), to: (u8, u8)) -> action::Move {
+            action::Move {
+                from_position: Position {
+                    x: from.0,
+                    y: from.1,
+                },
+                to_position: Position { x: to.0, y: to.1 },
+            }
+        }
+
+        #[test]
+        fn kingside() {
+            let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R").unwrap();
+            board
+                .castle(movement((4, 0), (6, 0)), movement((7, 0), (5, 0)))
+                .unwrap();
+            assert_eq!(board[Position { x: 4, y: 0 }], None);
+            assert_eq!(board[Position { x: 7, y: 0 }], None);
+            let king = board[Position { x: 6, y: 0 }].unwrap();
+            assert_eq!(king.piece_type, PieceType::King);
+            assert!(king.moved);
+            let rook = board[Position { x: 5, y: 0 }].unwrap();
+            assert_eq!(rook.piece_type, PieceType::Rook);
+            assert!(rook.moved);
+        }
+
+        #[test]
+        fn queenside() {
+            let mut board = Board::from_fen("r3k3/8/8/8/8/8/8/4K3").unwrap();
+            board
+                .execute_move(ChessMove::Castle(
+                    movement((4, 7), (2, 7)),
+                    movement((0, 7), (3, 7)),
+                ))
+                .unwrap();
+            let mut expected = Board::from_fen("2kr4/8/8/8/8/8/8/4K3").unwrap();
+            for position in [Position { x: 2, y: 7 }, Position { x: 3, y: 7 }] {
+                expected[position].as_mut().unwrap().moved = true;
+            }
+            assert_eq!(board, expected);
+        }
+
+        #[test]
+        fn occupied() {
+            let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K1NR").unwrap();
+            let before = board.clone();
+            assert!(matches!(
+                board.castle(movement((4, 0), (6, 0)), movement((7, 0), (5, 0))),
+                Err(PieceError::Occupied(_, PieceType::Knight))
+            ));
+            assert_eq!(board, before);
+        }
+
+        #[test]
+        fn missing_rook() {
+            let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3").unwrap();
+            assert!(matches!(
+                board.castle(movement((4, 0), (6, 0)), movement((7, 0), (5, 0))),
+                Err(PieceError::NotFound(_))
+            ));
+            assert_eq!(
+                board.king_position(Color::White),
+                Some(Position { x: 4, y: 0 })
+            );
+        }
+    }
+
+    mod from_fen {
+        use super::*;
+
+        #[test]
+        fn default_layout() {
+            assert_eq!(
+                Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").unwrap(),
+                Board::new()
+            );
+        }
+
+        #[test]
+        fn moved_pieces() {
+            let board = Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR").unwrap();
+            assert_eq!(board[Position { x: 4, y: 1 }], None);
+            assert_eq!(
+                board[Position { x: 4, y: 3 }],
+                Some(Piece {
+                    color: Color::White,
+                    piece_type: PieceType::Pawn,
+                    moved: true
+                })
+            );
+            assert!(!board[Position { x: 3, y: 1 }].unwrap().moved);
+        }
+
+        #[test]
+        fn two_white_kings() {
+            assert_eq!(
+                Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKKBNR"),
+                Err(FenError::IllegalPosition(BoardError::KingCount(
+                    Color::White,
+                    2
+                )))
+            );
+        }
+
+        #[test]
+        fn no_black_king() {
+            assert_eq!(
+                Board::from_fen("8/8/8/8/8/8/8/4K3"),
+                Err(FenError::IllegalPosition(BoardError::KingCount(
+                    Color::Black,
+                    0
+                )))
+            );
+        }
+
+        #[test]
+        fn malformed() {
+            assert_eq!(
+                Board::from_fen("8/8/8/8/8/8/8"),
+                Err(FenError::RankCount(7))
+            );
+            assert_eq!(
+                Board::from_fen("4k3/8/8/8/8/8/8/4K4"),
+                Err(FenError::FileCount(0))
+            );
+            assert_eq!(
+                Board::from_fen("4k3/8/8/8/8/8/8/4K2"),
+                Err(FenError::FileCount(0))
+            );
+            assert_eq!(
+                Board::from_fen("4k3/8/8/8/8/8/8/4X3"),
+                Err(FenError::InvalidPiece('X'))
+            );
+        }
+    }
+
+    mod validate {
+        use super::*;
+
+        #[test]
+        fn default_layout() {
+            assert_eq!(Board::new().validate(), Ok(()));
+        }
+
+        #[test]
+        fn missing_king() {
+            let mut board = Board::new();
+            board.take_piece(Position { x: 4, y: 0 }).unwrap();
+            assert_eq!(
+                board.validate(),
+                Err(BoardError::KingCount(Color::White, 0))
+            );
+        }
+
+        #[test]
+        fn too_many_pieces() {
+            let mut board = Board::new();
+            board[Position { x: 3, y: 3 }] = Some(Piece::new(Color::Black, PieceType::Queen));
+            assert_eq!(
+                board.validate(),
+                Err(BoardError::PieceCount(Color::Black, 17))
+            );
+        }
+
+        #[test]
+        fn pawn_on_back_rank() {
+            let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3").unwrap();
+            board[Position { x: 0, y: 7 }] = Some(Piece::new(Color::White, PieceType::Pawn));
+            assert_eq!(
+                board.validate(),
+                Err(BoardError::PawnOnBackRank(Position { x: 0, y: 7 }))
+            );
+            board[Position { x: 0, y: 7 }] = None;
+            board[Position { x: 3, y: 0 }] = Some(Piece::new(Color::Black, PieceType::Pawn));
+            assert_eq!(
+                board.validate(),
+                Err(BoardError::PawnOnBackRank(Position { x: 3, y: 0 }))
+            );
+        }
+    }
+
+    mod pieces_array {
+        use super::*;
+
+        #[test]
+        fn matches_index() {
+            let board =
+                Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+            let squares = board.pieces_array();
+            for y in 0..8 {
+                for x in 0..8 {
+                    assert_eq!(squares[usize::from(y * 8 + x)], board[Position { x, y }]);
+                }
+            }
+        }
+
+        #[test]
+        fn round_trip() {
+            let board = Board::new();
+            let pieces = (0..8)
+                .flat_map(|y| (0..8).map(move |x| Position { x, y }))
+                .zip(board.pieces_array())
+                .filter_map(|(position, piece)| Some((position, piece?)));
+            assert_eq!(Board::from_pieces(pieces), board);
+        }
+    }
+
+    mod from_pieces {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let board =
+                Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+            assert_eq!(Board::from_pieces(board.iter_pieces()), board);
+        }
+
+        #[test]
+        fn duplicate_overwrites() {
+            let position = Position { x: 3, y: 3 };
+            let board = Board::from_pieces([
+                (position, Piece::new(Color::White, PieceType::Knight)),
+                (position, Piece::new(Color::Black, PieceType::Bishop)),
+            ]);
+            assert_eq!(
+                board[position],
+                Some(Piece::new(Color::Black, PieceType::Bishop))
+            );
+            assert_eq!(board.iter_pieces().count(), 1);
+        }
+
+        #[test]
+        fn empty() {
+            assert_eq!(Board::from_pieces([]), Board::empty());
+        }
+    }
+
+    mod to_fen {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            for placement in [
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8",
+                "7k/8/8/8/8/8/8/K7",
+            ] {
+                assert_eq!(Board::from_fen(placement).unwrap().to_fen(), placement);
+            }
+        }
+    }
+
+    mod execute_move {
+        use super::*;
+        use crate::board::action;
+
+        #[test]
+        fn move_with_take() {
+            let mut board = Board::new();
+            board
+                .execute_move(ChessMove::MoveWithTake(
+                    action::Move {
+                        from_position: Position { x: 1, y: 0 },
+                        to_position: Position { x: 1, y: 6 },
+                    },
+                    action::Take {
+                        position: Position { x: 1, y: 6 },
+                    },
+                ))
+                .unwrap();
+            assert_eq!(board[Position { x: 1, y: 0 }], None);
+            assert_eq!(
+                board[Position { x: 1, y: 6 }].unwrap().piece_type,
+                PieceType::Knight
+            );
+        }
+
+        #[test]
+        fn en_passant() {
+            let mut board = Board::from_fen("4k3/8/8/3Pp3/8/8/8/4K3").unwrap();
+            board
+                .execute_move(ChessMove::MoveWithTake(
+                    action::Move {
+                        from_position: Position { x: 3, y: 4 },
+                        to_position: Position { x: 4, y: 5 },
+                    },
+                    action::Take {
+                        position: Position { x: 4, y: 4 },
+                    },
+                ))
+                .unwrap();
+            assert_eq!(board[Position { x: 3, y: 4 }], None);
+            assert_eq!(board[Position { x: 4, y: 4 }], None);
+            let pawn = board[Position { x: 4, y: 5 }].unwrap();
+            assert_eq!(pawn.color, Color::White);
+            assert_eq!(pawn.piece_type, PieceType::Pawn);
+            assert_eq!(board.iter_pieces().count(), 3);
+        }
+
+        #[test]
+        fn promote() {
+            let mut board = Board::new();
+            board.take_piece(Position { x: 0, y: 7 }).unwrap();
+            board
+                .execute_move(ChessMove::Promote(
+                    action::Move {
+                        from_position: Position { x: 0, y: 1 },
+                        to_position: Position { x: 0, y: 7 },
+                    },
+                    action::Promote {
+                        position: Position { x: 0, y: 7 },
+                        piece_type: PieceType::Queen,
+                    },
+                ))
+                .unwrap();
+            assert_eq!(
+                board[Position { x: 0, y: 7 }].unwrap(),
+                Piece {
+                    color: Color::White,
+                    piece_type: PieceType::Queen,
+                    moved: true
+                }
+            );
+        }
+
+        #[test]
+        fn missing_piece() {
+            let mut board = Board::new();
+            assert!(board
+                .execute_move(ChessMove::Move(action::Move {
+                    from_position: Position { x: 3, y: 3 },
+                    to_position: Position { x: 3, y: 4 },
+                }))
+                .is_err());
+        }
+    }
+
+    mod check_positions {
+        use super::*;
+
+        #[test]
+        fn pawn() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 5, y: 6 }, Position { x: 5, y: 4 })
+                .unwrap();
+            board
+                .move_piece(Position { x: 4, y: 1 }, Position { x: 4, y: 3 })
+                .unwrap();
+            let mut result = board.check_positions(Position { x: 4, y: 3 }).unwrap();
+            result.sort();
+            let mut expected_result = vec![Position { x: 5, y: 4 }, Position { x: 4, y: 4 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result)
+        }
+
+        #[test]
+        fn bishop() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 2, y: 7 }, Position { x: 4, y: 5 })
+                .unwrap();
+            let mut result = board.check_positions(Position { x: 4, y: 5 }).unwrap();
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 0, y: 1 },
+                Position { x: 1, y: 2 },
+                Position { x: 2, y: 3 },
+                Position { x: 3, y: 4 },
+                Position { x: 7, y: 2 },
+                Position { x: 6, y: 3 },
+                Position { x: 5, y: 4 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result)
+        }
+
+        #[test]
+        fn rook() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 0, y: 0 }, Position { x: 3, y: 4 })
+                .unwrap();
+            let mut result = board.check_positions(Position { x: 3, y: 4 }).unwrap();
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 0, y: 4 },
+                Position { x: 1, y: 4 },
+                Position { x: 2, y: 4 },
+                Position { x: 4, y: 4 },
+                Position { x: 5, y: 4 },
+                Position { x: 6, y: 4 },
+                Position { x: 7, y: 4 },
+                Position { x: 3, y: 2 },
+                Position { x: 3, y: 3 },
+                Position { x: 3, y: 5 },
+                Position { x: 3, y: 6 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result)
+        }
+
+        #[test]
+        fn queen() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 3, y: 7 }, Position { x: 1, y: 3 })
+                .unwrap();
+            let mut result = board.check_positions(Position { x: 1, y: 3 }).unwrap();
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 0, y: 3 },
+                Position { x: 2, y: 3 },
+                Position { x: 3, y: 3 },
+                Position { x: 4, y: 3 },
+                Position { x: 5, y: 3 },
+                Position { x: 6, y: 3 },
+                Position { x: 7, y: 3 },
+                Position { x: 1, y: 1 },
+                Position { x: 1, y: 2 },
+                Position { x: 1, y: 4 },
+                Position { x: 1, y: 5 },
+                Position { x: 0, y: 2 },
+                Position { x: 2, y: 4 },
+                Position { x: 3, y: 5 },
+                Position { x: 0, y: 4 },
+                Position { x: 2, y: 2 },
+                Position { x: 3, y: 1 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result)
+        }
+    }
+
+    mod check_directions {
+        use super::*;
+
+        #[test]
+        fn cardinal_directions() {
+            let board = Board::new();
+            let mut result = board.check_directions(
+                Position { x: 3, y: 4 },
+                vec![Direction::N, Direction::E, Direction::S, Direction::W],
+                Color::White,
+            );
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 0, y: 4 },
+                Position { x: 1, y: 4 },
+                Position { x: 2, y: 4 },
+                Position { x: 4, y: 4 },
+                Position { x: 5, y: 4 },
+                Position { x: 6, y: 4 },
+                Position { x: 7, y: 4 },
+                Position { x: 3, y: 2 },
+                Position { x: 3, y: 3 },
+                Position { x: 3, y: 5 },
+                Position { x: 3, y: 6 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result)
+        }
+
+        #[test]
+        fn diagonal_directions() {
+            let board = Board::new();
+            let mut result = board.check_directions(
+                Position { x: 4, y: 5 },
+                vec![Direction::NE, Direction::SE, Direction::SW, Direction::NW],
+                Color::Black,
+            );
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 0, y: 1 },
+                Position { x: 1, y: 2 },
+                Position { x: 2, y: 3 },
+                Position { x: 3, y: 4 },
+                Position { x: 7, y: 2 },
+                Position { x: 6, y: 3 },
+                Position { x: 5, y: 4 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result)
+        }
+
+        #[test]
+        fn all_directions() {
+            let board = Board::new();
+            let mut result = board.check_directions(
+                Position { x: 1, y: 3 },
+                vec![
+                    Direction::N,
+                    Direction::NE,
+                    Direction::E,
+                    Direction::SE,
+                    Direction::S,
+                    Direction::SW,
+                    Direction::W,
+                    Direction::NW,
+                ],
+                Color::Black,
+            );
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 0, y: 3 },
+                Position { x: 2, y: 3 },
+                Position { x: 3, y: 3 },
+                Position { x: 4, y: 3 },
+                Position { x: 5, y: 3 },
+                Position { x: 6, y: 3 },
+                Position { x: 7, y: 3 },
+                Position { x: 1, y: 1 },
+                Position { x: 1, y: 2 },
+                Position { x: 1, y: 4 },
+                Position { x: 1, y: 5 },
+                Position { x: 0, y: 2 },
+                Position { x: 2, y: 4 },
+                Position { x: 3, y: 5 },
+                Position { x: 0, y: 4 },
+                Position { x: 2, y: 2 },
+                Position { x: 3, y: 1 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result)
+        }
+    }
+
+    mod check_direction {
+        use super::*;
+
+        #[test]
+        fn no_move_n() {
+            let board = Board::new();
+            assert_eq!(
+                board.check_direction(Position { x: 4, y: 0 }, Direction::N, Color::White),
+                vec![]
+            );
+        }
+
+        #[test]
+        fn no_move_w() {
+            let board = Board::new();
+            assert_eq!(
+                board.check_direction(Position { x: 5, y: 1 }, Direction::W, Color::White),
+                vec![]
+            );
+        }
+
+        #[test]
+        fn edge_board_e() {
+            let board = Board::new();
+            assert_eq!(
+                board.check_direction(Position { x: 6, y: 5 }, Direction::E, Color::White),
+                vec![Position { x: 7, y: 5 }]
+            );
+        }
+
+        #[test]
+        fn edge_board_s() {
+            let board = Board::new();
+            assert_eq!(
+                board.check_direction(Position { x: 3, y: 0 }, Direction::S, Color::White),
+                vec![]
+            );
+        }
+
+        #[test]
+        fn from_edge_e() {
+            let board = Board::new();
+            let mut result =
+                board.check_direction(Position { x: 0, y: 2 }, Direction::E, Color::White);
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 1, y: 2 },
+                Position { x: 2, y: 2 },
+                Position { x: 3, y: 2 },
+                Position { x: 4, y: 2 },
+                Position { x: 5, y: 2 },
+                Position { x: 6, y: 2 },
+                Position { x: 7, y: 2 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn from_edge_w() {
+            let board = Board::new();
+            let mut result =
+                board.check_direction(Position { x: 7, y: 4 }, Direction::W, Color::White);
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 0, y: 4 },
+                Position { x: 1, y: 4 },
+                Position { x: 2, y: 4 },
+                Position { x: 3, y: 4 },
+                Position { x: 4, y: 4 },
+                Position { x: 5, y: 4 },
+                Position { x: 6, y: 4 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn take_piece_ne() {
+            let board = Board::new();
+            let mut result =
+                board.check_direction(Position { x: 2, y: 2 }, Direction::NE, Color::White);
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 3, y: 3 },
+                Position { x: 4, y: 4 },
+                Position { x: 5, y: 5 },
+                Position { x: 6, y: 6 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn take_piece_sw() {
+            let board = Board::new();
+            let mut result =
+                board.check_direction(Position { x: 4, y: 3 }, Direction::SW, Color::Black);
+            result.sort();
+            let mut expected_result = vec![Position { x: 3, y: 2 }, Position { x: 2, y: 1 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+    }
+
+    mod check_pawn {
+        use super::*;
+
+        #[test]
+        fn first_move_white() {
+            let board = Board::new();
+            let mut result = board.check_pawn(Position { x: 3, y: 3 }, Color::White, false);
+            result.sort();
+            let mut expected_result = vec![Position { x: 3, y: 4 }, Position { x: 3, y: 5 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn first_move_black() {
+            let board = Board::new();
+            let mut result = board.check_pawn(Position { x: 6, y: 5 }, Color::Black, false);
+            result.sort();
+            let mut expected_result = vec![Position { x: 6, y: 4 }, Position { x: 6, y: 3 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn take_two_white() {
+            let board = Board::new();
+            let mut result = board.check_pawn(Position { x: 6, y: 5 }, Color::White, true);
+            result.sort();
+            let mut expected_result = vec![Position { x: 5, y: 6 }, Position { x: 7, y: 6 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn take_one_black() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 2, y: 1 }, Position { x: 2, y: 3 })
+                .unwrap();
+            let mut result = board.check_pawn(Position { x: 3, y: 4 }, Color::Black, true);
+            result.sort();
+            let mut expected_result = vec![Position { x: 2, y: 3 }, Position { x: 3, y: 3 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+    }
+
+    mod check_knight {
+        use super::*;
+
+        #[test]
+        fn no_edge() {
+            let board = Board::new();
+            let mut result = board.check_knight(Position { x: 3, y: 5 }, Color::White);
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 2, y: 7 },
+                Position { x: 4, y: 7 },
+                Position { x: 1, y: 6 },
+                Position { x: 5, y: 6 },
+                Position { x: 5, y: 4 },
+                Position { x: 1, y: 4 },
+                Position { x: 2, y: 3 },
+                Position { x: 4, y: 3 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn near_edge() {
+            let board = Board::new();
+            let mut result = board.check_knight(Position { x: 0, y: 5 }, Color::Black);
+            result.sort();
+            let mut expected_result = vec![Position { x: 2, y: 4 }, Position { x: 1, y: 3 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+    }
+
+    mod check_king {
+        use super::*;
+
+        #[test]
+        fn near_friendlies() {
+            let board = Board::new();
+            let mut result = board.check_king(Position { x: 3, y: 5 }, Color::Black);
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 2, y: 5 },
+                Position { x: 2, y: 4 },
+                Position { x: 3, y: 4 },
+                Position { x: 4, y: 4 },
+                Position { x: 4, y: 5 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+    }
+
+    mod attackers_of {
+        use super::*;
+
+        #[test]
+        fn pawns_and_knight() {
+            let board = Board::new();
+            let mut result = board.attackers_of(Position { x: 5, y: 5 }, Color::Black);
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 4, y: 6 },
+                Position { x: 6, y: 6 },
+                Position { x: 6, y: 7 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn pawn_does_not_attack_forwards() {
+            let board = Board::new();
+            let mut result = board.attackers_of(Position { x: 4, y: 2 }, Color::White);
+            result.sort();
+            let mut expected_result = vec![Position { x: 3, y: 1 }, Position { x: 5, y: 1 }];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn blocked_slider() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 0, y: 0 }, Position { x: 0, y: 3 })
+                .unwrap();
+            assert_eq!(
+                board.attackers_of(Position { x: 7, y: 3 }, Color::White),
+                vec![Position { x: 0, y: 3 }]
+            );
+            board
+                .move_piece(Position { x: 1, y: 7 }, Position { x: 3, y: 3 })
+                .unwrap();
+            assert!(board
+                .attackers_of(Position { x: 7, y: 3 }, Color::White)
+                .is_empty());
+        }
+    }
+
+    mod is_attacked_by_pawn {
+        use super::*;
+
+        #[test]
+        fn e4_attacks_d5_and_f5() {
+            let board = Board::from_fen("4k3/8/8/8/4P3/8/8/4K3").unwrap();
+            assert!(board.is_attacked_by_pawn(Position { x: 3, y: 4 }, Color::White));
+            assert!(board.is_attacked_by_pawn(Position { x: 5, y: 4 }, Color::White));
+            assert!(!board.is_attacked_by_pawn(Position { x: 4, y: 4 }, Color::White));
+            assert!(!board.is_attacked_by_pawn(Position { x: 3, y: 2 }, Color::White));
+            assert!(!board.is_attacked_by_pawn(Position { x: 3, y: 4 }, Color::Black));
+        }
+
+        #[test]
+        fn black_attacks_downwards() {
+            let board = Board::from_fen("4k3/8/8/4p3/8/8/8/4K3").unwrap();
+            assert!(board.is_attacked_by_pawn(Position { x: 3, y: 3 }, Color::Black));
+            assert!(board.is_attacked_by_pawn(Position { x: 5, y: 3 }, Color::Black));
+            assert!(!board.is_attacked_by_pawn(Position { x: 3, y: 5 }, Color::Black));
+        }
+
+        #[test]
+        fn matches_attackers_of() {
+            let board =
+                Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+            for color in [Color::White, Color::Black] {
+                for square in (0..8).flat_map(|y| (0..8).map(move |x| Position { x, y })) {
+                    let pawn_attack = board
+                        .attackers_of(square, color)
+                        .iter()
+                        .any(|&attacker| board[attacker].unwrap().piece_type == PieceType::Pawn);
+                    assert_eq!(board.is_attacked_by_pawn(square, color), pawn_attack);
+                }
+            }
+        }
+    }
+
+    mod xray_attackers {
+        use super::*;
+
+        #[test]
+        fn rook_behind_rook() {
+            let board = Board::from_fen("4k3/8/8/3p4/8/8/3R4/3R2K1").unwrap();
+            let target = Position { x: 3, y: 4 };
+            assert_eq!(
+                board.attackers_of(target, Color::White),
+                vec![Position { x: 3, y: 1 }]
+            );
+            assert_eq!(
+                board.xray_attackers(target, Color::White),
+                vec![Position { x: 3, y: 0 }]
+            );
+        }
+
+        #[test]
+        fn through_enemy_piece() {
+            let board = Board::from_fen("8/8/2k5/8/4r3/8/6B1/4K3").unwrap();
+            assert_eq!(
+                board.xray_attackers(Position { x: 2, y: 5 }, Color::White),
+                vec![Position { x: 6, y: 1 }]
+            );
+        }
+
+        #[test]
+        fn only_one_blocker() {
+            let board = Board::from_fen("4k3/8/8/8/8/3Q4/3P4/3RK3").unwrap();
+            let target = Position { x: 3, y: 7 };
+            assert_eq!(
+                board.attackers_of(target, Color::White),
+                vec![Position { x: 3, y: 2 }]
+            );
+            assert!(board.xray_attackers(target, Color::White).is_empty());
+        }
+
+        #[test]
+        fn wrong_line() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/1P6/B3K3").unwrap();
+            assert_eq!(
+                board.xray_attackers(Position { x: 2, y: 2 }, Color::White),
+                vec![Position { x: 0, y: 0 }]
+            );
+            let board = Board::from_fen("4k3/8/8/8/8/8/1P6/R3K3").unwrap();
+            assert!(board
+                .xray_attackers(Position { x: 2, y: 2 }, Color::White)
+                .is_empty());
+        }
+    }
+
+    mod hanging_pieces {
+        use super::*;
+
+        #[test]
+        fn undefended() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 6, y: 7 }, Position { x: 3, y: 2 })
+                .unwrap();
+            assert_eq!(
+                board.hanging_pieces(Color::Black),
+                vec![Position { x: 3, y: 2 }]
+            );
+            assert!(board.hanging_pieces(Color::White).is_empty());
+        }
+
+        #[test]
+        fn defended_queen_attacked_by_pawn() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 3, y: 7 }, Position { x: 4, y: 2 })
+                .unwrap();
+            board
+                .move_piece(Position { x: 6, y: 7 }, Position { x: 5, y: 4 })
+                .unwrap();
+            assert_eq!(
+                board.hanging_pieces(Color::Black),
+                vec![Position { x: 4, y: 2 }]
+            );
+        }
+
+        #[test]
+        fn defended_attacked_by_equal() {
+            let mut board = Board::new();
+            board
+                .move_piece(Position { x: 1, y: 0 }, Position { x: 2, y: 2 })
+                .unwrap();
+            board
+                .move_piece(Position { x: 6, y: 7 }, Position { x: 3, y: 4 })
+                .unwrap();
+            board
+                .move_piece(Position { x: 1, y: 7 }, Position { x: 1, y: 5 })
+                .unwrap();
+            assert!(board.hanging_pieces(Color::Black).is_empty());
+            assert!(board.hanging_pieces(Color::White).is_empty());
+        }
+    }
+
+    mod rank {
+        use super::*;
+
+        #[test]
+        fn back_rank() {
+            let board = Board::new();
+            let rank: Vec<(Position, Option<Piece>)> = board.rank(7).unwrap().collect();
+            assert_eq!(rank.len(), 8);
+            for ((position, piece), x) in rank.into_iter().zip(0..) {
+                assert_eq!(position, Position::new(x, 7).unwrap());
+                assert_eq!(piece.unwrap().color, Color::Black);
+            }
+            assert_eq!(
+                board.rank(7).unwrap().nth(4).unwrap().1.unwrap().piece_type,
+                PieceType::King
+            );
+        }
+
+        #[test]
+        fn out_of_bounds() {
+            assert_eq!(Board::new().rank(8).err(), Some(PositionOutOfBounds(0, 8)));
+        }
+    }
+
+    mod file {
+        use super::*;
+
+        #[test]
+        fn doubled_pawns() {
+            let board = Board::from_fen("4k3/2p5/8/8/8/2P5/2P5/4K3").unwrap();
+            let pieces: Vec<Option<Piece>> =
+                board.file(2).unwrap().map(|(_, piece)| piece).collect();
+            assert_eq!(pieces.len(), 8);
+            assert!(pieces
+                .iter()
+                .take(3)
+                .skip(1)
+                .all(|piece| piece.unwrap().color == Color::White));
+            assert_eq!(pieces[6].unwrap().color, Color::Black);
+            assert_eq!(pieces.iter().filter(|piece| piece.is_some()).count(), 3);
+            assert_eq!(
+                board.file(2).unwrap().next().unwrap().0,
+                Position::new(2, 0).unwrap()
+            );
+        }
+
+        #[test]
+        fn out_of_bounds() {
+            assert_eq!(Board::new().file(8).err(), Some(PositionOutOfBounds(8, 0)));
+        }
+    }
+
+    mod hash {
+        use super::*;
+        use std::collections::HashMap;
+
+        #[test]
+        fn map_key() {
+            let mut moved = Board::new();
+            moved
+                .move_piece(Position { x: 0, y: 0 }, Position { x: 0, y: 2 })
+                .unwrap();
+            moved
+                .move_piece(Position { x: 0, y: 2 }, Position { x: 0, y: 0 })
+                .unwrap();
+            let mut evaluations = HashMap::new();
+            evaluations.insert(Board::new(), 0);
+            evaluations.insert(moved.clone(), 1);
+            assert_eq!(evaluations.len(), 2);
+            assert_eq!(evaluations[&Board::new()], 0);
+            assert_eq!(evaluations[&moved], 1);
+        }
+    }
+
+    mod same_position {
+        use super::*;
+
+        #[test]
+        fn ignores_moved() {
+            let board = Board::new();
+            let mut other = Board::new();
+            other
+                .move_piece(Position { x: 0, y: 0 }, Position { x: 0, y: 2 })
+                .unwrap();
+            other
+                .move_piece(Position { x: 0, y: 2 }, Position { x: 0, y: 0 })
+                .unwrap();
+            assert_ne!(board, other);
+            assert!(board.same_position(&other));
+            assert!(other.same_position(&board));
+        }
+
+        #[test]
+        fn different_pieces() {
+            let board = Board::new();
+            let other = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKQBNR").unwrap();
+            assert!(!board.same_position(&other));
+            let other = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1").unwrap();
+            assert!(!board.same_position(&other));
+        }
+    }
+
+    mod ray {
+        use super::*;
+
+        #[test]
+        fn ignores_pieces() {
+            let board = Board::new();
+            let ray: Vec<Position> = board.ray(Position { x: 0, y: 0 }, Direction::N).collect();
+            assert_eq!(
+                ray,
+                (1..8).map(|y| Position { x: 0, y }).collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn diagonal() {
+            let board = Board::new();
+            let ray: Vec<Position> = board.ray(Position { x: 2, y: 0 }, Direction::NE).collect();
+            assert_eq!(ray.len(), 5);
+            assert_eq!(ray.last(), Some(&Position { x: 7, y: 5 }));
+        }
+
+        #[test]
+        fn edge() {
+            let board = Board::new();
+            assert_eq!(board.ray(Position { x: 7, y: 3 }, Direction::E).count(), 0);
+            assert_eq!(board.ray(Position { x: 7, y: 3 }, Direction::W).count(), 7);
+        }
+    }
+
+    mod explosion {
+        use super::*;
+
+        #[test]
+        fn spares_pawns() {
+            let board = Board::from_fen("4k3/8/8/2ppp3/2pQp3/2ppp3/8/4K3").unwrap();
+            assert_eq!(
+                board.explosion(Position { x: 3, y: 3 }),
+                vec![Position { x: 3, y: 3 }]
+            );
+        }
+
+        #[test]
+        fn both_colors() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/3NrB2/4K3").unwrap();
+            let mut result = board.explosion(Position { x: 4, y: 1 });
+            result.sort();
+            let mut expected_result = vec![
+                Position { x: 3, y: 1 },
+                Position { x: 4, y: 1 },
+                Position { x: 5, y: 1 },
+                Position { x: 4, y: 0 },
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn empty_center() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3").unwrap();
+            assert_eq!(
+                board.explosion(Position { x: 4, y: 1 }),
+                vec![Position { x: 4, y: 0 }]
+            );
+        }
+    }
+
+    mod to_ascii_art {
+        use super::*;
+
+        #[test]
+        fn white_perspective() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3").unwrap();
+            let expected_result = "  +---+---+---+---+---+---+---+---+
+8 |   |   |   |   | k |   |   |   |
+  +---+---+---+---+---+---+---+---+
+7 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+6 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+5 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+4 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+3 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+2 |   |   |   |   |   |   |   |   |
+  +---+---+---+---+---+---+---+---+
+1 | R |   |   |   | K |   |   |   |
+  +---+---+---+---+---+---+---+---+
+    a   b   c   d   e   f   g   h
+";
+            assert_eq!(board.to_ascii_art(Color::White), expected_result);
+        }
+
+        #[test]
+        fn black_perspective() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3").unwrap();
+            let art = board.to_ascii_art(Color::Black);
+            let lines: Vec<&str> = art.lines().collect();
+            assert_eq!(lines.len(), 18);
+            assert_eq!(lines[1], "1 |   |   |   | K |   |   |   | R |");
+            assert_eq!(lines[15], "8 |   |   |   | k |   |   |   |   |");
+            assert_eq!(lines[17], "    h   g   f   e   d   c   b   a");
+        }
+    }
+
+    mod to_ansi {
+        use super::*;
+
+        #[test]
+        fn square_colors() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3").unwrap();
+            let drawing = board.to_ansi();
+            let lines: Vec<&str> = drawing.lines().collect();
+            let first_rank = format!(
+                "1 {ANSI_DARK}   {ANSI_LIGHT}   {ANSI_DARK}   {ANSI_LIGHT}   {ANSI_DARK} ♔ {ANSI_LIGHT}   {ANSI_DARK}   {ANSI_LIGHT}   {ANSI_RESET}"
+            );
+            assert_eq!(lines[7], first_rank);
+            assert!(lines[0].starts_with(&format!("8 {ANSI_LIGHT}")));
+            assert!(lines[0].contains('♚'));
+            assert_eq!(lines[8], "   a  b  c  d  e  f  g  h ");
+        }
+    }
+
+    mod material_list {
+        use super::*;
+
+        #[test]
+        fn start_position() {
+            let board = Board::new();
+            let material = board.material_list(Color::Black);
+            assert_eq!(material.len(), 16);
+            assert_eq!(
+                material
+                    .iter()
+                    .filter(|&&piece_type| piece_type == PieceType::Pawn)
+                    .count(),
+                8
+            );
+            assert_eq!(material.last(), Some(&PieceType::King));
+        }
+
+        #[test]
+        fn bare_king() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/Q3K3").unwrap();
+            assert_eq!(board.material_list(Color::Black), vec![PieceType::King]);
+        }
+    }
+
+    mod captured_pieces {
+        use super::*;
+
+        #[test]
+        fn nothing_captured() {
+            let board = Board::new();
+            assert!(board
+                .captured_pieces(&Board::new(), Color::White)
+                .is_empty());
+        }
+
+        #[test]
+        fn promotion() {
+            // White has promoted a pawn to a second queen and lost a knight
+            let board = Board::from_fen("Q3k3/8/8/8/8/8/1PPPPPPP/R1BQKBNR").unwrap();
+            assert_eq!(
+                board.captured_pieces(&Board::new(), Color::White),
+                vec![PieceType::Pawn, PieceType::Knight]
+            );
+            assert_eq!(board.captured_pieces(&Board::new(), Color::Black).len(), 15);
+        }
+    }
+
+    mod occupied_squares {
+        use super::*;
+
+        #[test]
+        fn after_move() {
+            let mut board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3").unwrap();
+            board
+                .move_piece(Position { x: 4, y: 1 }, Position { x: 4, y: 3 })
+                .unwrap();
+            assert_eq!(
+                board.occupied_squares(Color::White),
+                vec![Position { x: 4, y: 0 }, Position { x: 4, y: 3 }]
+            );
+            assert_eq!(
+                board.occupied_squares(Color::Black),
+                vec![Position { x: 4, y: 7 }]
+            );
+        }
+    }
+
+    mod is_trapped {
+        use super::*;
+
+        #[test]
+        fn bishop_on_a7() {
+            let board = Board::from_fen("2k5/B1p5/1p6/8/8/8/8/4K3").unwrap();
+            assert!(board.is_trapped(Position { x: 0, y: 6 }));
+        }
+
+        #[test]
+        fn bishop_can_escape() {
+            let board = Board::from_fen("2k5/B1p5/8/8/8/8/8/4K3").unwrap();
+            assert!(!board.is_trapped(Position { x: 0, y: 6 }));
+        }
+
+        #[test]
+        fn defended_square_attacked_by_queen() {
+            let board = Board::from_fen("4k3/8/8/8/8/q2p4/8/NR2K3").unwrap();
+            assert!(!board.is_trapped(Position { x: 0, y: 0 }));
+        }
+
+        #[test]
+        fn empty_square() {
+            assert!(!Board::new().is_trapped(Position { x: 3, y: 3 }));
+        }
+    }
+
+    mod evaluate {
+        use super::*;
+
+        #[test]
+        fn start_position() {
+            let board = Board::new();
+            assert_eq!(board.evaluate(Color::White), 0);
+            assert_eq!(board.evaluate(Color::Black), 0);
+        }
+
+        #[test]
+        fn central_knight() {
+            let rim = Board::from_fen("4k3/8/8/8/N7/8/8/4K3").unwrap();
+            let centre = Board::from_fen("4k3/8/8/8/3N4/8/8/4K3").unwrap();
+            assert!(centre.evaluate(Color::White) > rim.evaluate(Color::White));
+        }
+
+        #[test]
+        fn castled_king() {
+            let castled = Board::from_fen("4k3/8/8/8/8/8/8/6K1").unwrap();
+            let centre = Board::from_fen("4k3/8/8/8/8/8/8/4K3").unwrap();
+            assert!(castled.evaluate(Color::White) > centre.evaluate(Color::White));
+        }
+
+        #[test]
+        fn color_symmetry() {
+            let board = Board::from_fen("r3k3/pp3p2/8/3N4/8/8/PPP5/4K2R").unwrap();
+            assert_eq!(board.evaluate(Color::White), -board.evaluate(Color::Black));
+            assert_eq!(
+                board.evaluate(Color::White),
+                board.color_flipped().evaluate(Color::Black)
+            );
+        }
+    }
+
+    mod activity_score {
+        use super::*;
+
+        #[test]
+        fn developed_pieces() {
+            let board =
+                Board::from_fen("rnbqkbnr/pppppppp/8/8/2B1P3/5N2/PPPP1PPP/RNBQK2R").unwrap();
+            assert!(board.activity_score(Color::White) > board.activity_score(Color::Black));
+        }
+
+        #[test]
+        fn unsafe_squares_not_counted() {
+            let board = Board::from_fen("4k3/8/8/3p4/8/8/8/N3K3").unwrap();
+            assert_eq!(board.activity_score(Color::White), 8);
+            let board = Board::from_fen("4k3/8/8/8/p7/8/8/N3K3").unwrap();
+            assert_eq!(board.activity_score(Color::White), 4);
+        }
+    }
+
+    mod king_shield {
+        use super::*;
+
+        #[test]
+        fn castled_kingside() {
+            // Pawns on f2, g2 and h2 give the highest score a king can have
+            let board = Board::from_fen("r4rk1/ppp2ppp/8/8/8/8/PPP2PPP/R4RK1").unwrap();
+            assert_eq!(board.king_shield(Color::White), 6);
+            assert_eq!(board.king_shield(Color::Black), 6);
+        }
+
+        #[test]
+        fn two_ranks() {
+            // Pawns behind the first rank of the shield add nothing
+            let board = Board::from_fen("6k1/8/8/8/8/5PPP/5PPP/6K1").unwrap();
+            assert_eq!(board.king_shield(Color::White), 6);
+        }
+
+        #[test]
+        fn pushed_pawns() {
+            let board = Board::from_fen("6k1/8/8/8/8/5PPP/8/6K1").unwrap();
+            assert_eq!(board.king_shield(Color::White), 3);
+        }
+
+        #[test]
+        fn edge_file() {
+            // Only the a and b-files are in front of the king, so the most it can score is 4, and the c-pawn is ignored
+            let board = Board::from_fen("6k1/8/8/8/8/8/PPP5/K7").unwrap();
+            assert_eq!(board.king_shield(Color::White), 4);
+            // A pawn two ranks in front scores half as much as one directly in front
+            let board = Board::from_fen("6k1/8/8/8/8/1P6/P7/K7").unwrap();
+            assert_eq!(board.king_shield(Color::White), 3);
+        }
+
+        #[test]
+        fn no_ranks_in_front() {
+            // Kings on their last rank have no shield squares, whatever pawns are nearby
+            let board = Board::from_fen("K7/PP6/8/8/8/8/pp6/k7").unwrap();
+            assert_eq!(board.king_shield(Color::White), 0);
+            assert_eq!(board.king_shield(Color::Black), 0);
+        }
+
+        #[test]
+        fn enemy_pawns_ignored() {
+            let board = Board::from_fen("6k1/8/8/8/8/6p1/5p1p/6K1").unwrap();
+            assert_eq!(board.king_shield(Color::White), 0);
+        }
+    }
+
+    mod open_files {
+        use super::*;
+
+        #[test]
+        fn d_file_cleared() {
+            let board = Board::from_fen("rnbqkbnr/ppp1pppp/8/8/8/8/PPP1PPPP/RNBQKBNR").unwrap();
+            assert_eq!(board.open_files(), vec![3]);
+        }
+
+        #[test]
+        fn start_position() {
+            assert!(Board::new().open_files().is_empty());
+        }
+    }
+
+    mod semi_open_files {
+        use super::*;
+
+        #[test]
+        fn exchanged_pawns() {
+            let board = Board::from_fen("4k3/ppp1pppp/8/8/8/8/PP2PPPP/4K3").unwrap();
+            assert_eq!(board.semi_open_files(Color::White), vec![2, 3]);
+            assert_eq!(board.semi_open_files(Color::Black), vec![3]);
+        }
+    }
+
+    mod half_open_files {
+        use super::*;
+
+        #[test]
+        fn cleared_e_file() {
+            let board = Board::from_fen("4k3/ppp1pppp/8/8/8/8/PPP2PPP/4K3").unwrap();
+            assert_eq!(board.open_files(), vec![3]);
+            assert_eq!(board.half_open_files(Color::White), vec![4]);
+            assert!(board.half_open_files(Color::Black).is_empty());
+        }
+
+        #[test]
+        fn start_position() {
+            let board = Board::new();
+            assert!(board.half_open_files(Color::White).is_empty());
+            assert!(board.half_open_files(Color::Black).is_empty());
+        }
+    }
+
+    mod passed_pawns {
+        use super::*;
+
+        #[test]
+        fn blocked_by_adjacent_file() {
+            let board = Board::from_fen("4k3/3p4/8/4P3/8/8/8/4K3").unwrap();
+            assert!(board.passed_pawns(Color::White).is_empty());
+            assert!(board.passed_pawns(Color::Black).is_empty());
+        }
+
+        #[test]
+        fn enemy_pawn_behind() {
+            let board = Board::from_fen("4k3/8/8/4P3/3p4/8/8/4K3").unwrap();
+            assert_eq!(
+                board.passed_pawns(Color::White),
+                vec![Position { x: 4, y: 4 }]
+            );
+            assert_eq!(
+                board.passed_pawns(Color::Black),
+                vec![Position { x: 3, y: 3 }]
+            );
+        }
+    }
+
+    mod passed_pawn_distance_to_promotion {
+        use super::*;
+
+        #[test]
+        fn white_on_e6() {
+            let board = Board::from_fen("4k3/8/4P3/8/8/8/8/4K3").unwrap();
+            assert_eq!(
+                board.passed_pawn_distance_to_promotion(Color::White),
+                vec![(Position { x: 4, y: 5 }, 2)]
+            );
+        }
+
+        #[test]
+        fn black_on_b3() {
+            let board = Board::from_fen("4k3/8/8/8/8/1p6/8/4K3").unwrap();
+            assert_eq!(
+                board.passed_pawn_distance_to_promotion(Color::Black),
+                vec![(Position { x: 1, y: 2 }, 2)]
+            );
+        }
+    }
+
+    mod legal_move_count {
+        use super::*;
+        use crate::game::GameState;
+
+        fn matches_generated(placement: &str, color: Color, en_passant: Option<Position>) {
+            let board = Board::from_fen(placement).unwrap();
+            let state = GameState::from_board(board.clone(), color);
+            let (kingside, queenside) = (
+                state.castling_rights().kingside(color),
+                state.castling_rights().queenside(color),
+            );
+            let mut moves = state.all_legal_moves();
+            for (position, _) in board.pieces_of(color) {
+                moves.extend(en_passant.and_then(|target| board.en_passant_move(position, target)));
+            }
+            moves.retain(|chess_move| {
+                let mut after = board.clone();
+                after.execute_move(*chess_move).is_ok() && !after.is_in_check(color)
+            });
+            assert_eq!(
+                board.legal_move_count(color, en_passant, kingside, queenside),
+                moves.len(),
+                "{placement}"
+            );
+        }
+
+        #[test]
+        fn start_position() {
+            matches_generated(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
+                Color::White,
+                None,
+            );
+            matches_generated(
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
+                Color::Black,
+                None,
+            );
+        }
+
+        #[test]
+        fn castling_and_pins() {
+            matches_generated(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                Color::White,
+                None,
+            );
+            matches_generated("4k3/8/8/8/4r3/8/4N3/4K3", Color::White, None);
+        }
+
+        #[test]
+        fn in_check() {
+            matches_generated("4k3/8/8/8/Rb6/8/8/1N2K3", Color::White, None);
+            matches_generated("4k3/8/8/8/8/5n2/8/r3K3", Color::White, None);
+        }
+
+        #[test]
+        fn promotions() {
+            matches_generated("3nk3/2P5/8/8/8/8/8/4K3", Color::White, None);
+        }
+
+        #[test]
+        fn en_passant() {
+            let target = Position::new(3, 5).ok();
+            matches_generated("4k3/8/8/3pP3/8/8/8/4K3", Color::White, target);
+            // Taking en passant would uncover the rook's check along the rank
+            matches_generated("8/8/8/K2pP2r/8/8/8/7k", Color::White, target);
+        }
+    }
+
+    mod legal_moves_iter {
+        use super::*;
+
+        #[test]
+        fn matches_count() {
+            for (placement, color) in [
+                (
+                    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                    Color::White,
+                ),
+                (
+                    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                    Color::Black,
+                ),
+                ("4k3/8/8/8/Rb6/8/8/1N2K3", Color::White),
+                ("3nk3/2P5/8/8/8/8/8/4K3", Color::White),
+            ] {
+                let board = Board::from_fen(placement).unwrap();
+                assert_eq!(
+                    board.legal_moves_iter(color, None, true, true).count(),
+                    board.legal_move_count(color, None, true, true),
+                    "{placement}"
+                );
+            }
+        }
+
+        #[test]
+        fn castling_and_en_passant() {
+            let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K2R").unwrap();
+            let moves: Vec<ChessMove> = board
+                .legal_moves_iter(Color::White, Position::new(3, 5).ok(), true, false)
+                .collect();
+            assert!(moves
+                .iter()
+                .any(|chess_move| matches!(chess_move, ChessMove::Castle(_, _))));
+            assert!(moves
+                .iter()
+                .any(|chess_move| chess_move.taken_position() == Position::new(3, 4).ok()));
+        }
+
+        #[test]
+        fn pinned_piece() {
+            // The knight is pinned to the king by the rook, so cannot move
+            let board = Board::from_fen("4k3/8/8/8/4r3/8/4N3/4K3").unwrap();
+            assert!(board
+                .legal_moves_iter(Color::White, None, false, false)
+                .all(|chess_move| chess_move.movement().from_position != Position { x: 4, y: 1 }));
+        }
+    }
+
+    mod has_legal_move {
+        use super::*;
+
+        #[test]
+        fn stalemate() {
+            // The pawn is blocked, so every candidate is tried before giving up
+            let board = Board::from_fen("7k/5Q2/6K1/8/p7/P7/8/8").unwrap();
+            assert!(!board.has_legal_move(Color::Black, None));
+            assert_eq!(board.legal_move_count(Color::Black, None, false, false), 0);
+        }
+
+        #[test]
+        fn checkmate() {
+            let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1").unwrap();
+            assert!(!board.has_legal_move(Color::Black, None));
+        }
+
+        #[test]
+        fn escape_from_check() {
+            let board = Board::from_fen("4k3/8/8/8/Rb6/8/8/1N2K3").unwrap();
+            assert!(board.has_legal_move(Color::White, None));
+        }
+
+        #[test]
+        fn only_en_passant() {
+            // The king is boxed in and the pawn is blocked, but can take the pawn that just moved past it
+            let board = Board::from_fen("8/8/8/8/3Pp3/1Q2P3/8/k1K5").unwrap();
+            assert!(!board.has_legal_move(Color::Black, None));
+            assert!(board.has_legal_move(Color::Black, Position::new(3, 2).ok()));
+        }
+    }
+
+    mod has_bishop_pair {
+        use super::*;
+
+        #[test]
+        fn opposite_colored_bishops() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1KB2").unwrap();
+            assert!(board.has_bishop_pair(Color::White));
+        }
+
+        #[test]
+        fn same_colored_bishops() {
+            // Both bishops are on dark squares
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1K1B1").unwrap();
+            assert!(!board.has_bishop_pair(Color::White));
+        }
+
+        #[test]
+        fn single_bishop() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1K3").unwrap();
+            assert!(!board.has_bishop_pair(Color::White));
+        }
+
+        #[test]
+        fn enemy_bishops_do_not_count() {
+            let board = Board::from_fen("4kb2/8/8/8/8/8/8/2B1K3").unwrap();
+            assert!(!board.has_bishop_pair(Color::White));
+            assert!(!board.has_bishop_pair(Color::Black));
+        }
     }
 
-    #[test]
-    fn test_offset_negative_sw() {
-        assert_eq!(
-            Position { x: 5, y: 5 },
-            (Position { x: 6, y: 6 } + Offset { x: -1, y: -1 }).unwrap()
-        );
-    }
-}
+    mod pawn_structure {
+        use super::*;
 
-#[cfg(test)]
-mod board_tests {
-    use super::*;
+        #[test]
+        fn start_position() {
+            let board = Board::new();
+            for color in [Color::White, Color::Black] {
+                assert_eq!(board.pawn_structure(color), PawnStructure::default());
+            }
+        }
 
-    mod move_piece {
-        use super::*;
+        #[test]
+        fn pawn_chain() {
+            let board = Board::from_fen("4k3/p7/8/8/2P5/1P6/P7/4K3").unwrap();
+            assert_eq!(
+                board.pawn_structure(Color::White),
+                PawnStructure {
+                    doubled: 0,
+                    isolated: 0,
+                    passed: 1,
+                }
+            );
+            assert_eq!(
+                board.pawn_structure(Color::Black),
+                PawnStructure {
+                    doubled: 0,
+                    isolated: 1,
+                    passed: 0,
+                }
+            );
+        }
 
         #[test]
-        fn move_queen() {
-            let mut board = Board::new();
-            board
-                .move_piece(Position { x: 3, y: 0 }, Position { x: 5, y: 5 })
-                .unwrap();
-            assert_eq!(board[Position { x: 3, y: 0 }], None);
+        fn doubled_and_isolated() {
+            let board = Board::from_fen("4k3/3p1p2/8/8/5P2/1P3P2/1P3P1P/4K3").unwrap();
             assert_eq!(
-                board[Position { x: 5, y: 5 }].unwrap(),
-                Piece {
-                    color: Color::White,
-                    piece_type: PieceType::Queen,
-                    moved: true
+                board.pawn_structure(Color::White),
+                PawnStructure {
+                    doubled: 3,
+                    isolated: 6,
+                    passed: 3,
                 }
-            )
+            );
         }
     }
 
-    mod check_positions {
+    mod backward_pawns {
         use super::*;
 
         #[test]
-        fn pawn() {
-            let mut board = Board::new();
-            board
-                .move_piece(Position { x: 5, y: 6 }, Position { x: 5, y: 4 })
-                .unwrap();
-            board
-                .move_piece(Position { x: 4, y: 1 }, Position { x: 4, y: 3 })
-                .unwrap();
-            let mut result = board.check_positions(Position { x: 4, y: 3 }).unwrap();
-            result.sort();
-            let mut expected_result = vec![Position { x: 5, y: 4 }, Position { x: 4, y: 4 }];
-            expected_result.sort();
-            assert_eq!(result, expected_result)
+        fn white_d3() {
+            let board = Board::from_fen("4k3/8/8/2p5/2P1P3/3P4/8/4K3").unwrap();
+            assert_eq!(
+                board.backward_pawns(Color::White),
+                vec![Position { x: 3, y: 2 }]
+            );
+            assert_eq!(
+                board.backward_pawns(Color::Black),
+                vec![Position { x: 2, y: 4 }]
+            );
         }
 
         #[test]
-        fn bishop() {
-            let mut board = Board::new();
-            board
-                .move_piece(Position { x: 2, y: 7 }, Position { x: 4, y: 5 })
-                .unwrap();
-            let mut result = board.check_positions(Position { x: 4, y: 5 }).unwrap();
-            result.sort();
-            let mut expected_result = vec![
-                Position { x: 0, y: 1 },
-                Position { x: 1, y: 2 },
-                Position { x: 2, y: 3 },
-                Position { x: 3, y: 4 },
-                Position { x: 7, y: 2 },
-                Position { x: 6, y: 3 },
-                Position { x: 5, y: 4 },
-            ];
-            expected_result.sort();
-            assert_eq!(result, expected_result)
+        fn stop_square_not_attacked() {
+            let board = Board::from_fen("4k3/8/8/8/2P1P3/3P4/8/4K3").unwrap();
+            assert!(board.backward_pawns(Color::White).is_empty());
         }
 
         #[test]
-        fn rook() {
-            let mut board = Board::new();
-            board
-                .move_piece(Position { x: 0, y: 0 }, Position { x: 3, y: 4 })
-                .unwrap();
-            let mut result = board.check_positions(Position { x: 3, y: 4 }).unwrap();
-            result.sort();
-            let mut expected_result = vec![
-                Position { x: 0, y: 4 },
-                Position { x: 1, y: 4 },
-                Position { x: 2, y: 4 },
-                Position { x: 4, y: 4 },
-                Position { x: 5, y: 4 },
-                Position { x: 6, y: 4 },
-                Position { x: 7, y: 4 },
-                Position { x: 3, y: 2 },
-                Position { x: 3, y: 3 },
-                Position { x: 3, y: 5 },
-                Position { x: 3, y: 6 },
-            ];
-            expected_result.sort();
-            assert_eq!(result, expected_result)
+        fn start_position() {
+            let board = Board::new();
+            assert!(board.backward_pawns(Color::White).is_empty());
+            assert!(board.backward_pawns(Color::Black).is_empty());
         }
+    }
+
+    mod pawn_chains {
+        use super::*;
 
         #[test]
-        fn queen() {
-            let mut board = Board::new();
-            board
-                .move_piece(Position { x: 3, y: 7 }, Position { x: 1, y: 3 })
-                .unwrap();
-            let mut result = board.check_positions(Position { x: 1, y: 3 }).unwrap();
-            result.sort();
-            let mut expected_result = vec![
-                Position { x: 0, y: 3 },
-                Position { x: 2, y: 3 },
-                Position { x: 3, y: 3 },
-                Position { x: 4, y: 3 },
-                Position { x: 5, y: 3 },
-                Position { x: 6, y: 3 },
-                Position { x: 7, y: 3 },
-                Position { x: 1, y: 1 },
-                Position { x: 1, y: 2 },
-                Position { x: 1, y: 4 },
-                Position { x: 1, y: 5 },
-                Position { x: 0, y: 2 },
-                Position { x: 2, y: 4 },
-                Position { x: 3, y: 5 },
-                Position { x: 0, y: 4 },
-                Position { x: 2, y: 2 },
-                Position { x: 3, y: 1 },
-            ];
-            expected_result.sort();
-            assert_eq!(result, expected_result)
+        fn two_chains() {
+            let board = Board::from_fen("4k3/8/8/8/2P5/1P3P2/P5P1/4K3").unwrap();
+            assert_eq!(
+                board.pawn_chains(Color::White),
+                vec![
+                    vec![
+                        Position { x: 0, y: 1 },
+                        Position { x: 1, y: 2 },
+                        Position { x: 2, y: 3 }
+                    ],
+                    vec![Position { x: 6, y: 1 }, Position { x: 5, y: 2 }]
+                ]
+            );
+        }
+
+        #[test]
+        fn branching_chain() {
+            let board = Board::from_fen("4k3/8/8/8/3P4/2P1P3/8/4K3").unwrap();
+            assert_eq!(
+                board.pawn_chains(Color::White),
+                vec![vec![
+                    Position { x: 2, y: 2 },
+                    Position { x: 4, y: 2 },
+                    Position { x: 3, y: 3 }
+                ]]
+            );
+        }
+
+        #[test]
+        fn start_position() {
+            let board = Board::new();
+            assert!(board.pawn_chains(Color::White).is_empty());
+            assert!(board.pawn_chains(Color::Black).is_empty());
         }
     }
 
-    mod check_directions {
+    mod check_squares {
         use super::*;
 
         #[test]
-        fn cardinal_directions() {
-            let board = Board::new();
-            let mut result = board.check_directions(
-                Position { x: 3, y: 4 },
-                vec![Direction::N, Direction::E, Direction::S, Direction::W],
-                Color::White,
-            );
+        fn rook_blocked() {
+            let board = Board::from_fen("4k3/8/8/4p3/8/8/8/4K3").unwrap();
+            let mut result = board.check_squares(Color::White, PieceType::Rook);
             result.sort();
             let mut expected_result = vec![
-                Position { x: 0, y: 4 },
-                Position { x: 1, y: 4 },
-                Position { x: 2, y: 4 },
+                Position { x: 0, y: 7 },
+                Position { x: 1, y: 7 },
+                Position { x: 2, y: 7 },
+                Position { x: 3, y: 7 },
+                Position { x: 5, y: 7 },
+                Position { x: 6, y: 7 },
+                Position { x: 7, y: 7 },
+                Position { x: 4, y: 6 },
+                Position { x: 4, y: 5 },
                 Position { x: 4, y: 4 },
-                Position { x: 5, y: 4 },
-                Position { x: 6, y: 4 },
-                Position { x: 7, y: 4 },
-                Position { x: 3, y: 2 },
-                Position { x: 3, y: 3 },
-                Position { x: 3, y: 5 },
-                Position { x: 3, y: 6 },
             ];
             expected_result.sort();
-            assert_eq!(result, expected_result)
+            assert_eq!(result, expected_result);
         }
 
         #[test]
-        fn diagonal_directions() {
-            let board = Board::new();
-            let mut result = board.check_directions(
-                Position { x: 4, y: 5 },
-                vec![Direction::NE, Direction::SE, Direction::SW, Direction::NW],
-                Color::Black,
+        fn pawns() {
+            let board = Board::from_fen("4k3/3P4/8/8/8/8/8/4K3").unwrap();
+            assert_eq!(
+                board.check_squares(Color::White, PieceType::Pawn),
+                vec![Position { x: 5, y: 6 }]
             );
+            let mut result = board.check_squares(Color::Black, PieceType::Pawn);
             result.sort();
-            let mut expected_result = vec![
-                Position { x: 0, y: 1 },
-                Position { x: 1, y: 2 },
-                Position { x: 2, y: 3 },
-                Position { x: 3, y: 4 },
-                Position { x: 7, y: 2 },
-                Position { x: 6, y: 3 },
-                Position { x: 5, y: 4 },
-            ];
-            expected_result.sort();
-            assert_eq!(result, expected_result)
+            assert_eq!(
+                result,
+                vec![Position { x: 3, y: 1 }, Position { x: 5, y: 1 }]
+            );
         }
 
         #[test]
-        fn all_directions() {
-            let board = Board::new();
-            let mut result = board.check_directions(
-                Position { x: 1, y: 3 },
+        fn king() {
+            assert!(Board::new()
+                .check_squares(Color::White, PieceType::King)
+                .is_empty());
+        }
+    }
+
+    mod absolute_pins {
+        use super::*;
+
+        #[test]
+        fn rook_pins_knight() {
+            let board = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3").unwrap();
+            assert_eq!(
+                board.absolute_pins(Color::White),
+                vec![(
+                    Position { x: 4, y: 1 },
+                    Position { x: 4, y: 0 },
+                    Direction::N
+                )]
+            );
+        }
+
+        #[test]
+        fn bishop_and_queen() {
+            let board = Board::from_fen("6k1/8/8/1b6/8/3P4/4K1Nq/8").unwrap();
+            assert_eq!(
+                board.absolute_pins(Color::White),
                 vec![
-                    Direction::N,
-                    Direction::NE,
-                    Direction::E,
-                    Direction::SE,
-                    Direction::S,
-                    Direction::SW,
-                    Direction::W,
-                    Direction::NW,
-                ],
-                Color::Black,
+                    (
+                        Position { x: 6, y: 1 },
+                        Position { x: 4, y: 1 },
+                        Direction::E
+                    ),
+                    (
+                        Position { x: 3, y: 2 },
+                        Position { x: 4, y: 1 },
+                        Direction::NW
+                    )
+                ]
             );
+        }
+
+        #[test]
+        fn no_pin() {
+            // Rook on a diagonal, two pieces in the way, and an enemy piece in the way
+            let board = Board::from_fen("6k1/8/8/4r3/1r2n2q/2P1N1P1/5P2/4K3").unwrap();
+            assert!(board.absolute_pins(Color::White).is_empty());
+            assert!(Board::new().absolute_pins(Color::White).is_empty());
+        }
+    }
+
+    mod pinned_piece_moves {
+        use super::*;
+
+        #[test]
+        fn rook_along_pin() {
+            let board = Board::from_fen("4r1k1/8/8/8/8/8/4R3/4K3").unwrap();
+            let mut result = board.pinned_piece_moves(Position { x: 4, y: 1 });
             result.sort();
-            let mut expected_result = vec![
-                Position { x: 0, y: 3 },
-                Position { x: 2, y: 3 },
-                Position { x: 3, y: 3 },
-                Position { x: 4, y: 3 },
-                Position { x: 5, y: 3 },
-                Position { x: 6, y: 3 },
-                Position { x: 7, y: 3 },
-                Position { x: 1, y: 1 },
-                Position { x: 1, y: 2 },
-                Position { x: 1, y: 4 },
-                Position { x: 1, y: 5 },
-                Position { x: 0, y: 2 },
-                Position { x: 2, y: 4 },
-                Position { x: 3, y: 5 },
-                Position { x: 0, y: 4 },
-                Position { x: 2, y: 2 },
-                Position { x: 3, y: 1 },
-            ];
+            let mut expected_result: Vec<Position> = (2..8).map(|y| Position { x: 4, y }).collect();
             expected_result.sort();
-            assert_eq!(result, expected_result)
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn cannot_leave_line() {
+            let board = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3").unwrap();
+            assert!(board.pinned_piece_moves(Position { x: 4, y: 1 }).is_empty());
+            let board = Board::from_fen("4r1k1/8/8/8/8/8/4B3/4K3").unwrap();
+            assert!(board.pinned_piece_moves(Position { x: 4, y: 1 }).is_empty());
+        }
+
+        #[test]
+        fn bishop_takes_pinner() {
+            let board = Board::from_fen("6k1/8/8/8/8/2q5/3B4/4K3").unwrap();
+            let mut result = board.pinned_piece_moves(Position { x: 3, y: 1 });
+            result.sort();
+            assert_eq!(result, vec![Position { x: 2, y: 2 }]);
+        }
+
+        #[test]
+        fn not_pinned() {
+            let board = Board::new();
+            let position = Position { x: 6, y: 0 };
+            assert_eq!(
+                board.pinned_piece_moves(position),
+                board.check_positions(position).unwrap()
+            );
+            assert!(board.pinned_piece_moves(Position { x: 3, y: 3 }).is_empty());
         }
     }
 
-    mod check_direction {
+    mod safe_king_positions {
         use super::*;
 
         #[test]
-        fn no_move_n() {
-            let board = Board::new();
+        fn cannot_move_along_check() {
+            let board = Board::from_fen("4r1k1/8/8/8/8/8/8/4K3").unwrap();
+            let mut result = board.safe_king_positions(Color::White);
+            result.sort();
             assert_eq!(
-                board.check_direction(Position { x: 4, y: 0 }, Direction::N, Color::White),
-                vec![]
+                result,
+                vec![
+                    Position { x: 3, y: 0 },
+                    Position { x: 3, y: 1 },
+                    Position { x: 5, y: 0 },
+                    Position { x: 5, y: 1 }
+                ]
             );
         }
 
         #[test]
-        fn no_move_w() {
-            let board = Board::new();
+        fn defended_piece() {
+            let board = Board::from_fen("6k1/8/8/8/8/8/3r4/2r1K3").unwrap();
             assert_eq!(
-                board.check_direction(Position { x: 5, y: 1 }, Direction::W, Color::White),
-                vec![]
+                board.safe_king_positions(Color::White),
+                vec![Position { x: 3, y: 1 }]
             );
         }
+    }
+
+    mod see {
+        use super::*;
 
         #[test]
-        fn edge_board_e() {
-            let board = Board::new();
-            assert_eq!(
-                board.check_direction(Position { x: 6, y: 5 }, Direction::E, Color::White),
-                vec![Position { x: 7, y: 5 }]
-            );
+        fn even_trade() {
+            let board = Board::from_fen("4k3/8/2p5/3n4/4P3/8/8/4K3").unwrap();
+            assert_eq!(board.see(Position { x: 3, y: 4 }, Color::White), 200);
         }
 
         #[test]
-        fn edge_board_s() {
-            let board = Board::new();
+        fn defender_declines() {
+            let board = Board::from_fen("4k3/8/8/3r4/8/8/3R4/3RK3").unwrap();
+            assert_eq!(board.see(Position { x: 3, y: 4 }, Color::White), 500);
+        }
+
+        #[test]
+        fn battery_revealed() {
+            let board = Board::from_fen("3rk3/3r4/8/3p4/8/8/3R4/3QK3").unwrap();
+            assert_eq!(board.see(Position { x: 3, y: 4 }, Color::White), -400);
+        }
+
+        #[test]
+        fn undefended_pawn() {
+            let board = Board::from_fen("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3").unwrap();
+            assert_eq!(board.see(Position { x: 4, y: 4 }, Color::White), 100);
+        }
+
+        #[test]
+        fn knight_takes_defended_pawn() {
+            let board = Board::from_fen("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3").unwrap();
+            assert_eq!(board.see(Position { x: 4, y: 4 }, Color::White), -200);
+        }
+
+        #[test]
+        fn xray_attacker_joins() {
+            let board = Board::from_fen("4k3/4r3/8/4p3/8/8/4R3/4R1K1").unwrap();
+            let target = Position { x: 4, y: 4 };
             assert_eq!(
-                board.check_direction(Position { x: 3, y: 0 }, Direction::S, Color::White),
-                vec![]
+                board.xray_attackers(target, Color::White),
+                vec![Position { x: 4, y: 0 }]
             );
+            assert_eq!(board.see(target, Color::White), 100);
+            let board = Board::from_fen("4k3/4r3/8/4p3/8/8/4R3/6K1").unwrap();
+            assert_eq!(board.see(target, Color::White), -400);
         }
 
         #[test]
-        fn from_edge_e() {
+        fn nothing_to_take() {
             let board = Board::new();
-            let mut result =
-                board.check_direction(Position { x: 0, y: 2 }, Direction::E, Color::White);
-            result.sort();
-            let mut expected_result = vec![
-                Position { x: 1, y: 2 },
-                Position { x: 2, y: 2 },
-                Position { x: 3, y: 2 },
-                Position { x: 4, y: 2 },
-                Position { x: 5, y: 2 },
-                Position { x: 6, y: 2 },
-                Position { x: 7, y: 2 },
-            ];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+            assert_eq!(board.see(Position { x: 3, y: 3 }, Color::White), 0);
+            assert_eq!(board.see(Position { x: 3, y: 1 }, Color::White), 0);
         }
+    }
+
+    mod clear_outside {
+        use super::*;
 
         #[test]
-        fn from_edge_w() {
-            let board = Board::new();
-            let mut result =
-                board.check_direction(Position { x: 7, y: 4 }, Direction::W, Color::White);
+        fn corner() {
+            let mut board = Board::new();
+            board.clear_outside(Position { x: 4, y: 7 }, Position { x: 7, y: 4 });
+            let mut result: Vec<Position> =
+                board.iter_pieces().map(|(position, _)| position).collect();
             result.sort();
-            let mut expected_result = vec![
-                Position { x: 0, y: 4 },
-                Position { x: 1, y: 4 },
-                Position { x: 2, y: 4 },
-                Position { x: 3, y: 4 },
-                Position { x: 4, y: 4 },
-                Position { x: 5, y: 4 },
-                Position { x: 6, y: 4 },
-            ];
+            let mut expected_result = vec![];
+            for x in 4..8 {
+                expected_result.push(Position { x, y: 6 });
+                expected_result.push(Position { x, y: 7 });
+            }
             expected_result.sort();
             assert_eq!(result, expected_result);
         }
+    }
+
+    mod color_flipped {
+        use super::*;
 
         #[test]
-        fn take_piece_ne() {
-            let board = Board::new();
-            let mut result =
-                board.check_direction(Position { x: 2, y: 2 }, Direction::NE, Color::White);
-            result.sort();
-            let mut expected_result = vec![
-                Position { x: 3, y: 3 },
-                Position { x: 4, y: 4 },
-                Position { x: 5, y: 5 },
-                Position { x: 6, y: 6 },
-            ];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+        fn start_position() {
+            assert_eq!(Board::new().color_flipped(), Board::new());
         }
 
         #[test]
-        fn take_piece_sw() {
-            let board = Board::new();
-            let mut result =
-                board.check_direction(Position { x: 4, y: 3 }, Direction::SW, Color::Black);
-            result.sort();
-            let mut expected_result = vec![Position { x: 3, y: 2 }, Position { x: 2, y: 1 }];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+        fn pieces_mirrored() {
+            let board = Board::from_fen("4k3/8/8/8/2N5/8/8/4K3").unwrap();
+            let flipped = board.color_flipped();
+            let knight = flipped[Position { x: 2, y: 4 }].unwrap();
+            assert_eq!(knight.color, Color::Black);
+            assert_eq!(knight.piece_type, PieceType::Knight);
+            assert_eq!(flipped[Position { x: 2, y: 3 }], None);
+            assert_eq!(
+                flipped.king_position(Color::White),
+                Some(Position { x: 4, y: 0 })
+            );
+        }
+
+        #[test]
+        fn twice_is_unchanged() {
+            let board =
+                Board::from_fen("r1bqk2r/pppp1ppp/2n2n2/4p3/1bB1P3/5N2/PPPP1PPP/RNBQK2R").unwrap();
+            assert_eq!(board.color_flipped().color_flipped(), board);
         }
     }
 
-    mod check_pawn {
+    mod square_control {
         use super::*;
 
         #[test]
-        fn first_move_white() {
+        fn start_position_balanced() {
             let board = Board::new();
-            let mut result = board.check_pawn(Position { x: 3, y: 3 }, Color::White, false);
-            result.sort();
-            let mut expected_result = vec![Position { x: 3, y: 4 }, Position { x: 3, y: 5 }];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+            let total: i32 = (0..8)
+                .flat_map(|y| (0..8).map(move |x| Position { x, y }))
+                .map(|position| board.square_control(position))
+                .sum();
+            assert_eq!(total, 0);
+            assert_eq!(board.square_control(Position { x: 3, y: 5 }), -2);
         }
 
         #[test]
-        fn first_move_black() {
-            let board = Board::new();
-            let mut result = board.check_pawn(Position { x: 6, y: 5 }, Color::Black, false);
-            result.sort();
-            let mut expected_result = vec![Position { x: 6, y: 4 }, Position { x: 6, y: 3 }];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+        fn after_e4_d5() {
+            let board = Board::from_fen("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR").unwrap();
+            assert_eq!(board.square_control(Position { x: 3, y: 4 }), 0);
+            assert_eq!(board.square_control(Position { x: 4, y: 3 }), -1);
         }
+    }
+
+    mod king_distance {
+        use super::*;
 
         #[test]
-        fn take_two_white() {
-            let board = Board::new();
-            let mut result = board.check_pawn(Position { x: 6, y: 5 }, Color::White, true);
-            result.sort();
-            let mut expected_result = vec![Position { x: 5, y: 6 }, Position { x: 7, y: 6 }];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+        fn start_position() {
+            assert_eq!(Board::new().king_distance(), Some(7));
         }
 
         #[test]
-        fn take_one_black() {
+        fn diagonal() {
+            let board = Board::from_fen("8/8/8/5k2/8/8/8/2K5").unwrap();
+            assert_eq!(board.king_distance(), Some(4));
+        }
+
+        #[test]
+        fn missing_king() {
             let mut board = Board::new();
-            board
-                .move_piece(Position { x: 2, y: 1 }, Position { x: 2, y: 3 })
-                .unwrap();
-            let mut result = board.check_pawn(Position { x: 3, y: 4 }, Color::Black, true);
-            result.sort();
-            let mut expected_result = vec![Position { x: 2, y: 3 }, Position { x: 3, y: 3 }];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+            board.take_piece(Position { x: 4, y: 0 }).unwrap();
+            assert_eq!(board.king_distance(), None);
         }
     }
 
-    mod check_knight {
+    mod has_opposition {
         use super::*;
 
         #[test]
-        fn no_edge() {
-            let board = Board::new();
-            let mut result = board.check_knight(Position { x: 3, y: 5 }, Color::White);
-            result.sort();
-            let mut expected_result = vec![
-                Position { x: 2, y: 7 },
-                Position { x: 4, y: 7 },
-                Position { x: 1, y: 6 },
-                Position { x: 5, y: 6 },
-                Position { x: 5, y: 4 },
-                Position { x: 1, y: 4 },
-                Position { x: 2, y: 3 },
-                Position { x: 4, y: 3 },
+        fn direct() {
+            let board = Board::from_fen("4k3/8/4K3/8/8/8/8/8").unwrap();
+            assert!(board.has_opposition(Color::White, Color::Black));
+            assert!(!board.has_opposition(Color::Black, Color::Black));
+            assert!(board.has_opposition(Color::Black, Color::White));
+            assert!(!board.has_opposition(Color::White, Color::White));
+        }
+
+        #[test]
+        fn distant() {
+            let board = Board::from_fen("4k3/8/8/8/4K3/8/8/8").unwrap();
+            assert!(board.has_opposition(Color::White, Color::Black));
+            let board = Board::from_fen("8/8/8/8/8/2k5/8/K7").unwrap();
+            assert!(board.has_opposition(Color::Black, Color::White));
+        }
+
+        #[test]
+        fn no_opposition() {
+            let board = Board::from_fen("4k3/8/8/4K3/8/8/8/8").unwrap();
+            assert!(!board.has_opposition(Color::White, Color::Black));
+            assert!(!board.has_opposition(Color::Black, Color::White));
+            let board = Board::from_fen("4k3/8/3K4/8/8/8/8/8").unwrap();
+            assert!(!board.has_opposition(Color::White, Color::Black));
+        }
+    }
+
+    mod capture_moves {
+        use super::*;
+
+        #[test]
+        fn matches_moves_from() {
+            let board =
+                Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+            for color in [Color::White, Color::Black] {
+                let mut expected_result: Vec<ChessMove> = board
+                    .pieces_of(color)
+                    .into_iter()
+                    .flat_map(|(position, _)| board.moves_from(position).unwrap())
+                    .filter(|chess_move| chess_move.taken_position().is_some())
+                    .collect();
+                let mut result = board.capture_moves(color);
+                assert!(!result.is_empty());
+                result.sort_by_key(|chess_move| {
+                    let movement = chess_move.movement();
+                    (movement.from_position, movement.to_position)
+                });
+                expected_result.sort_by_key(|chess_move| {
+                    let movement = chess_move.movement();
+                    (movement.from_position, movement.to_position)
+                });
+                assert_eq!(result, expected_result);
+            }
+        }
+
+        #[test]
+        fn promotion_with_take() {
+            let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3").unwrap();
+            let result = board.capture_moves(Color::White);
+            assert_eq!(result.len(), 4);
+            assert!(result
+                .iter()
+                .all(|chess_move| matches!(chess_move, ChessMove::PromoteWithTake(_, _, _))));
+        }
+    }
+
+    mod check_evasions {
+        use super::*;
+
+        /// Generates every legal move the slow way, by playing every pseudo legal move and keeping those that leave the king safe.
+        fn brute_force(
+            board: &Board,
+            color: Color,
+            en_passant: Option<Position>,
+        ) -> Vec<ChessMove> {
+            let mut moves = vec![];
+            for (position, _) in board.pieces_of(color) {
+                moves.extend(board.moves_from(position).unwrap());
+                moves.extend(en_passant.and_then(|target| board.en_passant_move(position, target)));
+            }
+            moves.retain(|chess_move| {
+                let mut after = board.clone();
+                after.execute_move(*chess_move).is_ok() && !after.is_in_check(color)
+            });
+            moves.sort_by_key(|chess_move| {
+                let movement = chess_move.movement();
+                (movement.from_position, movement.to_position)
+            });
+            moves
+        }
+
+        fn sorted_evasions(
+            board: &Board,
+            color: Color,
+            en_passant: Option<Position>,
+        ) -> Vec<ChessMove> {
+            let mut moves = board.check_evasions(color, en_passant);
+            moves.sort_by_key(|chess_move| {
+                let movement = chess_move.movement();
+                (movement.from_position, movement.to_position)
+            });
+            moves
+        }
+
+        #[test]
+        fn single_bishop_check() {
+            let board = Board::from_fen("4k3/8/8/8/Rb6/8/8/1N2K3").unwrap();
+            let moves = sorted_evasions(&board, Color::White, None);
+            assert_eq!(moves, brute_force(&board, Color::White, None));
+            let mut destinations: Vec<Position> = moves
+                .iter()
+                .map(|chess_move| chess_move.movement().to_position)
+                .collect();
+            destinations.sort();
+            let mut expected = vec![
+                // King steps aside
+                Position { x: 3, y: 0 },
+                Position { x: 5, y: 0 },
+                Position { x: 4, y: 1 },
+                Position { x: 5, y: 1 },
+                // Knight blocks
+                Position { x: 2, y: 2 },
+                Position { x: 3, y: 1 },
+                // Rook takes the bishop
+                Position { x: 1, y: 3 },
             ];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+            expected.sort();
+            assert_eq!(destinations, expected);
         }
 
         #[test]
-        fn near_edge() {
+        fn pinned_piece_cannot_block() {
+            // The knight on e2 could block on c3, but is pinned by the rook on e7
+            let board = Board::from_fen("7k/4r3/8/8/1b6/8/4N3/4K3").unwrap();
+            let moves = sorted_evasions(&board, Color::White, None);
+            assert_eq!(moves, brute_force(&board, Color::White, None));
+            assert!(moves
+                .iter()
+                .all(|chess_move| chess_move.movement().from_position == Position { x: 4, y: 0 }));
+        }
+
+        #[test]
+        fn double_check() {
+            let board = Board::from_fen("4k3/8/8/8/1b6/8/4r3/R3K3").unwrap();
+            let moves = sorted_evasions(&board, Color::White, None);
+            assert_eq!(moves, brute_force(&board, Color::White, None));
+            assert!(moves
+                .iter()
+                .all(|chess_move| chess_move.movement().from_position == Position { x: 4, y: 0 }));
+        }
+
+        #[test]
+        fn en_passant_takes_checker() {
+            // Black has just played d7-d5, checking the king on e4
+            let board = Board::from_fen("4k3/8/8/3pP3/4K3/8/8/8").unwrap();
+            let en_passant = Some(Position { x: 3, y: 5 });
+            let moves = sorted_evasions(&board, Color::White, en_passant);
+            assert_eq!(moves, brute_force(&board, Color::White, en_passant));
+            assert!(moves.iter().any(|chess_move| {
+                *chess_move
+                    == ChessMove::MoveWithTake(
+                        action::Move {
+                            from_position: Position { x: 4, y: 4 },
+                            to_position: Position { x: 3, y: 5 },
+                        },
+                        action::Take {
+                            position: Position { x: 3, y: 4 },
+                        },
+                    )
+            }));
+        }
+
+        #[test]
+        fn not_in_check() {
+            let board =
+                Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R").unwrap();
+            assert!(board.check_evasions(Color::White, None).is_empty());
+            assert!(board.check_evasions(Color::Black, None).is_empty());
+        }
+    }
+
+    mod antichess_legal_moves {
+        use super::*;
+
+        #[test]
+        fn capture_is_forced() {
+            let board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3").unwrap();
+            let moves = board.antichess_legal_moves(Color::White, None);
+            assert_eq!(moves.len(), 1);
+            assert_eq!(moves[0].taken_position(), Some(Position { x: 3, y: 4 }));
+        }
+
+        #[test]
+        fn every_capture_is_allowed() {
+            // Both the pawn and the knight can take, and either may be chosen
+            let board = Board::from_fen("4k3/8/8/3p4/4P3/2N5/8/4K3").unwrap();
+            assert_eq!(board.antichess_legal_moves(Color::White, None).len(), 2);
+        }
+
+        #[test]
+        fn quiet_moves_without_captures() {
             let board = Board::new();
-            let mut result = board.check_knight(Position { x: 0, y: 5 }, Color::Black);
-            result.sort();
-            let mut expected_result = vec![Position { x: 2, y: 4 }, Position { x: 1, y: 3 }];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+            assert_eq!(board.antichess_legal_moves(Color::White, None).len(), 20);
+        }
+
+        #[test]
+        fn king_ignores_check() {
+            let board = Board::from_fen("4k3/8/8/8/8/8/r7/4K3").unwrap();
+            // Every king move is allowed, including staying on the rook's rank
+            assert_eq!(board.antichess_legal_moves(Color::White, None).len(), 5);
+        }
+
+        #[test]
+        fn en_passant_is_forced() {
+            let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3").unwrap();
+            let moves = board.antichess_legal_moves(Color::White, Some(Position { x: 3, y: 5 }));
+            assert_eq!(moves.len(), 1);
+            assert_eq!(moves[0].taken_position(), Some(Position { x: 3, y: 4 }));
+        }
+
+        #[test]
+        fn promote_to_king() {
+            let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3").unwrap();
+            let moves = board.antichess_legal_moves(Color::White, None);
+            assert!(moves.contains(&ChessMove::Promote(
+                action::Move {
+                    from_position: Position { x: 1, y: 6 },
+                    to_position: Position { x: 1, y: 7 },
+                },
+                action::Promote {
+                    position: Position { x: 1, y: 7 },
+                    piece_type: PieceType::King,
+                },
+            )));
+            // Five promotions and the five king moves
+            assert_eq!(moves.len(), 10);
         }
     }
 
-    mod check_king {
+    mod moves_from_promoting {
         use super::*;
 
         #[test]
-        fn near_friendlies() {
+        fn only_given_pieces() {
+            let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3").unwrap();
+            let position = Position { x: 1, y: 6 };
+            let moves = board
+                .moves_from_promoting(position, &[PieceType::Queen])
+                .unwrap();
+            assert_eq!(moves.len(), 1);
+            assert!(board
+                .moves_from_promoting(position, &[])
+                .unwrap()
+                .is_empty());
+            assert_eq!(
+                board
+                    .moves_from_promoting(position, Variant::Standard.promotion_candidates())
+                    .unwrap(),
+                board.moves_from(position).unwrap()
+            );
+        }
+    }
+
+    mod moves_from_sorted {
+        use super::*;
+
+        fn destinations(moves: &[ChessMove]) -> Vec<Position> {
+            moves
+                .iter()
+                .map(|chess_move| chess_move.movement().to_position)
+                .collect()
+        }
+
+        #[test]
+        fn ordered_by_destination() {
+            let board = Board::from_fen("4k3/8/8/8/3Q4/8/8/4K3").unwrap();
+            let moves = board.moves_from_sorted(Position { x: 3, y: 3 }).unwrap();
+            let mut expected = destinations(&moves);
+            expected.sort_by_key(|position| (position.y, position.x));
+            assert_eq!(destinations(&moves), expected);
+            assert_eq!(
+                board.moves_from(Position { x: 3, y: 3 }).unwrap().len(),
+                moves.len()
+            );
+        }
+
+        #[test]
+        fn promotions_ordered_by_piece() {
+            let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3").unwrap();
+            let moves = board.moves_from_sorted(Position { x: 0, y: 6 }).unwrap();
+            let promotions: Vec<(Position, PieceType)> = moves
+                .iter()
+                .map(|chess_move| match chess_move {
+                    ChessMove::Promote(_, promote) | ChessMove::PromoteWithTake(_, _, promote) => {
+                        (promote.position, promote.piece_type)
+                    }
+                    _ => panic!("Expected a promotion, got {chess_move:?}"),
+                })
+                .collect();
+            let mut expected = vec![];
+            for x in [0, 1] {
+                for piece_type in [
+                    PieceType::Knight,
+                    PieceType::Bishop,
+                    PieceType::Rook,
+                    PieceType::Queen,
+                ] {
+                    expected.push((Position { x, y: 7 }, piece_type));
+                }
+            }
+            assert_eq!(promotions, expected);
+        }
+
+        #[test]
+        fn no_piece() {
             let board = Board::new();
-            let mut result = board.check_king(Position { x: 3, y: 5 }, Color::Black);
-            result.sort();
-            let mut expected_result = vec![
-                Position { x: 2, y: 5 },
-                Position { x: 2, y: 4 },
-                Position { x: 3, y: 4 },
-                Position { x: 4, y: 4 },
-                Position { x: 4, y: 5 },
-            ];
-            expected_result.sort();
-            assert_eq!(result, expected_result);
+            assert!(board.moves_from_sorted(Position { x: 3, y: 3 }).is_err());
         }
     }
 
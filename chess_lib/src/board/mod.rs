@@ -1,5 +1,6 @@
 pub mod layout;
 pub mod mailbox;
+pub mod piece_square;
 
 
 use crate::{error::{OffsetOutOfBounds, PieceError, PositionOutOfBounds}, piece::{PieceType}};
@@ -37,6 +38,146 @@ impl Position {
             Err(PositionOutOfBounds(x.into(), y.into()))
         }
     }
+
+    /// Returns the horizontal coordinate (file) of the position.
+    #[must_use]
+    pub fn x(self) -> u8 {
+        self.x
+    }
+
+    /// Returns the vertical coordinate (rank) of the position.
+    #[must_use]
+    pub fn y(self) -> u8 {
+        self.y
+    }
+
+    /// Returns the position reflected vertically, so the same square from the other color's side of the board.
+    ///
+    /// Used to look up tables written from white's point of view for black pieces.
+    ///
+    /// ```
+    /// use chess_lib::board::Position;
+    ///
+    /// assert_eq!(Position::new(0, 0).unwrap().mirror_rank(), Position::new(0, 7).unwrap());
+    /// assert_eq!(Position::new(4, 3).unwrap().mirror_rank(), Position::new(4, 4).unwrap());
+    /// ```
+    #[must_use]
+    pub fn mirror_rank(self) -> Self {
+        Self {
+            x: self.x,
+            y: 7 - self.y,
+        }
+    }
+
+    /// Returns whether the position is a light square. A1 is a dark square.
+    ///
+    /// ```
+    /// use chess_lib::board::Position;
+    ///
+    /// assert!(!Position::new(0, 0).unwrap().is_light());
+    /// assert!(Position::new(7, 0).unwrap().is_light());
+    /// ```
+    #[must_use]
+    pub fn is_light(self) -> bool {
+        (self.x + self.y) % 2 == 1
+    }
+
+    /// Returns whether this position and `other` are both light squares or both dark squares.
+    ///
+    /// Bishops on squares of the same color can never attack the same squares, which matters when deciding whether there is enough material to mate.
+    ///
+    /// ```
+    /// use chess_lib::board::Position;
+    ///
+    /// let c1 = Position::new(2, 0).unwrap();
+    /// assert!(c1.same_color_as(Position::new(5, 7).unwrap()));
+    /// assert!(!c1.same_color_as(Position::new(5, 0).unwrap()));
+    /// ```
+    #[must_use]
+    pub fn same_color_as(self, other: Self) -> bool {
+        self.is_light() == other.is_light()
+    }
+
+    /// Returns the number of king moves needed to get from this position to `other`.
+    ///
+    /// This is the larger of the horizontal and vertical distances, also known as the Chebyshev distance.
+    ///
+    /// ```
+    /// use chess_lib::board::Position;
+    ///
+    /// let e1 = Position::new(4, 0).unwrap();
+    /// assert_eq!(e1.distance(Position::new(4, 7).unwrap()), 7);
+    /// assert_eq!(e1.distance(Position::new(6, 2).unwrap()), 2);
+    /// ```
+    #[must_use]
+    pub fn distance(self, other: Self) -> u8 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Returns the offset from this position to `other`, so that adding it to this position gives `other`.
+    ///
+    /// # Parameters
+    /// * `other`: The position the offset points to.
+    ///
+    /// # Errors
+    /// * Will return [`OffsetOutOfBounds`] error if the offset is too large, which cannot happen for two positions on the board.
+    ///
+    /// ```
+    /// use chess_lib::board::{Offset, Position};
+    ///
+    /// let e2 = Position::new(4, 1).unwrap();
+    /// let c3 = Position::new(2, 2).unwrap();
+    /// let offset = e2.offset_to(c3).unwrap();
+    /// assert_eq!(offset, Offset::new(-2, 1).unwrap());
+    /// assert_eq!((e2 + offset).unwrap(), c3);
+    /// ```
+    pub fn offset_to(self, other: Self) -> Result<Offset, OffsetOutOfBounds> {
+        let (x, y) = unsafe {
+            (
+                i8::try_from(other.x).unwrap_unchecked() - i8::try_from(self.x).unwrap_unchecked(),
+                i8::try_from(other.y).unwrap_unchecked() - i8::try_from(self.y).unwrap_unchecked(),
+            )
+        }; // This is okay since x and y must always be less than 8
+        Offset::new(x, y)
+    }
+
+    /// Returns the smallest number of knight moves needed to get from this position to `other`.
+    ///
+    /// Found by spreading out from this position one knight move at a time until `other` is reached, so accounts for the edges of the board, e.g. a1 to b2 takes 4 moves.
+    ///
+    /// ```
+    /// use chess_lib::board::Position;
+    ///
+    /// let a1 = Position::new(0, 0).unwrap();
+    /// assert_eq!(a1.knight_distance(Position::new(1, 2).unwrap()), 1);
+    /// assert_eq!(a1.knight_distance(Position::new(1, 1).unwrap()), 4);
+    /// assert_eq!(a1.knight_distance(Position::new(7, 7).unwrap()), 6);
+    /// ```
+    #[must_use]
+    pub fn knight_distance(self, other: Self) -> u8 {
+        let bit = |position: Self| 1u64 << (position.y * 8 + position.x);
+        // A knight can reach every square on the board, so the loop always ends
+        let mut reached = bit(self);
+        let mut frontier = vec![self];
+        let mut distance = 0;
+        while reached & bit(other) == 0 {
+            frontier = frontier
+                .into_iter()
+                .flat_map(|position| {
+                    mailbox::KNIGHT_OFFSETS
+                        .into_iter()
+                        .filter_map(move |offset| (position + offset).ok())
+                })
+                .filter(|&next| {
+                    let unseen = reached & bit(next) == 0;
+                    reached |= bit(next);
+                    unseen
+                })
+                .collect();
+            distance += 1;
+        }
+        distance
+    }
 }
 
 impl Display for Position {
@@ -48,8 +189,8 @@ impl Display for Position {
 /// Offset to a position on a chess board. Can be added to position.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub struct Offset {
-    x: i8,
-    y: i8,
+    pub(crate) x: i8,
+    pub(crate) y: i8,
 }
 
 impl Offset {
@@ -123,22 +264,176 @@ pub enum Direction {
     NW,
 }
 
+impl Direction {
+    /// Every direction, clockwise from north, in the order of [`Direction::index`].
+    pub const ALL: [Direction; 8] = [
+        Direction::N,
+        Direction::NE,
+        Direction::E,
+        Direction::SE,
+        Direction::S,
+        Direction::SW,
+        Direction::W,
+        Direction::NW,
+    ];
+
+    /// Returns the direction with the given index, or `None` if `index` is not less than 8, see [`Direction::index`].
+    ///
+    /// # Parameters
+    /// * `index`: The index of the direction, counting clockwise from north at 0.
+    ///
+    /// ```
+    /// use chess_lib::board::Direction;
+    ///
+    /// assert_eq!(Direction::from_index(2), Some(Direction::E));
+    /// assert_eq!(Direction::from_index(8), None);
+    /// ```
+    #[must_use]
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(index)).copied()
+    }
+
+    /// Returns the index of the direction, counting clockwise from north at 0.
+    ///
+    /// Useful for arrays with an entry for each direction, e.g. tables of rays.
+    ///
+    /// ```
+    /// use chess_lib::board::Direction;
+    ///
+    /// assert_eq!(Direction::N.index(), 0);
+    /// assert_eq!(Direction::NW.index(), 7);
+    /// ```
+    #[must_use]
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the offset of a single step in this direction.
+    ///
+    /// ```
+    /// use chess_lib::board::{Direction, Offset};
+    ///
+    /// assert_eq!(Direction::NE.offset(), Offset::new(1, 1).unwrap());
+    /// ```
+    #[must_use]
+    pub fn offset(self) -> Offset {
+        match self {
+            Direction::N => Offset { x: 0, y: 1 },
+            Direction::NE => Offset { x: 1, y: 1 },
+            Direction::E => Offset { x: 1, y: 0 },
+            Direction::SE => Offset { x: 1, y: -1 },
+            Direction::S => Offset { x: 0, y: -1 },
+            Direction::SW => Offset { x: -1, y: -1 },
+            Direction::W => Offset { x: -1, y: 0 },
+            Direction::NW => Offset { x: -1, y: 1 },
+        }
+    }
+
+    /// Returns the direction pointing the opposite way.
+    ///
+    /// ```
+    /// use chess_lib::board::Direction;
+    ///
+    /// assert_eq!(Direction::NE.opposite(), Direction::SW);
+    /// ```
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        match self {
+            Direction::N => Direction::S,
+            Direction::NE => Direction::SW,
+            Direction::E => Direction::W,
+            Direction::SE => Direction::NW,
+            Direction::S => Direction::N,
+            Direction::SW => Direction::NE,
+            Direction::W => Direction::E,
+            Direction::NW => Direction::SE,
+        }
+    }
+}
+
+/// Primitive actions that make up a [`ChessMove`].
 pub mod action {
     use super::Position;
-    use crate::piece::PieceType;
+    use crate::piece::{Color, PieceType};
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
     pub struct Move {pub from_position: Position, pub to_position: Position}
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
     pub struct Take {pub position: Position}
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
     pub struct Promote {pub position: Position, pub piece_type: PieceType}
+    #[derive(PartialEq, Eq, Clone, Copy, Debug)]
+    pub struct Place {pub position: Position, pub color: Color, pub piece_type: PieceType}
 }
 
+/// A move in a game of chess, made up of one or more primitive actions.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum ChessMove {
     Move(action::Move),
     MoveWithTake(action::Move, action::Take),
     Castle(action::Move, action::Move),
-    Promote(action::Move, action::Promote)
+    Promote(action::Move, action::Promote),
+    PromoteWithTake(action::Move, action::Take, action::Promote),
+    /// Places a piece from the player's hand onto an empty square, see [`Variant::Crazyhouse`](crate::variant::Variant::Crazyhouse).
+    Drop(action::Place),
+}
+
+impl ChessMove {
+    /// Returns the main movement of the move.
+    ///
+    /// For castling this is the movement of the king. A dropped piece does not come from another square, so both positions are the square it is dropped on.
+    #[must_use]
+    pub fn movement(&self) -> action::Move {
+        match self {
+            ChessMove::Move(movement)
+            | ChessMove::MoveWithTake(movement, _)
+            | ChessMove::Castle(movement, _)
+            | ChessMove::Promote(movement, _)
+            | ChessMove::PromoteWithTake(movement, _, _) => *movement,
+            ChessMove::Drop(place) => action::Move {
+                from_position: place.position,
+                to_position: place.position,
+            },
+        }
+    }
+
+    /// Returns the type of the piece making the move on `board`, or `None` if there is no piece at the start of the move.
+    ///
+    /// For castling this is the king.
+    ///
+    /// # Parameters
+    /// * `board`: The board before the move is played.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action, mailbox::*}, piece::*};
+    ///
+    /// let b = Board::new();
+    /// let nf3 = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(6, 0).unwrap(),
+    ///     to_position: Position::new(5, 2).unwrap(),
+    /// });
+    /// assert_eq!(nf3.moving_piece(&b), Some(PieceType::Knight));
+    /// ```
+    #[must_use]
+    pub fn moving_piece(&self, board: &mailbox::Board) -> Option<PieceType> {
+        if let ChessMove::Drop(place) = self {
+            return Some(place.piece_type);
+        }
+        board[self.movement().from_position].map(|piece| piece.piece_type)
+    }
+
+    /// Returns the position of the piece taken by the move, if any.
+    #[must_use]
+    pub fn taken_position(&self) -> Option<Position> {
+        match self {
+            ChessMove::MoveWithTake(_, take) | ChessMove::PromoteWithTake(_, take, _) => {
+                Some(take.position)
+            }
+            _ => None,
+        }
+    }
 }
 
-pub trait ExecuteMove: MovePiece + TakePiece + PromotePiece {
+pub trait ExecuteMove: MovePiece + TakePiece + PromotePiece + PlacePiece {
     /// Execute a chess move on the board.
     /// 
     /// Will not check that the move is legal.
@@ -147,7 +442,7 @@ pub trait ExecuteMove: MovePiece + TakePiece + PromotePiece {
     /// # Errors
     /// * Returns [`PieceError::NotFound`] if move attempts to move, take or promote a piece that does not exist.
     /// * Returns [`PieceError::Occupied`] if move attempts to move piece to a square that is already occupied.
-    fn execute_move(&self, chess_move: ChessMove) -> Result<(), PieceError>;
+    fn execute_move(&mut self, chess_move: ChessMove) -> Result<(), PieceError>;
 }
 
 
@@ -162,7 +457,7 @@ pub trait MovePiece {
     /// # Errors
     /// * Returns [`PieceError::NotFound`] if there is no piece at `from_position`.
     /// * Returns [`PieceError::Occupied`] if there is already a piece at `to_position`.
-    fn move_piece(&self, from_position: Position, to_position: Position) -> Result<(), PieceError>;
+    fn move_piece(&mut self, from_position: Position, to_position: Position) -> Result<(), PieceError>;
 }
 
 pub trait TakePiece {
@@ -172,7 +467,7 @@ pub trait TakePiece {
     /// * `position`: The position of the piece.
     /// # Errors
     /// * Returns [`PieceError::NotFound`] if there is no piece at `position`.
-    fn take_piece(&self, position: Position) -> Result<(), PieceError>;
+    fn take_piece(&mut self, position: Position) -> Result<(), PieceError>;
 }
 
 pub trait PromotePiece {
@@ -183,7 +478,18 @@ pub trait PromotePiece {
     /// * `position`: The position of the piece.
     /// # Errors
     /// * Returns [`PieceError::NotFound`] if there is no piece at `position`.
-    fn promote_piece(&self, position: Position, piece_type: PieceType) -> Result<(), PieceError>;
+    fn promote_piece(&mut self, position: Position, piece_type: PieceType) -> Result<(), PieceError>;
+}
+
+pub trait PlacePiece {
+    /// Place a new piece on the board, e.g. a piece dropped from the hand in crazyhouse.
+    /// 
+    /// Does not check that the drop is legal.
+    /// # Parameters
+    /// * `place`: The piece to place and where to place it.
+    /// # Errors
+    /// * Returns [`PieceError::Occupied`] if there is already a piece at the position.
+    fn place_piece(&mut self, place: action::Place) -> Result<(), PieceError>;
 }
 
 pub trait PseudoLegalMoves {
@@ -204,4 +510,123 @@ pub trait LegalMoves {
     /// # Errors
     /// * Returns [`PieceError::NotFound`] if there is no piece at `position`.
     fn legal_moves(&self, position: Position) -> Result<HashSet<ChessMove>, PieceError>;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod position_tests {
+    use super::*;
+
+    fn all_positions() -> impl Iterator<Item = Position> {
+        (0..8).flat_map(|y| (0..8).map(move |x| Position { x, y }))
+    }
+
+    mod mirror_rank {
+        use super::*;
+
+        #[test]
+        fn a1_to_a8() {
+            assert_eq!(Position { x: 0, y: 0 }.mirror_rank(), Position { x: 0, y: 7 });
+        }
+
+        #[test]
+        fn involution() {
+            for position in all_positions() {
+                assert_eq!(position.mirror_rank().mirror_rank(), position);
+                assert_eq!(position.mirror_rank().x, position.x);
+            }
+        }
+    }
+
+    mod same_color_as {
+        use super::*;
+
+        #[test]
+        fn dark_square_bishops() {
+            // The bishops on c1 and f8 both start on dark squares
+            let c1 = Position { x: 2, y: 0 };
+            let f8 = Position { x: 5, y: 7 };
+            assert!(!c1.is_light());
+            assert!(c1.same_color_as(f8));
+            assert!(f8.same_color_as(c1));
+        }
+
+        #[test]
+        fn neighbours_differ() {
+            for position in all_positions() {
+                assert!(position.same_color_as(position));
+                if let Ok(right) = position + (Offset { x: 1, y: 0 }) {
+                    assert!(!position.same_color_as(right));
+                }
+            }
+        }
+    }
+
+    mod knight_distance {
+        use super::*;
+
+        #[test]
+        fn symmetric() {
+            for a in all_positions() {
+                for b in all_positions() {
+                    assert_eq!(a.knight_distance(b), b.knight_distance(a));
+                }
+            }
+        }
+
+        #[test]
+        fn same_square() {
+            assert!(all_positions().all(|position| position.knight_distance(position) == 0));
+        }
+
+        #[test]
+        fn single_move() {
+            let d4 = Position { x: 3, y: 3 };
+            for position in all_positions() {
+                let dx = position.x.abs_diff(d4.x);
+                let dy = position.y.abs_diff(d4.y);
+                let is_knight_move = (dx, dy) == (1, 2) || (dx, dy) == (2, 1);
+                assert_eq!(d4.knight_distance(position) == 1, is_knight_move);
+            }
+        }
+
+        #[test]
+        fn corners() {
+            let a1 = Position { x: 0, y: 0 };
+            assert_eq!(a1.knight_distance(Position { x: 1, y: 1 }), 4);
+            assert_eq!(a1.knight_distance(Position { x: 7, y: 7 }), 6);
+            assert_eq!(
+                all_positions().map(|position| a1.knight_distance(position)).max(),
+                Some(6)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod direction_tests {
+    use super::*;
+
+    mod index {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            for (index, direction) in (0..).zip(Direction::ALL) {
+                assert_eq!(direction.index(), index);
+                assert_eq!(Direction::from_index(index), Some(direction));
+            }
+        }
+
+        #[test]
+        fn out_of_range() {
+            assert!((8..=u8::MAX).all(|index| Direction::from_index(index).is_none()));
+        }
+
+        #[test]
+        fn opposite_is_half_turn() {
+            for direction in Direction::ALL {
+                assert_eq!((direction.index() + 4) % 8, direction.opposite().index());
+            }
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use crate::{board::Position, piece::PieceType};
+use crate::{
+    board::Position,
+    piece::{Color, PieceType},
+};
 use thiserror::Error;
 /// Error if a position where no piece is present is passed into a function that requires it.
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
 #[allow(clippy::module_name_repetitions)]
 pub enum PieceError {
     #[error("No piece found at {0}.")]
@@ -19,3 +22,112 @@ pub struct PositionOutOfBounds(pub isize, pub isize);
 #[derive(Error, Debug)]
 #[error("Attempted to create offset of {0}, {1}. Position x and y must both be less than 8 and more than -8")]
 pub struct OffsetOutOfBounds(pub i8, pub i8);
+
+/// Error if a board is in a state that could not occur in a game of chess.
+#[derive(Error, Debug, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum BoardError {
+    #[error("{0:?} has {1} kings, expected exactly one.")]
+    KingCount(Color, usize),
+    #[error("{0:?} has {1} pieces, expected at most 16.")]
+    PieceCount(Color, usize),
+    #[error("Pawn found on the first or last rank at {0}.")]
+    PawnOnBackRank(Position),
+}
+
+/// Error if a FEN string cannot be parsed into a valid position or game state.
+#[derive(Error, Debug, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum FenError {
+    #[error("Expected 8 ranks, found {0}.")]
+    RankCount(usize),
+    #[error("Rank {0} does not describe exactly 8 squares.")]
+    FileCount(u8),
+    #[error("Invalid piece character '{0}'.")]
+    InvalidPiece(char),
+    #[error("Illegal position: {0}")]
+    IllegalPosition(#[from] BoardError),
+    #[error("Expected between 4 and 6 fields, found {0}.")]
+    FieldCount(usize),
+    #[error("Invalid side to move '{0}'.")]
+    InvalidTurn(String),
+    #[error("Invalid castling rights '{0}'.")]
+    InvalidCastling(String),
+    #[error("Invalid en passant square '{0}'.")]
+    InvalidEnPassant(String),
+    #[error("Invalid move clock '{0}'.")]
+    InvalidClock(String),
+}
+
+/// Reason a move cannot be played in the current position.
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IllegalMoveReason {
+    #[error("No piece found at {0}.")]
+    NoPiece(Position),
+    #[error("Piece at {0} does not belong to the side to move.")]
+    NotYourPiece(Position),
+    #[error("The piece cannot make that move.")]
+    NotAMoveForThisPiece,
+    #[error("Another piece is in the way.")]
+    PathBlocked,
+    #[error("The right to castle on that side has been lost.")]
+    NoCastlingRights,
+    #[error("Cannot castle while in check.")]
+    CastleOutOfCheck,
+    #[error("Cannot castle through or into an attacked square.")]
+    CastleThroughCheck,
+    #[error("Move would leave the king in check.")]
+    LeavesKingInCheck,
+    #[error("A piece can be taken, so a capture must be played.")]
+    CaptureRequired,
+    #[error("No {0:?} in hand to drop.")]
+    NotInHand(PieceType),
+}
+
+/// Error if a draw is claimed when the rules do not allow it.
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ClaimError {
+    #[error("Neither threefold repetition nor the fifty move rule applies.")]
+    NotClaimable,
+    #[error("The game is already over.")]
+    GameOver,
+}
+
+/// Error if a move cannot be played.
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+#[allow(clippy::module_name_repetitions)]
+pub enum MoveError {
+    #[error("Illegal move: {0}")]
+    Illegal(#[from] IllegalMoveReason),
+    #[error("The game is already over.")]
+    GameOver,
+    #[error("Move could not be played: {0}")]
+    Piece(#[from] PieceError),
+}
+
+/// Error if a game cannot be ended by resignation or agreement.
+#[derive(Error, Debug, PartialEq, Eq, Clone, Copy)]
+#[allow(clippy::module_name_repetitions)]
+pub enum GameEndError {
+    #[error("{0} has no draw offer to accept.")]
+    NoDrawOffer(Color),
+    #[error("The game is already over.")]
+    GameOver,
+}
+
+/// Error if an EPD line cannot be parsed.
+#[derive(Error, Debug, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub enum EpdError {
+    #[error("Expected at least 4 fields, found {0}.")]
+    FieldCount(usize),
+    #[error("Invalid piece placement: {0}")]
+    Placement(#[from] FenError),
+    #[error("Invalid side to move '{0}'.")]
+    InvalidTurn(String),
+    #[error("Invalid castling rights '{0}'.")]
+    InvalidCastling(String),
+    #[error("Invalid en passant square '{0}'.")]
+    InvalidEnPassant(String),
+}
@@ -0,0 +1,3663 @@
+use crate::board::{action, mailbox::Board, ChessMove, ExecuteMove, Offset, Position};
+use crate::error::{ClaimError, FenError, GameEndError, IllegalMoveReason, MoveError, PieceError};
+use crate::notation;
+use crate::piece::{Color, Piece, PieceType};
+use crate::search::{self, INFINITY};
+use crate::variant::Variant;
+use crate::zobrist;
+use log::{debug, info};
+use std::collections::HashMap;
+
+/// Which sides each color is still allowed to castle on.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
+
+impl CastlingRights {
+    /// Creates castling rights where both colors can castle on both sides.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            white_kingside: true,
+            white_queenside: true,
+            black_kingside: true,
+            black_queenside: true,
+        }
+    }
+
+    /// Returns whether `color` can castle kingside.
+    #[must_use]
+    pub fn kingside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_kingside,
+            Color::Black => self.black_kingside,
+        }
+    }
+
+    /// Returns whether `color` can castle queenside.
+    #[must_use]
+    pub fn queenside(&self, color: Color) -> bool {
+        match color {
+            Color::White => self.white_queenside,
+            Color::Black => self.black_queenside,
+        }
+    }
+
+    /// Removes any rights that depend on a king or rook starting at `position`.
+    ///
+    /// Called with every square a move touches, since moving a king or rook or taking a rook on its starting square loses the right.
+    fn remove_for(&mut self, position: Position) {
+        match (position.x(), position.y()) {
+            (4, 0) => {
+                self.white_kingside = false;
+                self.white_queenside = false;
+            }
+            (4, 7) => {
+                self.black_kingside = false;
+                self.black_queenside = false;
+            }
+            (7, 0) => self.white_kingside = false,
+            (0, 0) => self.white_queenside = false,
+            (7, 7) => self.black_kingside = false,
+            (0, 7) => self.black_queenside = false,
+            _ => {}
+        }
+    }
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Why a game ended in a draw.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DrawReason {
+    Stalemate,
+    /// The same position occurred five times, ending the game automatically.
+    FivefoldRepetition,
+    /// 75 moves by each player without a capture or pawn move, ending the game automatically.
+    SeventyFiveMoveRule,
+    /// The same position occurred three times and a player claimed the draw.
+    ThreefoldRepetition,
+    /// 50 moves by each player without a capture or pawn move and a player claimed the draw.
+    FiftyMoveRule,
+    /// The players agreed to a draw.
+    Agreement,
+}
+
+/// Result of a finished game.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GameResult {
+    /// The color that delivered checkmate wins.
+    Checkmate(Color),
+    /// The color that did not resign wins.
+    Resignation(Color),
+    /// The color that exploded the enemy king wins, see [`Variant::Atomic`].
+    KingExploded(Color),
+    /// The color that lost all its pieces or had no moves left wins, see [`Variant::Antichess`].
+    GivenAway(Color),
+    /// The color that gave check three times wins, see [`Variant::ThreeCheck`].
+    ThreeChecks(Color),
+    /// The color whose king reached the center wins, see [`Variant::KingOfTheHill`].
+    HillReached(Color),
+    Draw(DrawReason),
+}
+
+/// How a game ends when the side to move has no legal moves.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GameEnd {
+    /// The side to move is in check, so loses.
+    Checkmate,
+    /// The side to move is not in check, so the game is drawn.
+    Stalemate,
+}
+
+/// Whether a game is still being played.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GameStatus {
+    InProgress,
+    Finished(GameResult),
+}
+
+/// Everything needed to restore a [`GameState`] to before a move was played.
+///
+/// Only the squares the move changed are kept rather than the whole board, so playing a move does not need to clone the board.
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct UndoInfo {
+    chess_move: ChessMove,
+    /// The squares the move changed along with what was on them before, see [`GameState::touched_squares`].
+    squares: [(Position, Option<Piece>); 4],
+    castling_rights: CastlingRights,
+    en_passant: Option<Position>,
+    halfmove_clock: u32,
+    /// Pieces removed by an explosion in atomic chess, which may be on squares the move did not touch.
+    exploded: Vec<(Position, Piece)>,
+    /// The piece type the move added to the mover's hand in crazyhouse, if it took a piece.
+    to_hand: Option<PieceType>,
+    /// Bitboard of the squares holding promoted pieces before the move, so crazyhouse captures hand back pawns correctly after undoing.
+    promoted: u64,
+    checks_given: [u8; 2],
+}
+
+/// State of a game of chess in progress.
+///
+/// Keeps track of the board, whose turn it is, castling rights and en passant, along with the moves that have been played so that they can be undone.
+///
+/// ```
+/// use chess_lib::{game::GameState, piece::Color};
+///
+/// let state = GameState::new();
+/// assert_eq!(state.turn(), Color::White);
+/// assert_eq!(state.fullmove_number(), 1);
+/// assert_eq!(state.ply_count(), 0);
+/// assert_eq!(state.all_legal_moves().len(), 20);
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct GameState {
+    board: Board,
+    turn: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Position>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    history: Vec<UndoInfo>,
+    hash: u64,
+    /// Zobrist hash of every position reached, ending with the current one, for detecting repetition.
+    keys: Vec<u64>,
+    draw_claim: Option<DrawReason>,
+    variant: Variant,
+    /// Pieces each color has captured and can drop in crazyhouse. Piece types with none in hand are left out.
+    hand: HashMap<(Color, PieceType), u8>,
+    /// Bitboard of the squares holding pieces that were promoted from pawns.
+    promoted: u64,
+    /// Number of times white and black have given check in three-check.
+    checks_given: [u8; 2],
+}
+
+impl GameState {
+    /// Creates a game state with a standard board layout and white to move.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_board(Board::new(), Color::White)
+    }
+
+    /// Creates a game state from an existing board with `turn` to move.
+    ///
+    /// Castling rights are given for every king and rook that have not moved from their starting squares, and no en passant capture is available.
+    ///
+    /// # Parameters
+    /// * `board`: The board to start from.
+    /// * `turn`: The color to move first.
+    #[must_use]
+    pub fn from_board(board: Board, turn: Color) -> Self {
+        let unmoved = |x, y, piece_type| {
+            Position::new(x, y)
+                .ok()
+                .and_then(|position| board[position])
+                .is_some_and(|piece| piece.piece_type == piece_type && !piece.moved)
+        };
+        let castling_rights = CastlingRights {
+            white_kingside: unmoved(4, 0, PieceType::King) && unmoved(7, 0, PieceType::Rook),
+            white_queenside: unmoved(4, 0, PieceType::King) && unmoved(0, 0, PieceType::Rook),
+            black_kingside: unmoved(4, 7, PieceType::King) && unmoved(7, 7, PieceType::Rook),
+            black_queenside: unmoved(4, 7, PieceType::King) && unmoved(0, 7, PieceType::Rook),
+        };
+        Self::from_parts(board, turn, castling_rights, None)
+    }
+
+    /// Creates a game state from a full FEN string, e.g. `rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1`.
+    ///
+    /// The fields are the piece placement, side to move, castling rights, en passant target square, halfmove clock and fullmove number.
+    /// The two clocks may be left off, in which case they start at 0 and 1. No moves have been played, so none can be undone.
+    ///
+    /// # Parameters
+    /// * `fen`: The FEN string to parse.
+    ///
+    /// # Errors
+    /// * Returns [`FenError::FieldCount`] if there are not between four and six fields.
+    /// * Returns [`FenError::InvalidTurn`], [`FenError::InvalidCastling`], [`FenError::InvalidEnPassant`] or [`FenError::InvalidClock`] if a field is invalid.
+    /// * Returns any error from [`Board::from_fen`] if the piece placement is invalid.
+    ///
+    /// ```
+    /// use chess_lib::{game::GameState, piece::Color};
+    ///
+    /// let state = GameState::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+    /// assert_eq!(state.turn(), Color::Black);
+    /// assert_eq!(state.fullmove_number(), 1);
+    /// assert!(GameState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR").is_err());
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let &[placement, turn, castling, en_passant, ref clocks @ ..] = fields.as_slice() else {
+            return Err(FenError::FieldCount(fields.len()));
+        };
+        if clocks.len() > 2 {
+            return Err(FenError::FieldCount(fields.len()));
+        }
+        let board = Board::from_fen(placement)?;
+        let turn =
+            notation::parse_turn(turn).ok_or_else(|| FenError::InvalidTurn(turn.to_string()))?;
+        let castling_rights = notation::parse_castling(castling)
+            .ok_or_else(|| FenError::InvalidCastling(castling.to_string()))?;
+        let en_passant = if en_passant == "-" {
+            None
+        } else {
+            Some(
+                notation::parse_square(en_passant)
+                    .ok_or_else(|| FenError::InvalidEnPassant(en_passant.to_string()))?,
+            )
+        };
+        let parse_clock = |clock: &str| {
+            clock
+                .parse()
+                .map_err(|_| FenError::InvalidClock(clock.to_string()))
+        };
+        let mut state = Self::from_parts(board, turn, castling_rights, en_passant);
+        if let Some(&halfmove_clock) = clocks.first() {
+            state.halfmove_clock = parse_clock(halfmove_clock)?;
+        }
+        if let Some(&fullmove_number) = clocks.get(1) {
+            state.fullmove_number = parse_clock(fullmove_number)?;
+        }
+        debug!("Parsed FEN {fen}");
+        Ok(state)
+    }
+
+    /// Returns the full FEN string describing the state, see [`GameState::from_fen`].
+    ///
+    /// ```
+    /// use chess_lib::game::GameState;
+    ///
+    /// assert_eq!(
+    ///     GameState::new().to_fen(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_fen(&self) -> String {
+        let turn = match self.turn {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+        let rights = self.castling_rights;
+        let mut castling: String = [
+            (rights.white_kingside, 'K'),
+            (rights.white_queenside, 'Q'),
+            (rights.black_kingside, 'k'),
+            (rights.black_queenside, 'q'),
+        ]
+        .into_iter()
+        .filter(|&(right, _)| right)
+        .map(|(_, c)| c)
+        .collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        let en_passant = self
+            .en_passant
+            .map_or_else(|| "-".to_string(), notation::square_name);
+        format!(
+            "{} {turn} {castling} {en_passant} {} {}",
+            self.board.to_fen(),
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+
+    /// Creates a game state from a position where the castling rights and en passant target square are already known, e.g. from FEN or EPD.
+    pub(crate) fn from_parts(
+        board: Board,
+        turn: Color,
+        castling_rights: CastlingRights,
+        en_passant: Option<Position>,
+    ) -> Self {
+        let mut state = Self {
+            board,
+            turn,
+            castling_rights,
+            en_passant,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            history: vec![],
+            hash: 0,
+            keys: vec![],
+            draw_claim: None,
+            variant: Variant::Standard,
+            hand: HashMap::new(),
+            promoted: 0,
+            checks_given: [0; 2],
+        };
+        state.hash = zobrist::zobrist_hash(&state);
+        state.keys.push(state.hash);
+        state
+    }
+
+    /// Returns the state with the game played under the rules of `variant` rather than standard chess.
+    ///
+    /// Should be called before any moves are played, since moves already played were played under the previous rules.
+    ///
+    /// # Parameters
+    /// * `variant`: The rules to play under.
+    #[must_use]
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Returns the rules the game is played under.
+    #[must_use]
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Returns the piece types a pawn can promote to under the rules the game is played under, see [`Variant::promotion_candidates`].
+    #[must_use]
+    pub fn promotion_candidates(&self) -> &[PieceType] {
+        self.variant.promotion_candidates()
+    }
+
+    /// Returns the current board.
+    #[must_use]
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns the number of pieces of each type each color holds in hand, see [`Variant::Crazyhouse`].
+    ///
+    /// Piece types a color holds none of are left out, so the hand is always empty in other variants.
+    #[must_use]
+    pub fn hand(&self) -> &HashMap<(Color, PieceType), u8> {
+        &self.hand
+    }
+
+    /// Returns the color whose turn it is.
+    #[must_use]
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    /// Returns the remaining castling rights.
+    #[must_use]
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
+    /// Returns the en passant target square, if the last move was a pawn moving two squares.
+    #[must_use]
+    pub fn en_passant(&self) -> Option<Position> {
+        self.en_passant
+    }
+
+    /// Returns the number of half moves since the last capture or pawn move, used for the fifty move rule.
+    #[must_use]
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Returns the number of half moves since the last irreversible move.
+    ///
+    /// Captures, pawn moves and any move that loses castling rights, including castling itself, can never be undone by later moves, so no earlier position can repeat after them.
+    /// Same as [`GameState::halfmove_clock`], except that the halfmove clock is not reset by losing castling rights, as the fifty move rule only counts captures and pawn moves.
+    /// Moves from before the game state was created are not known, so only the halfmove clock is used for them.
+    ///
+    /// ```
+    /// use chess_lib::game::GameState;
+    ///
+    /// let state = GameState::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 10 30").unwrap();
+    /// assert_eq!(state.reversible_move_count(), 10);
+    /// ```
+    #[must_use]
+    pub fn reversible_move_count(&self) -> u32 {
+        let mut castling_rights = self.castling_rights;
+        for (count, entry) in (0..self.halfmove_clock).zip(self.history.iter().rev()) {
+            if entry.castling_rights != castling_rights {
+                return count;
+            }
+            castling_rights = entry.castling_rights;
+        }
+        self.halfmove_clock
+    }
+
+    /// Returns the fullmove number.
+    ///
+    /// Starts at 1 and increments after each of black's moves, matching the fullmove number field of FEN.
+    #[must_use]
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /// Returns the number of half moves that have been played.
+    #[must_use]
+    pub fn ply_count(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Returns the Zobrist hash of the position.
+    ///
+    /// The hash is updated as moves are played and undone rather than recomputed, see [`zobrist::zobrist_hash`] for what it covers.
+    #[must_use]
+    pub fn zobrist(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns the standard algebraic notation of the last move played, or `None` if no moves have been played.
+    ///
+    /// The notation is worked out from the state before the move, which is restored from the history.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, game::GameState};
+    ///
+    /// let mut state = GameState::new();
+    /// assert_eq!(state.last_move_san(), None);
+    /// state.make_move(ChessMove::Move(action::Move {
+    ///     from_position: Position::new(4, 1).unwrap(),
+    ///     to_position: Position::new(4, 3).unwrap(),
+    /// })).unwrap();
+    /// assert_eq!(state.last_move_san(), Some("e4".to_string()));
+    /// ```
+    #[must_use]
+    pub fn last_move_san(&self) -> Option<String> {
+        let mut before = self.clone();
+        let chess_move = before.undo()?;
+        Some(notation::to_san(&before, &chess_move))
+    }
+
+    /// Returns every move played so far in the long algebraic notation used by UCI, see [`notation::to_uci`].
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, game::GameState};
+    ///
+    /// let mut state = GameState::new();
+    /// state.make_move(ChessMove::Move(action::Move {
+    ///     from_position: Position::new(4, 1).unwrap(),
+    ///     to_position: Position::new(4, 3).unwrap(),
+    /// })).unwrap();
+    /// assert_eq!(state.uci_move_list(), vec!["e2e4"]);
+    /// ```
+    #[must_use]
+    pub fn uci_move_list(&self) -> Vec<String> {
+        self.history
+            .iter()
+            .map(|entry| notation::to_uci(&entry.chess_move))
+            .collect()
+    }
+
+    /// Returns the number of times `color` has given check, which is only counted in three-check, see [`Variant::ThreeCheck`].
+    ///
+    /// # Parameters
+    /// * `color`: The color to count the checks of.
+    #[must_use]
+    pub fn checks_given(&self, color: Color) -> u8 {
+        self.checks_given[color.index()]
+    }
+
+    /// Returns whether the side to move is in check.
+    #[must_use]
+    pub fn is_check(&self) -> bool {
+        self.board.is_in_check(self.turn)
+    }
+
+    /// Returns whether the side to move is checkmated or stalemated, or `None` if they have a legal move.
+    ///
+    /// Stops looking for legal moves at the first one found, see [`GameState::has_legal_move`].
+    /// Having no king, because it exploded in atomic chess, counts as checkmate.
+    /// Draws by repetition or the move rules are not considered, see [`GameState::status`].
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::Board, game::{GameEnd, GameState}, piece::Color};
+    ///
+    /// assert_eq!(GameState::new().game_end(), None);
+    /// let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8").unwrap();
+    /// assert_eq!(GameState::from_board(board, Color::Black).game_end(), Some(GameEnd::Stalemate));
+    /// ```
+    #[must_use]
+    pub fn game_end(&self) -> Option<GameEnd> {
+        if self.has_legal_move() {
+            None
+        } else if self.is_check() || self.board.king_position(self.turn).is_none() {
+            Some(GameEnd::Checkmate)
+        } else {
+            Some(GameEnd::Stalemate)
+        }
+    }
+
+    /// Returns whether the game is still in progress, or how it ended.
+    ///
+    /// Only draws that the rules apply automatically are reported (stalemate, fivefold repetition and the 75 move rule), unless a draw has been claimed with [`GameState::claim_draw`].
+    ///
+    /// ```
+    /// use chess_lib::game::{GameState, GameStatus};
+    ///
+    /// assert_eq!(GameState::new().status(), GameStatus::InProgress);
+    /// ```
+    #[must_use]
+    pub fn status(&self) -> GameStatus {
+        if let Some(reason) = self.draw_claim {
+            return GameStatus::Finished(GameResult::Draw(reason));
+        }
+        if self.variant == Variant::Atomic {
+            if let Some(color) = [Color::White, Color::Black]
+                .into_iter()
+                .find(|&color| self.board.king_position(color).is_none())
+            {
+                return GameStatus::Finished(GameResult::KingExploded(color.opposite()));
+            }
+        }
+        // Taking is compulsory, so the side to move has no moves left only once it has no pieces that can move
+        if self.variant == Variant::Antichess && !self.has_legal_move() {
+            return GameStatus::Finished(GameResult::GivenAway(self.turn));
+        }
+        if let Some(color) = [Color::White, Color::Black]
+            .into_iter()
+            .find(|&color| self.checks_given(color) >= 3)
+        {
+            return GameStatus::Finished(GameResult::ThreeChecks(color));
+        }
+        if self.variant == Variant::KingOfTheHill {
+            if let Some(color) = [Color::White, Color::Black].into_iter().find(|&color| {
+                self.board
+                    .king_position(color)
+                    .is_some_and(|king| (3..=4).contains(&king.x()) && (3..=4).contains(&king.y()))
+            }) {
+                return GameStatus::Finished(GameResult::HillReached(color));
+            }
+        }
+        match self.game_end() {
+            Some(GameEnd::Checkmate) => {
+                return GameStatus::Finished(GameResult::Checkmate(self.turn.opposite()))
+            }
+            Some(GameEnd::Stalemate) => {
+                return GameStatus::Finished(GameResult::Draw(DrawReason::Stalemate))
+            }
+            None => {}
+        }
+        if self.repetition_count() >= 5 {
+            GameStatus::Finished(GameResult::Draw(DrawReason::FivefoldRepetition))
+        } else if self.halfmove_clock >= 150 {
+            GameStatus::Finished(GameResult::Draw(DrawReason::SeventyFiveMoveRule))
+        } else {
+            GameStatus::InProgress
+        }
+    }
+
+    /// Returns whether the current position has occurred at least three times, so a draw can be claimed.
+    #[must_use]
+    pub fn can_claim_threefold(&self) -> bool {
+        self.repetition_count() >= 3
+    }
+
+    /// Returns whether a draw can be claimed by threefold repetition or the fifty move rule.
+    ///
+    /// Unlike stalemate or fivefold repetition, these draws only end the game once claimed with [`GameState::claim_draw`].
+    #[must_use]
+    pub fn can_claim_draw(&self) -> bool {
+        self.can_claim_threefold() || self.halfmove_clock >= 100
+    }
+
+    /// Claims a draw by threefold repetition or the fifty move rule, ending the game.
+    ///
+    /// The claim is withdrawn if the last move is undone.
+    ///
+    /// # Errors
+    /// * Returns [`ClaimError::GameOver`] if the game has already finished.
+    /// * Returns [`ClaimError::NotClaimable`] if neither threefold repetition nor the fifty move rule applies.
+    pub fn claim_draw(&mut self) -> Result<(), ClaimError> {
+        if self.status() != GameStatus::InProgress {
+            return Err(ClaimError::GameOver);
+        }
+        let reason = if self.can_claim_threefold() {
+            DrawReason::ThreefoldRepetition
+        } else if self.halfmove_clock >= 100 {
+            DrawReason::FiftyMoveRule
+        } else {
+            return Err(ClaimError::NotClaimable);
+        };
+        info!("Draw claimed by {reason:?}");
+        self.draw_claim = Some(reason);
+        Ok(())
+    }
+
+    /// Takes in the position of a piece, returns all the legal moves it could make.
+    ///
+    /// Includes castling and en passant. Pieces that do not belong to the side to move have no legal moves.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `position`: The position of the piece to check.
+    /// # Errors
+    /// * Returns [`PieceError::NotFound`] error if piece does not exist.
+    pub fn legal_moves(&self, position: Position) -> Result<Vec<ChessMove>, PieceError> {
+        let piece = self.board.piece_at(position)?;
+        if piece.color != self.turn {
+            return Ok(vec![]);
+        }
+        if self.variant == Variant::Antichess {
+            let mut moves = self.all_legal_moves();
+            moves.retain(|chess_move| chess_move.movement().from_position == position);
+            return Ok(moves);
+        }
+        let mut moves = self
+            .board
+            .moves_from_promoting(position, self.promotion_candidates())?;
+        match piece.piece_type {
+            PieceType::Pawn => moves.extend(
+                self.en_passant
+                    .and_then(|target| self.board.en_passant_move(position, target)),
+            ),
+            PieceType::King => moves.extend(self.board.castling_moves(
+                self.turn,
+                self.castling_rights.kingside(self.turn),
+                self.castling_rights.queenside(self.turn),
+            )),
+            _ => {}
+        }
+        // Kings can move next to the enemy king in atomic chess, so every king move is checked by playing it
+        let check_king_squares =
+            piece.piece_type == PieceType::King && self.variant != Variant::Atomic;
+        let safe_king_positions = if check_king_squares {
+            self.board.safe_king_positions(self.turn)
+        } else {
+            vec![]
+        };
+        moves.retain(|chess_move| {
+            if check_king_squares && !matches!(chess_move, ChessMove::Castle(_, _)) {
+                safe_king_positions.contains(&chess_move.movement().to_position)
+            } else {
+                self.leaves_king_safe(chess_move)
+            }
+        });
+        Ok(moves)
+    }
+
+    /// Returns every legal move for the side to move.
+    ///
+    /// When in check only moves that could get out of check are generated, see [`Board::check_evasions`], except in atomic chess where exploding the enemy king also gets out of check.
+    /// Includes drops from the player's hand in crazyhouse.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    #[must_use]
+    pub fn all_legal_moves(&self) -> Vec<ChessMove> {
+        if self.variant == Variant::Antichess {
+            return self.board.antichess_legal_moves(self.turn, self.en_passant);
+        }
+        let mut moves: Vec<ChessMove> = if self.variant == Variant::Atomic {
+            self.board
+                .pieces_of(self.turn)
+                .into_iter()
+                .flat_map(|(position, _)| self.legal_moves(position).unwrap_or_default())
+                .collect()
+        } else {
+            self.board
+                .legal_moves_iter_promoting(
+                    self.turn,
+                    self.en_passant,
+                    self.castling_rights.kingside(self.turn),
+                    self.castling_rights.queenside(self.turn),
+                    self.promotion_candidates(),
+                )
+                .collect()
+        };
+        if self.variant == Variant::Crazyhouse {
+            let mut drops = self.drop_moves();
+            drops.retain(|chess_move| self.leaves_king_safe(chess_move));
+            moves.append(&mut drops);
+        }
+        moves
+    }
+
+    /// Returns the number of legal moves for the side to move, the same as the length of [`GameState::all_legal_moves`].
+    ///
+    /// Cheaper than generating the moves when the rules of movement are the same as standard chess, see [`Board::legal_move_count`].
+    ///
+    /// ```
+    /// use chess_lib::game::GameState;
+    ///
+    /// assert_eq!(GameState::new().legal_move_count(), 20);
+    /// ```
+    #[must_use]
+    pub fn legal_move_count(&self) -> usize {
+        match self.variant {
+            Variant::Standard | Variant::ThreeCheck | Variant::KingOfTheHill => {
+                self.board.legal_move_count_promoting(
+                    self.turn,
+                    self.en_passant,
+                    self.castling_rights.kingside(self.turn),
+                    self.castling_rights.queenside(self.turn),
+                    self.promotion_candidates(),
+                )
+            }
+            Variant::Atomic | Variant::Antichess | Variant::Crazyhouse => {
+                self.all_legal_moves().len()
+            }
+        }
+    }
+
+    /// Returns whether the side to move has any legal move, the same as [`GameState::all_legal_moves`] not being empty.
+    ///
+    /// Cheaper than generating the moves when the rules of movement are the same as standard chess, see [`Board::has_legal_move`].
+    #[must_use]
+    pub fn has_legal_move(&self) -> bool {
+        match self.variant {
+            Variant::Standard | Variant::ThreeCheck | Variant::KingOfTheHill => {
+                self.board.has_legal_move(self.turn, self.en_passant)
+            }
+            Variant::Atomic | Variant::Antichess | Variant::Crazyhouse => {
+                !self.all_legal_moves().is_empty()
+            }
+        }
+    }
+
+    /// Returns every legal move for the side to move, except promotions to anything other than a queen.
+    ///
+    /// Useful for simple interfaces that always promote to a queen, since each promotion is one move instead of four.
+    /// Underpromotion is occasionally the only good move, e.g. promoting to a knight to give check or to a rook to avoid stalemate, so engines and move generator tests should use [`GameState::all_legal_moves`] instead.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// ```
+    /// use chess_lib::{board::mailbox::Board, game::GameState, piece::Color};
+    ///
+    /// let state = GameState::from_board(Board::from_fen("4k3/1P6/8/8/8/8/8/4K3").unwrap(), Color::White);
+    /// assert_eq!(state.all_legal_moves().len(), 9);
+    /// assert_eq!(state.legal_moves_queen_promo_only().len(), 6);
+    /// ```
+    #[must_use]
+    pub fn legal_moves_queen_promo_only(&self) -> Vec<ChessMove> {
+        let mut moves = self.all_legal_moves();
+        moves.retain(|chess_move| match chess_move {
+            ChessMove::Promote(_, promote) | ChessMove::PromoteWithTake(_, _, promote) => {
+                promote.piece_type == PieceType::Queen
+            }
+            _ => true,
+        });
+        moves
+    }
+
+    /// Returns every legal move for the side to move that takes a piece, including en passant.
+    ///
+    /// Cheaper than filtering [`GameState::all_legal_moves`], since quiet moves are never generated, see [`Board::capture_moves`].
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// ```
+    /// use chess_lib::game::GameState;
+    ///
+    /// assert!(GameState::new().capture_moves().is_empty());
+    /// ```
+    #[must_use]
+    pub fn capture_moves(&self) -> Vec<ChessMove> {
+        let mut moves = self
+            .board
+            .capture_moves_promoting(self.turn, self.promotion_candidates());
+        if let Some(target) = self.en_passant {
+            moves.extend(
+                self.board
+                    .pieces_of(self.turn)
+                    .into_iter()
+                    .filter_map(|(position, _)| self.board.en_passant_move(position, target)),
+            );
+        }
+        moves.retain(|chess_move| self.leaves_king_safe(chess_move));
+        moves
+    }
+
+    /// Returns why `chess_move` is illegal for the side to move, or `None` if it is legal.
+    ///
+    /// Same as [`GameState::check_move_legal`], for front ends that want to explain a rejected move to the user.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, error::IllegalMoveReason, game::GameState};
+    ///
+    /// let state = GameState::new();
+    /// let e5 = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(4, 6).unwrap(),
+    ///     to_position: Position::new(4, 4).unwrap(),
+    /// });
+    /// assert_eq!(
+    ///     state.why_illegal(e5),
+    ///     Some(IllegalMoveReason::NotYourPiece(Position::new(4, 6).unwrap()))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn why_illegal(&self, chess_move: ChessMove) -> Option<IllegalMoveReason> {
+        self.check_move_legal(&chess_move).err()
+    }
+
+    /// Checks whether `chess_move` is legal for the side to move, returning the reason if it is not.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to check.
+    /// # Errors
+    /// * Returns [`IllegalMoveReason::NoPiece`] if there is no piece to move.
+    /// * Returns [`IllegalMoveReason::NotYourPiece`] if the piece belongs to the opponent.
+    /// * Returns [`IllegalMoveReason::PathBlocked`] if the piece could make the move on an empty board, but another piece is in the way.
+    /// * Returns [`IllegalMoveReason::NotAMoveForThisPiece`] if the piece can never make the move.
+    /// * Returns [`IllegalMoveReason::NoCastlingRights`], [`IllegalMoveReason::CastleOutOfCheck`] or [`IllegalMoveReason::CastleThroughCheck`] if castling is not allowed.
+    /// * Returns [`IllegalMoveReason::LeavesKingInCheck`] if the move would leave the king in check.
+    /// * Returns [`IllegalMoveReason::CaptureRequired`] in antichess if the move does not take a piece, but another move does.
+    /// * Returns [`IllegalMoveReason::NotInHand`] if the move drops a piece the side to move does not hold.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, error::IllegalMoveReason, game::GameState};
+    ///
+    /// let state = GameState::new();
+    /// let rook_up = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(0, 0).unwrap(),
+    ///     to_position: Position::new(0, 2).unwrap(),
+    /// });
+    /// assert_eq!(state.check_move_legal(&rook_up), Err(IllegalMoveReason::PathBlocked));
+    /// ```
+    pub fn check_move_legal(&self, chess_move: &ChessMove) -> Result<(), IllegalMoveReason> {
+        if let ChessMove::Drop(place) = chess_move {
+            self.check_drop_legal(*place)?;
+            return if self.leaves_king_safe(chess_move) {
+                Ok(())
+            } else {
+                Err(IllegalMoveReason::LeavesKingInCheck)
+            };
+        }
+        let movement = chess_move.movement();
+        let from_position = movement.from_position;
+        let Some(piece) = self.board[from_position] else {
+            return Err(IllegalMoveReason::NoPiece(from_position));
+        };
+        if piece.color != self.turn {
+            return Err(IllegalMoveReason::NotYourPiece(from_position));
+        }
+        if let ChessMove::Castle(..) = chess_move {
+            if self.variant == Variant::Antichess {
+                return Err(IllegalMoveReason::NotAMoveForThisPiece);
+            }
+            self.check_castle_legal(chess_move)?;
+        } else if !self.pseudo_legal_moves(from_position).contains(chess_move) {
+            let mut lone_board = Board::empty();
+            lone_board[from_position] = Some(piece);
+            let reachable = lone_board
+                .moves_from(from_position)
+                .unwrap_or_default()
+                .iter()
+                .any(|lone_move| lone_move.movement().to_position == movement.to_position);
+            let blocked = self.is_path_blocked(from_position, movement.to_position)
+                || self.board[movement.to_position].is_some_and(|other| other.color == piece.color);
+            return Err(if reachable && blocked {
+                IllegalMoveReason::PathBlocked
+            } else {
+                IllegalMoveReason::NotAMoveForThisPiece
+            });
+        }
+        if self.variant == Variant::Antichess
+            && chess_move.taken_position().is_none()
+            && !self.capture_moves().is_empty()
+        {
+            return Err(IllegalMoveReason::CaptureRequired);
+        }
+        if self.leaves_king_safe(chess_move) {
+            Ok(())
+        } else {
+            Err(IllegalMoveReason::LeavesKingInCheck)
+        }
+    }
+
+    /// Checks that `chess_move` can be played now, i.e. the game is still in progress and the move is legal for the side to move.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to check.
+    /// # Errors
+    /// * Returns [`MoveError::GameOver`] if the game has already finished.
+    /// * Returns [`MoveError::Illegal`] with the reason the move is illegal, see [`GameState::check_move_legal`].
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, error::*, game::GameState};
+    ///
+    /// let state = GameState::new();
+    /// let black_pawn = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(4, 6).unwrap(),
+    ///     to_position: Position::new(4, 4).unwrap(),
+    /// });
+    /// assert_eq!(
+    ///     state.validate_move(black_pawn),
+    ///     Err(MoveError::Illegal(IllegalMoveReason::NotYourPiece(Position::new(4, 6).unwrap())))
+    /// );
+    /// ```
+    pub fn validate_move(&self, chess_move: ChessMove) -> Result<(), MoveError> {
+        if self.status() != GameStatus::InProgress {
+            return Err(MoveError::GameOver);
+        }
+        Ok(self.check_move_legal(&chess_move)?)
+    }
+
+    /// Checks that `moves` can be played in order from the current position, without changing the state.
+    ///
+    /// # Parameters
+    /// * `moves`: The moves to check, in the order they would be played.
+    /// # Errors
+    /// * Returns the index of the first move that cannot be played, along with [`MoveError::Illegal`] if the move is illegal, or [`MoveError::GameOver`] if the game had already finished.
+    /// * Returns [`MoveError::Piece`] with the index if a legal move could not be played on the board.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, error::*, game::GameState};
+    ///
+    /// let e4 = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(4, 1).unwrap(),
+    ///     to_position: Position::new(4, 3).unwrap(),
+    /// });
+    /// let state = GameState::new();
+    /// assert_eq!(state.validate_moves(&[e4]), Ok(()));
+    /// assert_eq!(
+    ///     state.validate_moves(&[e4, e4]),
+    ///     Err((1, MoveError::Illegal(IllegalMoveReason::NoPiece(Position::new(4, 1).unwrap()))))
+    /// );
+    /// ```
+    pub fn validate_moves(&self, moves: &[ChessMove]) -> Result<(), (usize, MoveError)> {
+        let mut state = self.clone();
+        for (index, chess_move) in moves.iter().enumerate() {
+            state
+                .validate_move(*chess_move)
+                .map_err(|error| (index, error))?;
+            state
+                .make_move(*chess_move)
+                .map_err(|error| (index, error.into()))?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether playing `chess_move` would put the opponent in check.
+    ///
+    /// Does not check that the move is legal.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to check.
+    #[must_use]
+    pub fn move_gives_check(&self, chess_move: &ChessMove) -> bool {
+        let mut board = self.board.clone();
+        board.execute_move(*chess_move).is_ok() && board.is_in_check(self.turn.opposite())
+    }
+
+    /// Returns every legal move for the side to move that puts the opponent in check.
+    ///
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    #[must_use]
+    pub fn checking_moves(&self) -> Vec<ChessMove> {
+        self.all_legal_moves()
+            .into_iter()
+            .filter(|chess_move| self.move_gives_check(chess_move))
+            .collect()
+    }
+
+    /// Returns every legal move for the side to move that takes the piece on `target`.
+    ///
+    /// En passant captures are included when `target` is the pawn being taken, even though the capturing pawn moves to a different square.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `target`: The position of the piece to take.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, game::GameState, piece::*};
+    ///
+    /// let state = GameState::from_board(Board::from_fen("4k3/8/8/3p4/1N2P3/8/8/4K3").unwrap(), Color::White);
+    /// assert_eq!(state.moves_capturing(Position::new(3, 4).unwrap()).len(), 2);
+    /// ```
+    #[must_use]
+    pub fn moves_capturing(&self, target: Position) -> Vec<ChessMove> {
+        self.all_legal_moves()
+            .into_iter()
+            .filter(|chess_move| chess_move.taken_position() == Some(target))
+            .collect()
+    }
+
+    /// Returns every legal move that saves the side to move's hanging piece on `target`.
+    ///
+    /// A move saves the piece if it is no longer hanging after the move is played, see [`Board::hanging_pieces`].
+    /// This includes moving the piece to safety, adding a defender, blocking or taking the attacker.
+    /// Returns an empty vector if `target` is not a hanging piece of the side to move.
+    /// Order of returned vector is arbitrary, and should not be relied on.
+    ///
+    /// # Parameters
+    /// * `target`: The position of the hanging piece.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, game::GameState, piece::*};
+    ///
+    /// let state = GameState::from_board(Board::from_fen("3rk3/8/8/8/8/3B4/8/4K3").unwrap(), Color::White);
+    /// assert_eq!(state.moves_defending(Position::new(3, 2).unwrap()).len(), 13);
+    /// ```
+    #[must_use]
+    pub fn moves_defending(&self, target: Position) -> Vec<ChessMove> {
+        if !self.board.hanging_pieces(self.turn).contains(&target) {
+            return vec![];
+        }
+        self.all_legal_moves()
+            .into_iter()
+            .filter(|chess_move| {
+                let movement = chess_move.movement();
+                let destination = if movement.from_position == target {
+                    movement.to_position
+                } else {
+                    target
+                };
+                let mut board = self.board.clone();
+                board.execute_move(*chess_move).is_ok()
+                    && !board.hanging_pieces(self.turn).contains(&destination)
+            })
+            .collect()
+    }
+
+    /// Returns a knight move that delivers smothered mate, if there is one.
+    ///
+    /// A smothered mate is a checkmate by a knight where every square next to the enemy king is occupied by the enemy's own pieces.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, mailbox::*}, game::GameState, piece::*};
+    ///
+    /// let state = GameState::from_board(Board::from_fen("6rk/6pp/8/6N1/8/8/8/6K1").unwrap(), Color::White);
+    /// let mate = state.smothered_mate_in_one().unwrap();
+    /// assert_eq!(mate.movement().to_position, Position::new(5, 6).unwrap());
+    /// ```
+    #[must_use]
+    pub fn smothered_mate_in_one(&self) -> Option<ChessMove> {
+        self.checking_moves().into_iter().find(|chess_move| {
+            let is_knight = self.board[chess_move.movement().from_position]
+                .is_some_and(|piece| piece.piece_type == PieceType::Knight);
+            if !is_knight {
+                return false;
+            }
+            let mut after = self.clone();
+            if after.make_move(*chess_move).is_err() || !after.all_legal_moves().is_empty() {
+                return false;
+            }
+            let Some(king_position) = after.board.king_position(after.turn) else {
+                return false;
+            };
+            // The king cannot step onto any square, even to take, so it is surrounded by its own pieces
+            after
+                .board
+                .check_positions(king_position)
+                .is_ok_and(|positions| positions.is_empty())
+        })
+    }
+
+    /// Returns false if playing `chess_move` would stalemate the opponent, true otherwise.
+    ///
+    /// The opponent is stalemated if they have no legal moves but are not in check.
+    /// Does not check that the move is legal, if the move cannot be played true is returned.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to check.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action, mailbox::*}, game::GameState, piece::*};
+    ///
+    /// let state = GameState::from_board(Board::from_fen("7k/8/5K2/8/8/8/8/6Q1").unwrap(), Color::White);
+    /// let qg6 = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(6, 0).unwrap(),
+    ///     to_position: Position::new(6, 5).unwrap(),
+    /// });
+    /// assert!(!state.avoids_stalemate(&qg6));
+    /// ```
+    #[must_use]
+    pub fn avoids_stalemate(&self, chess_move: &ChessMove) -> bool {
+        let mut after = self.clone();
+        if after.make_move(*chess_move).is_err() {
+            return true;
+        }
+        after.is_check() || !after.all_legal_moves().is_empty()
+    }
+
+    /// Returns whether the position is quiet, so a static evaluation of it can be trusted.
+    ///
+    /// A position is quiet if the side to move is not in check and has no capture that wins material according to [`Board::see`].
+    ///
+    /// ```
+    /// use chess_lib::game::GameState;
+    ///
+    /// assert!(GameState::new().is_quiet_position());
+    /// ```
+    #[must_use]
+    pub fn is_quiet_position(&self) -> bool {
+        !self.is_check()
+            && !self.all_legal_moves().iter().any(|chess_move| {
+                chess_move
+                    .taken_position()
+                    .is_some_and(|target| self.board.see(target, self.turn) > 0)
+            })
+    }
+
+    /// Returns the score of the position after `chess_move` is played, from the perspective of the side playing it.
+    ///
+    /// The resulting position is searched `depth` half moves ahead from the opponent's perspective and the score negated, so scores of different moves from the same position can be compared to find mistakes.
+    /// If the move cannot be played, a score lower than any real position is returned.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to evaluate.
+    /// * `depth`: The number of half moves to search after the move.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, game::GameState};
+    ///
+    /// let state = GameState::new();
+    /// let e4 = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(4, 1).unwrap(),
+    ///     to_position: Position::new(4, 3).unwrap(),
+    /// });
+    /// assert!(state.evaluate_move(&e4, 0) > 0);
+    /// ```
+    #[must_use]
+    pub fn evaluate_move(&self, chess_move: &ChessMove, depth: u32) -> i32 {
+        let mut state = self.clone();
+        if state.make_move(*chess_move).is_err() {
+            return -INFINITY;
+        }
+        -search::negamax(&mut state, depth, -INFINITY, INFINITY)
+    }
+
+    /// Plays `chess_move` for the side to move.
+    ///
+    /// Does not check that the move is legal. If the move cannot be executed the state is left unchanged.
+    /// In crazyhouse a piece that is taken is added to the mover's hand, as a pawn if it had been promoted.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to play.
+    /// # Errors
+    /// * Returns [`PieceError::NotFound`] if move attempts to move, take or promote a piece that does not exist, or drop a piece that is not in hand.
+    /// * Returns [`PieceError::Occupied`] if move attempts to move piece to a square that is already occupied.
+    pub fn make_move(&mut self, chess_move: ChessMove) -> Result<(), PieceError> {
+        info!("Playing {chess_move:?} for {:?}", self.turn);
+        if let ChessMove::Drop(place) = chess_move {
+            if !self.hand.contains_key(&(place.color, place.piece_type)) {
+                return Err(PieceError::NotFound(place.position));
+            }
+        }
+        let squares =
+            Self::touched_squares(chess_move).map(|position| (position, self.board[position]));
+        let pieces_hash_delta = Self::pieces_hash_delta(&self.board, chess_move);
+        let to_hand = self.captured_for_hand(chess_move);
+        if let Err(error) = self.board.execute_move(chess_move) {
+            self.restore_squares(&squares);
+            return Err(error);
+        }
+        let movement = chess_move.movement();
+        let castling_rights = self.castling_rights;
+        let promoted = self.promoted;
+        self.track_promoted(chess_move);
+        let exploded = if self.variant == Variant::Atomic && chess_move.taken_position().is_some() {
+            self.explode(movement.to_position)
+        } else {
+            vec![]
+        };
+        self.hash ^= pieces_hash_delta;
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
+        for &(position, piece) in &exploded {
+            self.hash ^= zobrist::piece_key(piece, position);
+            self.castling_rights.remove_for(position);
+            self.promoted &= !square_bit(position);
+        }
+        if let ChessMove::Drop(place) = chess_move {
+            self.change_hand(place.color, place.piece_type, false);
+        }
+        if let Some(piece_type) = to_hand {
+            self.change_hand(self.turn, piece_type, true);
+        }
+        self.history.push(UndoInfo {
+            chess_move,
+            squares,
+            castling_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            exploded,
+            to_hand,
+            promoted,
+            checks_given: self.checks_given,
+        });
+
+        // For moves on the board the first touched square is where the moving piece started, a drop only touches the empty square it lands on
+        let is_pawn_move = match chess_move {
+            ChessMove::Drop(place) => place.piece_type == PieceType::Pawn,
+            _ => squares[0]
+                .1
+                .is_some_and(|piece| piece.piece_type == PieceType::Pawn),
+        };
+        if is_pawn_move || chess_move.taken_position().is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        self.castling_rights.remove_for(movement.from_position);
+        self.castling_rights.remove_for(movement.to_position);
+        let is_double_push = matches!(chess_move, ChessMove::Move(_))
+            && self.board[movement.to_position]
+                .is_some_and(|piece| piece.piece_type == PieceType::Pawn)
+            && movement
+                .from_position
+                .y()
+                .abs_diff(movement.to_position.y())
+                == 2;
+        self.en_passant = if is_double_push {
+            Position::new(
+                movement.from_position.x(),
+                movement.from_position.y().min(movement.to_position.y()) + 1,
+            )
+            .ok()
+        } else {
+            None
+        };
+        debug!("En passant target is now {:?}", self.en_passant);
+        self.hash ^= zobrist::castling_key(self.castling_rights);
+        self.hash ^= zobrist::en_passant_key(self.en_passant);
+        self.hash ^= zobrist::black_to_move_key();
+
+        if self.turn == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.turn = self.turn.opposite();
+        if self.variant == Variant::ThreeCheck && self.is_check() {
+            let checker = self.turn.opposite().index();
+            let count = self.checks_given[checker];
+            self.checks_given[checker] += 1;
+            self.hash ^= zobrist::checks_key(self.turn.opposite(), count)
+                ^ zobrist::checks_key(self.turn.opposite(), count + 1);
+        }
+        self.keys.push(self.hash);
+        debug_assert_eq!(self.hash, zobrist::zobrist_hash(self));
+        Ok(())
+    }
+
+    /// Undoes the last move played, returning it.
+    ///
+    /// Returns `None` if no moves have been played.
+    ///
+    /// ```
+    /// use chess_lib::{board::{*, action}, game::GameState};
+    ///
+    /// let mut state = GameState::new();
+    /// let e4 = ChessMove::Move(action::Move {
+    ///     from_position: Position::new(4, 1).unwrap(),
+    ///     to_position: Position::new(4, 3).unwrap(),
+    /// });
+    /// state.make_move(e4).unwrap();
+    /// assert_eq!(state.undo(), Some(e4));
+    /// assert_eq!(state, GameState::new());
+    /// ```
+    pub fn undo(&mut self) -> Option<ChessMove> {
+        let entry = self.history.pop()?;
+        debug!("Undoing {:?}", entry.chess_move);
+        // The exploded pieces include the capturing piece, which the touched squares then move back to where it started
+        for &(position, piece) in &entry.exploded {
+            self.board[position] = Some(piece);
+        }
+        self.restore_squares(&entry.squares);
+        if let Some(piece_type) = entry.to_hand {
+            self.change_hand(self.turn.opposite(), piece_type, false);
+        }
+        if let ChessMove::Drop(place) = entry.chess_move {
+            self.change_hand(place.color, place.piece_type, true);
+        }
+        self.promoted = entry.promoted;
+        self.checks_given = entry.checks_given;
+        self.castling_rights = entry.castling_rights;
+        self.en_passant = entry.en_passant;
+        self.halfmove_clock = entry.halfmove_clock;
+        self.keys.pop();
+        self.hash = self.keys.last().copied().unwrap_or_default();
+        self.draw_claim = None;
+        self.turn = self.turn.opposite();
+        if self.turn == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        debug_assert_eq!(self.hash, zobrist::zobrist_hash(self));
+        Some(entry.chess_move)
+    }
+
+    /// Returns the number of times the current position has occurred, including now.
+    ///
+    /// Positions are compared by Zobrist hash, so include the side to move, castling rights and en passant, but not whether a piece has moved (see [`Board::same_position`]).
+    /// Only positions since the last capture or pawn move are checked, since no earlier position can occur again.
+    pub(crate) fn repetition_count(&self) -> usize {
+        self.keys
+            .iter()
+            .rev()
+            .take(
+                usize::try_from(self.halfmove_clock)
+                    .unwrap_or(usize::MAX)
+                    .saturating_add(1),
+            )
+            .filter(|&&key| key == self.hash)
+            .count()
+    }
+
+    /// Returns the squares `chess_move` changes, starting with the square the moving piece is on.
+    ///
+    /// Moves that change fewer than four squares repeat the last one, so the squares can be saved before the move and restored to undo it.
+    fn touched_squares(chess_move: ChessMove) -> [Position; 4] {
+        match chess_move {
+            ChessMove::Move(movement) | ChessMove::Promote(movement, _) => [
+                movement.from_position,
+                movement.to_position,
+                movement.to_position,
+                movement.to_position,
+            ],
+            ChessMove::MoveWithTake(movement, take)
+            | ChessMove::PromoteWithTake(movement, take, _) => [
+                movement.from_position,
+                movement.to_position,
+                take.position,
+                take.position,
+            ],
+            ChessMove::Castle(king_movement, rook_movement) => [
+                king_movement.from_position,
+                king_movement.to_position,
+                rook_movement.from_position,
+                rook_movement.to_position,
+            ],
+            ChessMove::Drop(place) => [place.position; 4],
+        }
+    }
+
+    /// Returns the piece type taken by `chess_move` that goes into the mover's hand in crazyhouse, or `None` if nothing is taken or the variant has no hands.
+    fn captured_for_hand(&self, chess_move: ChessMove) -> Option<PieceType> {
+        if self.variant != Variant::Crazyhouse {
+            return None;
+        }
+        let position = chess_move.taken_position()?;
+        let piece = self.board[position]?;
+        Some(if self.promoted & square_bit(position) == 0 {
+            piece.piece_type
+        } else {
+            PieceType::Pawn
+        })
+    }
+
+    /// Moves the promoted marker of any promoted piece `chess_move` moves, and marks the square of a new promotion.
+    fn track_promoted(&mut self, chess_move: ChessMove) {
+        let movement = chess_move.movement();
+        let moves_promoted_piece = self.promoted & square_bit(movement.from_position) != 0;
+        if let Some(position) = chess_move.taken_position() {
+            self.promoted &= !square_bit(position);
+        }
+        self.promoted &= !square_bit(movement.from_position);
+        if moves_promoted_piece
+            || matches!(
+                chess_move,
+                ChessMove::Promote(..) | ChessMove::PromoteWithTake(..)
+            )
+        {
+            self.promoted |= square_bit(movement.to_position);
+        }
+    }
+
+    /// Adds one piece of `piece_type` to `color`'s hand if `gain` is true, otherwise removes one, keeping the hash up to date.
+    fn change_hand(&mut self, color: Color, piece_type: PieceType, gain: bool) {
+        let count = self
+            .hand
+            .get(&(color, piece_type))
+            .copied()
+            .unwrap_or_default();
+        let new_count = if gain {
+            count + 1
+        } else {
+            count.saturating_sub(1)
+        };
+        self.hash ^= zobrist::hand_key(color, piece_type, count)
+            ^ zobrist::hand_key(color, piece_type, new_count);
+        if new_count == 0 {
+            self.hand.remove(&(color, piece_type));
+        } else {
+            self.hand.insert((color, piece_type), new_count);
+        }
+    }
+
+    /// Returns every drop the side to move could make with the pieces in their hand, without checking the king is safe.
+    fn drop_moves(&self) -> Vec<ChessMove> {
+        let empty_squares: Vec<Position> = (0..8)
+            .flat_map(|y| (0..8).filter_map(move |x| Position::new(x, y).ok()))
+            .filter(|&position| self.board[position].is_none())
+            .collect();
+        self.hand
+            .keys()
+            .filter(|&&(color, _)| color == self.turn)
+            .flat_map(|&(color, piece_type)| {
+                empty_squares
+                    .iter()
+                    .filter(move |position| {
+                        piece_type != PieceType::Pawn || (1..7).contains(&position.y())
+                    })
+                    .map(move |&position| {
+                        ChessMove::Drop(action::Place {
+                            position,
+                            color,
+                            piece_type,
+                        })
+                    })
+            })
+            .collect()
+    }
+
+    /// Checks a drop for the side to move, without checking the king is safe afterwards.
+    fn check_drop_legal(&self, place: action::Place) -> Result<(), IllegalMoveReason> {
+        if place.color != self.turn {
+            return Err(IllegalMoveReason::NotYourPiece(place.position));
+        }
+        if !self.hand.contains_key(&(place.color, place.piece_type)) {
+            return Err(IllegalMoveReason::NotInHand(place.piece_type));
+        }
+        let pawn_on_back_rank =
+            place.piece_type == PieceType::Pawn && !(1..7).contains(&place.position.y());
+        if self.board[place.position].is_some() || pawn_on_back_rank {
+            return Err(IllegalMoveReason::NotAMoveForThisPiece);
+        }
+        Ok(())
+    }
+
+    /// Removes the pieces caught in an explosion on `center`, returning them along with where they were, see [`Board::explosion`].
+    fn explode(&mut self, center: Position) -> Vec<(Position, Piece)> {
+        let exploded: Vec<(Position, Piece)> = self
+            .board
+            .explosion(center)
+            .into_iter()
+            .filter_map(|position| Some((position, self.board[position].take()?)))
+            .collect();
+        debug!("Explosion on {center} removed {} pieces", exploded.len());
+        exploded
+    }
+
+    /// Puts back what was on each of `squares` before a move changed them.
+    fn restore_squares(&mut self, squares: &[(Position, Option<Piece>)]) {
+        for &(position, piece) in squares {
+            self.board[position] = piece;
+        }
+    }
+
+    /// Returns the change to the Zobrist hash from the pieces `chess_move` moves, takes and promotes on `board`.
+    ///
+    /// `board` must be the board before the move is executed.
+    fn pieces_hash_delta(board: &Board, chess_move: ChessMove) -> u64 {
+        let key_at = |position: Position| {
+            board[position].map_or(0, |piece| zobrist::piece_key(piece, position))
+        };
+        let movement_delta = |movement: action::Move| {
+            board[movement.from_position].map_or(0, |piece| {
+                zobrist::piece_key(piece, movement.from_position)
+                    ^ zobrist::piece_key(piece, movement.to_position)
+            })
+        };
+        let promotion_delta = |movement: action::Move, promote: action::Promote| {
+            board[movement.from_position].map_or(0, |pawn| {
+                let promoted = Piece::new(pawn.color, promote.piece_type);
+                zobrist::piece_key(pawn, promote.position)
+                    ^ zobrist::piece_key(promoted, promote.position)
+            })
+        };
+        match chess_move {
+            ChessMove::Move(movement) => movement_delta(movement),
+            ChessMove::MoveWithTake(movement, take) => {
+                key_at(take.position) ^ movement_delta(movement)
+            }
+            ChessMove::Castle(king_movement, rook_movement) => {
+                movement_delta(king_movement) ^ movement_delta(rook_movement)
+            }
+            ChessMove::Promote(movement, promote) => {
+                movement_delta(movement) ^ promotion_delta(movement, promote)
+            }
+            ChessMove::PromoteWithTake(movement, take, promote) => {
+                key_at(take.position)
+                    ^ movement_delta(movement)
+                    ^ promotion_delta(movement, promote)
+            }
+            ChessMove::Drop(place) => {
+                zobrist::piece_key(Piece::new(place.color, place.piece_type), place.position)
+            }
+        }
+    }
+
+    /// Returns the pseudo legal moves of the piece at `position`, including en passant but not castling.
+    fn pseudo_legal_moves(&self, position: Position) -> Vec<ChessMove> {
+        let mut moves = self
+            .board
+            .moves_from_promoting(position, self.promotion_candidates())
+            .unwrap_or_default();
+        moves.extend(
+            self.en_passant
+                .and_then(|target| self.board.en_passant_move(position, target)),
+        );
+        moves
+    }
+
+    /// Returns whether any square strictly between `from_position` and `to_position` is occupied.
+    ///
+    /// Positions that are not on the same rank, file or diagonal have nothing between them.
+    fn is_path_blocked(&self, from_position: Position, to_position: Position) -> bool {
+        let dx = i8::try_from(to_position.x()).unwrap_or(0)
+            - i8::try_from(from_position.x()).unwrap_or(0);
+        let dy = i8::try_from(to_position.y()).unwrap_or(0)
+            - i8::try_from(from_position.y()).unwrap_or(0);
+        if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+            return false;
+        }
+        let Ok(step) = Offset::new(dx.signum(), dy.signum()) else {
+            return false;
+        };
+        let mut position = from_position;
+        for _ in 1..dx.abs().max(dy.abs()) {
+            match position + step {
+                Ok(next) if self.board[next].is_none() => position = next,
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    /// Checks a castling move for the side to move, without checking the king is safe on arrival.
+    fn check_castle_legal(&self, chess_move: &ChessMove) -> Result<(), IllegalMoveReason> {
+        let y = if self.turn == Color::White { 0 } else { 7 };
+        // x is always a file on the board
+        let square = |x| Position::new(x, y).unwrap();
+        let castle = |king_to_x, rook_x, rook_to_x| {
+            ChessMove::Castle(
+                action::Move {
+                    from_position: square(4),
+                    to_position: square(king_to_x),
+                },
+                action::Move {
+                    from_position: square(rook_x),
+                    to_position: square(rook_to_x),
+                },
+            )
+        };
+        let (allowed, rook_x, path) = if *chess_move == castle(6, 7, 5) {
+            (self.castling_rights.kingside(self.turn), 7, 5..=6)
+        } else if *chess_move == castle(2, 0, 3) {
+            (self.castling_rights.queenside(self.turn), 0, 2..=3)
+        } else {
+            return Err(IllegalMoveReason::NotAMoveForThisPiece);
+        };
+        let rook_present = self.board[square(rook_x)]
+            .is_some_and(|rook| rook.piece_type == PieceType::Rook && rook.color == self.turn);
+        if !allowed || !rook_present {
+            return Err(IllegalMoveReason::NoCastlingRights);
+        }
+        if self.is_path_blocked(square(4), square(rook_x)) {
+            return Err(IllegalMoveReason::PathBlocked);
+        }
+        if self.is_check() {
+            return Err(IllegalMoveReason::CastleOutOfCheck);
+        }
+        if path.into_iter().any(|x| {
+            self.board
+                .is_square_attacked(square(x), self.turn.opposite())
+        }) {
+            return Err(IllegalMoveReason::CastleThroughCheck);
+        }
+        Ok(())
+    }
+
+    /// Returns whether the side to move's king is safe after playing `chess_move`.
+    ///
+    /// In atomic chess the king must also survive any explosion, and is always safe once the enemy king has exploded or while the kings are next to each other.
+    /// There is no check in antichess, so the king is always safe.
+    fn leaves_king_safe(&self, chess_move: &ChessMove) -> bool {
+        let mut board = self.board.clone();
+        if board.execute_move(*chess_move).is_err() {
+            return false;
+        }
+        if self.variant == Variant::Antichess {
+            return true;
+        }
+        if self.variant != Variant::Atomic {
+            return !board.is_in_check(self.turn);
+        }
+        if chess_move.taken_position().is_some() {
+            for position in board.explosion(chess_move.movement().to_position) {
+                board[position] = None;
+            }
+        }
+        match (
+            board.king_position(self.turn),
+            board.king_position(self.turn.opposite()),
+        ) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(king), Some(enemy_king)) => {
+                king.distance(enemy_king) == 1 || !board.is_in_check(self.turn)
+            }
+        }
+    }
+}
+
+/// Returns the bit for `position` in a bitboard, with a1 as the lowest bit.
+fn square_bit(position: Position) -> u64 {
+    1 << (position.y() * 8 + position.x())
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A game of chess between two players.
+///
+/// Wraps a [`GameState`], only allowing legal moves to be played, and lets the players end the game by resigning or agreeing to a draw.
+///
+/// ```
+/// use chess_lib::{board::{*, action}, game::*, piece::Color};
+///
+/// let mut game = Game::new();
+/// game.play(ChessMove::Move(action::Move {
+///     from_position: Position::new(4, 1).unwrap(),
+///     to_position: Position::new(4, 3).unwrap(),
+/// })).unwrap();
+/// game.resign(Color::Black).unwrap();
+/// assert_eq!(game.status(), GameStatus::Finished(GameResult::Resignation(Color::White)));
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Game {
+    state: GameState,
+    /// Set when the game is ended by the players rather than on the board.
+    result: Option<GameResult>,
+    /// The color that has offered a draw, if the offer is still open.
+    draw_offer: Option<Color>,
+}
+
+impl Game {
+    /// Creates a game from the standard starting position.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_position(GameState::new())
+    }
+
+    /// Creates a game continuing from `state`, e.g. a position set up with [`GameState::from_fen`] or [`GameState::from_board`], or one reached by playing moves.
+    ///
+    /// The game carries on from `state` as it is, so the side to move, castling rights and move history are kept.
+    ///
+    /// # Parameters
+    /// * `state`: The position to continue from.
+    #[must_use]
+    pub fn from_position(state: GameState) -> Self {
+        Self {
+            state,
+            result: None,
+            draw_offer: None,
+        }
+    }
+
+    /// Creates a game continuing from a full FEN string, so the side to move, castling rights and clocks all come from the FEN.
+    ///
+    /// Useful for puzzles, analysis and adjourned games. See [`GameState::from_fen`] for the accepted format.
+    ///
+    /// # Parameters
+    /// * `fen`: The FEN string to parse.
+    /// # Errors
+    /// * Returns any error from [`GameState::from_fen`] if the FEN string is invalid.
+    ///
+    /// ```
+    /// use chess_lib::{game::Game, piece::Color};
+    ///
+    /// let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R b KQ - 3 40").unwrap();
+    /// assert_eq!(game.state().turn(), Color::Black);
+    /// assert_eq!(game.state().fullmove_number(), 40);
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Ok(Self::from_position(GameState::from_fen(fen)?))
+    }
+
+    /// Returns the current state of the game.
+    #[must_use]
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Returns the color that has an open draw offer, if any.
+    #[must_use]
+    pub fn draw_offer(&self) -> Option<Color> {
+        self.draw_offer
+    }
+
+    /// Returns whether the game is still in progress, or how it ended.
+    ///
+    /// Resignation and draws by agreement are reported along with everything in [`GameState::status`].
+    #[must_use]
+    pub fn status(&self) -> GameStatus {
+        match self.result {
+            Some(result) => GameStatus::Finished(result),
+            None => self.state.status(),
+        }
+    }
+
+    /// Plays `chess_move` for the side to move, if it is legal.
+    ///
+    /// Playing a move declines any draw offer made by the opponent.
+    ///
+    /// # Parameters
+    /// * `chess_move`: The move to play.
+    /// # Errors
+    /// * Returns [`MoveError::GameOver`] if the game has already finished.
+    /// * Returns [`MoveError::Illegal`] with the reason the move is illegal.
+    /// * Returns [`MoveError::Piece`] if the move could not be played on the board.
+    pub fn play(&mut self, chess_move: ChessMove) -> Result<(), MoveError> {
+        if self.result.is_some() {
+            return Err(MoveError::GameOver);
+        }
+        self.state.validate_move(chess_move)?;
+        let opponent = self.state.turn.opposite();
+        self.state.make_move(chess_move)?;
+        if self.draw_offer == Some(opponent) {
+            debug!("Draw offer declined");
+            self.draw_offer = None;
+        }
+        Ok(())
+    }
+
+    /// Resigns the game for `color`, so the opponent wins.
+    ///
+    /// # Parameters
+    /// * `color`: The color resigning.
+    /// # Errors
+    /// * Returns [`GameEndError::GameOver`] if the game has already finished.
+    pub fn resign(&mut self, color: Color) -> Result<(), GameEndError> {
+        if self.status() != GameStatus::InProgress {
+            return Err(GameEndError::GameOver);
+        }
+        info!("{color} resigned");
+        self.result = Some(GameResult::Resignation(color.opposite()));
+        Ok(())
+    }
+
+    /// Offers a draw from `color`, which stays open until the opponent accepts it or plays a move.
+    ///
+    /// # Parameters
+    /// * `color`: The color offering the draw.
+    /// # Errors
+    /// * Returns [`GameEndError::GameOver`] if the game has already finished.
+    pub fn offer_draw(&mut self, color: Color) -> Result<(), GameEndError> {
+        if self.status() != GameStatus::InProgress {
+            return Err(GameEndError::GameOver);
+        }
+        debug!("{color} offered a draw");
+        self.draw_offer = Some(color);
+        Ok(())
+    }
+
+    /// Accepts the opponent's draw offer for `color`, ending the game in a draw.
+    ///
+    /// # Parameters
+    /// * `color`: The color accepting the draw.
+    /// # Errors
+    /// * Returns [`GameEndError::GameOver`] if the game has already finished.
+    /// * Returns [`GameEndError::NoDrawOffer`] if the opponent has not offered a draw.
+    pub fn accept_draw(&mut self, color: Color) -> Result<(), GameEndError> {
+        if self.status() != GameStatus::InProgress {
+            return Err(GameEndError::GameOver);
+        }
+        if self.draw_offer != Some(color.opposite()) {
+            return Err(GameEndError::NoDrawOffer(color));
+        }
+        info!("Draw agreed");
+        self.draw_offer = None;
+        self.result = Some(GameResult::Draw(DrawReason::Agreement));
+        Ok(())
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod game_state_tests {
+    use super::*;
+    use crate::board::{action, Position};
+
+    fn simple_move(from: (u8, u8), to: (u8, u8)) -> ChessMove {
+        ChessMove::Move(action::Move {
+            from_position: Position::new(from.0, from.1).unwrap(),
+            to_position: Position::new(to.0, to.1).unwrap(),
+        })
+    }
+
+    fn take(from: (u8, u8), to: (u8, u8)) -> ChessMove {
+        ChessMove::MoveWithTake(
+            action::Move {
+                from_position: Position::new(from.0, from.1).unwrap(),
+                to_position: Position::new(to.0, to.1).unwrap(),
+            },
+            action::Take {
+                position: Position::new(to.0, to.1).unwrap(),
+            },
+        )
+    }
+
+    fn from_fen(placement: &str, turn: Color) -> GameState {
+        GameState::from_board(Board::from_fen(placement).unwrap(), turn)
+    }
+
+    fn shuffle_knights(state: &mut GameState, times: usize) {
+        for _ in 0..times {
+            state.make_move(simple_move((6, 0), (5, 2))).unwrap();
+            state.make_move(simple_move((6, 7), (5, 5))).unwrap();
+            state.make_move(simple_move((5, 2), (6, 0))).unwrap();
+            state.make_move(simple_move((5, 5), (6, 7))).unwrap();
+        }
+    }
+
+    mod legal_moves {
+        use super::*;
+
+        #[test]
+        fn start_position() {
+            let state = GameState::new();
+            assert_eq!(state.all_legal_moves().len(), 20);
+        }
+
+        #[test]
+        fn pinned_piece() {
+            let state = from_fen("4k3/8/8/8/8/4r3/4B3/4K3", Color::White);
+            assert!(state
+                .legal_moves(Position::new(4, 1).unwrap())
+                .unwrap()
+                .is_empty());
+        }
+
+        #[test]
+        fn must_escape_check() {
+            let state = from_fen("4k3/8/8/8/8/8/8/r3K3", Color::White);
+            let mut result: Vec<Position> = state
+                .all_legal_moves()
+                .iter()
+                .map(|chess_move| chess_move.movement().to_position)
+                .collect();
+            result.sort();
+            let mut expected_result = vec![
+                Position::new(3, 1).unwrap(),
+                Position::new(4, 1).unwrap(),
+                Position::new(5, 1).unwrap(),
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn king_cannot_move_along_check() {
+            let state = from_fen("4r1k1/8/8/8/8/8/8/4K3", Color::White);
+            let result: Vec<Position> = state
+                .legal_moves(Position::new(4, 0).unwrap())
+                .unwrap()
+                .iter()
+                .map(|chess_move| chess_move.movement().to_position)
+                .collect();
+            assert_eq!(result.len(), 4);
+            assert!(!result.contains(&Position::new(4, 1).unwrap()));
+        }
+
+        #[test]
+        fn castling() {
+            let state = from_fen("r3k2r/8/8/8/8/8/8/R3K2R", Color::White);
+            let castles = state
+                .legal_moves(Position::new(4, 0).unwrap())
+                .unwrap()
+                .into_iter()
+                .filter(|chess_move| matches!(chess_move, ChessMove::Castle(_, _)))
+                .count();
+            assert_eq!(castles, 2);
+        }
+
+        #[test]
+        fn castling_rights_lost() {
+            let mut state = from_fen("r3k2r/8/8/8/8/8/8/R3K2R", Color::White);
+            state.make_move(simple_move((7, 0), (7, 1))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            state.make_move(simple_move((7, 1), (7, 0))).unwrap();
+            assert!(!state.castling_rights().white_kingside);
+            assert!(state.castling_rights().white_queenside);
+            assert!(!state.castling_rights().kingside(Color::Black));
+            assert!(!state.castling_rights().queenside(Color::Black));
+        }
+
+        #[test]
+        fn en_passant() {
+            let mut state = from_fen("4k3/8/8/8/5p2/8/4P3/4K3", Color::White);
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            assert_eq!(state.en_passant(), Some(Position::new(4, 2).unwrap()));
+            assert!(state
+                .legal_moves(Position::new(5, 3).unwrap())
+                .unwrap()
+                .contains(&ChessMove::MoveWithTake(
+                    action::Move {
+                        from_position: Position::new(5, 3).unwrap(),
+                        to_position: Position::new(4, 2).unwrap(),
+                    },
+                    action::Take {
+                        position: Position::new(4, 3).unwrap(),
+                    }
+                )));
+            state.undo();
+            assert_eq!(state.en_passant(), None);
+        }
+
+        #[test]
+        fn en_passant_exposes_king() {
+            // Taking en passant removes both pawns from the fifth rank, leaving the king in check from the rook
+            let mut state = from_fen("8/3p4/8/K3P2r/8/8/8/7k", Color::Black);
+            state.make_move(simple_move((3, 6), (3, 4))).unwrap();
+            let en_passant = ChessMove::MoveWithTake(
+                action::Move {
+                    from_position: Position::new(4, 4).unwrap(),
+                    to_position: Position::new(3, 5).unwrap(),
+                },
+                action::Take {
+                    position: Position::new(3, 4).unwrap(),
+                },
+            );
+            assert_eq!(state.en_passant(), Position::new(3, 5).ok());
+            assert_eq!(
+                state
+                    .board()
+                    .en_passant_move(Position::new(4, 4).unwrap(), Position::new(3, 5).unwrap()),
+                Some(en_passant)
+            );
+            assert!(!state
+                .legal_moves(Position::new(4, 4).unwrap())
+                .unwrap()
+                .contains(&en_passant));
+            assert!(!state.all_legal_moves().contains(&en_passant));
+            assert!(!state.capture_moves().contains(&en_passant));
+            assert_eq!(state.legal_move_count(), state.all_legal_moves().len());
+            assert_eq!(
+                state.check_move_legal(&en_passant),
+                Err(IllegalMoveReason::LeavesKingInCheck)
+            );
+        }
+    }
+
+    mod legal_moves_queen_promo_only {
+        use super::*;
+
+        #[test]
+        fn promotions() {
+            let state = from_fen("1n2k3/P7/8/8/8/8/8/4K3", Color::White);
+            let all = state.all_legal_moves();
+            let queen_only = state.legal_moves_queen_promo_only();
+            assert_eq!(all.len() - queen_only.len(), 6);
+            let promotions: Vec<ChessMove> = queen_only
+                .into_iter()
+                .filter(|chess_move| {
+                    matches!(
+                        chess_move,
+                        ChessMove::Promote(_, _) | ChessMove::PromoteWithTake(_, _, _)
+                    )
+                })
+                .collect();
+            assert_eq!(promotions.len(), 2);
+            assert!(promotions.iter().all(|chess_move| all.contains(chess_move)));
+        }
+
+        #[test]
+        fn no_promotions() {
+            let state = GameState::new();
+            assert_eq!(state.legal_moves_queen_promo_only().len(), 20);
+        }
+    }
+
+    mod capture_moves {
+        use super::*;
+
+        fn legal_captures(state: &GameState) -> Vec<ChessMove> {
+            state
+                .all_legal_moves()
+                .into_iter()
+                .filter(|chess_move| chess_move.taken_position().is_some())
+                .collect()
+        }
+
+        #[test]
+        fn tactical_position() {
+            let state = from_fen(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                Color::White,
+            );
+            let mut result = state.capture_moves();
+            let mut expected_result = legal_captures(&state);
+            assert_eq!(result.len(), 8);
+            result.sort_by_key(|chess_move| {
+                let movement = chess_move.movement();
+                (movement.from_position, movement.to_position)
+            });
+            expected_result.sort_by_key(|chess_move| {
+                let movement = chess_move.movement();
+                (movement.from_position, movement.to_position)
+            });
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn en_passant() {
+            let mut state = from_fen("4k3/8/8/8/5p2/8/4P3/4K3", Color::White);
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            assert_eq!(state.capture_moves(), legal_captures(&state));
+            assert_eq!(state.capture_moves().len(), 1);
+        }
+
+        #[test]
+        fn pinned_piece() {
+            let state = from_fen("4r1k1/8/8/8/8/8/4N3/4K1r1", Color::White);
+            let result = state.capture_moves();
+            assert_eq!(result, legal_captures(&state));
+        }
+    }
+
+    mod from_fen {
+        use super::*;
+
+        #[test]
+        fn all_fields() {
+            let state = GameState::from_fen(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq - 3 17",
+            )
+            .unwrap();
+            assert_eq!(state.turn(), Color::Black);
+            assert_eq!(
+                state.castling_rights(),
+                CastlingRights {
+                    white_kingside: true,
+                    white_queenside: false,
+                    black_kingside: false,
+                    black_queenside: true,
+                }
+            );
+            assert_eq!(state.en_passant(), None);
+            assert_eq!(state.halfmove_clock(), 3);
+            assert_eq!(state.fullmove_number(), 17);
+        }
+
+        #[test]
+        fn en_passant() {
+            let state = GameState::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+            assert_eq!(state.en_passant(), Position::new(4, 2).ok());
+            let en_passant = state
+                .all_legal_moves()
+                .into_iter()
+                .filter(|chess_move| chess_move.taken_position() == Position::new(4, 3).ok())
+                .count();
+            assert_eq!(en_passant, 1);
+        }
+
+        #[test]
+        fn clocks_optional() {
+            let state = GameState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -")
+                .unwrap();
+            assert_eq!(state, GameState::new());
+        }
+
+        #[test]
+        fn matches_start_position() {
+            let state =
+                GameState::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                    .unwrap();
+            assert_eq!(state.zobrist(), GameState::new().zobrist());
+            assert_eq!(state.all_legal_moves().len(), 20);
+        }
+
+        #[test]
+        fn invalid_fields() {
+            let placement = "4k3/8/8/8/8/8/8/4K3";
+            assert_eq!(
+                GameState::from_fen(placement).unwrap_err(),
+                FenError::FieldCount(1)
+            );
+            assert_eq!(
+                GameState::from_fen(&format!("{placement} w - - 0 1 x")).unwrap_err(),
+                FenError::FieldCount(7)
+            );
+            assert_eq!(
+                GameState::from_fen(&format!("{placement} x - - 0 1")).unwrap_err(),
+                FenError::InvalidTurn("x".to_string())
+            );
+            assert_eq!(
+                GameState::from_fen(&format!("{placement} w KX - 0 1")).unwrap_err(),
+                FenError::InvalidCastling("KX".to_string())
+            );
+            assert_eq!(
+                GameState::from_fen(&format!("{placement} w - e9 0 1")).unwrap_err(),
+                FenError::InvalidEnPassant("e9".to_string())
+            );
+            assert_eq!(
+                GameState::from_fen(&format!("{placement} w - - -1 1")).unwrap_err(),
+                FenError::InvalidClock("-1".to_string())
+            );
+            assert_eq!(
+                GameState::from_fen("4k3/8/8 w - - 0 1").unwrap_err(),
+                FenError::RankCount(3)
+            );
+        }
+    }
+
+    mod to_fen {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            for fen in [
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3",
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 12 40",
+            ] {
+                assert_eq!(GameState::from_fen(fen).unwrap().to_fen(), fen);
+            }
+        }
+
+        #[test]
+        fn after_moves() {
+            let mut state = GameState::new();
+            for san in ["e4", "c5", "Nf3"] {
+                let chess_move = notation::parse_san(&state, san).unwrap();
+                state.make_move(chess_move).unwrap();
+            }
+            assert_eq!(
+                state.to_fen(),
+                "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+            );
+        }
+    }
+
+    mod game_end {
+        use super::*;
+
+        #[test]
+        fn in_progress() {
+            assert_eq!(GameState::new().game_end(), None);
+            let state = from_fen("4k3/8/8/8/8/8/8/4K2R", Color::Black);
+            assert_eq!(state.game_end(), None);
+        }
+
+        #[test]
+        fn fools_mate() {
+            let mut state = GameState::new();
+            for san in ["f3", "e5", "g4", "Qh4#"] {
+                let chess_move = notation::parse_san(&state, san).unwrap();
+                state.make_move(chess_move).unwrap();
+            }
+            assert_eq!(state.game_end(), Some(GameEnd::Checkmate));
+        }
+
+        #[test]
+        fn back_rank_mate() {
+            let state = from_fen("R5k1/5ppp/8/8/8/8/8/6K1", Color::Black);
+            assert_eq!(state.game_end(), Some(GameEnd::Checkmate));
+        }
+
+        #[test]
+        fn king_and_queen_stalemate() {
+            let state = from_fen("7k/5Q2/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(state.game_end(), Some(GameEnd::Stalemate));
+        }
+
+        #[test]
+        fn king_and_pawn_stalemate() {
+            let state = from_fen("8/8/8/8/8/2k5/2p5/2K5", Color::White);
+            assert_eq!(state.game_end(), Some(GameEnd::Stalemate));
+        }
+    }
+
+    mod why_illegal {
+        use super::*;
+
+        #[test]
+        fn legal_move() {
+            let state = GameState::new();
+            assert_eq!(state.why_illegal(simple_move((6, 0), (5, 2))), None);
+        }
+
+        #[test]
+        fn reasons() {
+            let state = from_fen("4k3/8/8/8/4r3/8/8/R3K3", Color::White);
+            assert_eq!(
+                state.why_illegal(simple_move((2, 2), (2, 3))),
+                Some(IllegalMoveReason::NoPiece(Position::new(2, 2).unwrap()))
+            );
+            assert_eq!(
+                state.why_illegal(simple_move((4, 3), (4, 2))),
+                Some(IllegalMoveReason::NotYourPiece(
+                    Position::new(4, 3).unwrap()
+                ))
+            );
+            assert_eq!(
+                state.why_illegal(simple_move((0, 0), (1, 1))),
+                Some(IllegalMoveReason::NotAMoveForThisPiece)
+            );
+            assert_eq!(
+                state.why_illegal(simple_move((0, 0), (0, 2))),
+                Some(IllegalMoveReason::LeavesKingInCheck)
+            );
+        }
+    }
+
+    mod check_move_legal {
+        use super::*;
+
+        #[test]
+        fn legal_moves_agree() {
+            let state = from_fen("r3k2r/8/8/8/8/4b3/8/R3K2R", Color::White);
+            for chess_move in state.all_legal_moves() {
+                assert_eq!(state.check_move_legal(&chess_move), Ok(()));
+            }
+        }
+
+        #[test]
+        fn no_piece() {
+            let state = GameState::new();
+            assert_eq!(
+                state.check_move_legal(&simple_move((4, 3), (4, 4))),
+                Err(IllegalMoveReason::NoPiece(Position::new(4, 3).unwrap()))
+            );
+        }
+
+        #[test]
+        fn not_your_piece() {
+            let state = GameState::new();
+            assert_eq!(
+                state.check_move_legal(&simple_move((4, 6), (4, 4))),
+                Err(IllegalMoveReason::NotYourPiece(
+                    Position::new(4, 6).unwrap()
+                ))
+            );
+        }
+
+        #[test]
+        fn not_a_move_for_this_piece() {
+            let state = GameState::new();
+            assert_eq!(
+                state.check_move_legal(&simple_move((1, 0), (1, 2))),
+                Err(IllegalMoveReason::NotAMoveForThisPiece)
+            );
+            assert_eq!(
+                state.check_move_legal(&simple_move((4, 1), (4, 4))),
+                Err(IllegalMoveReason::NotAMoveForThisPiece)
+            );
+        }
+
+        #[test]
+        fn path_blocked() {
+            let state = GameState::new();
+            assert_eq!(
+                state.check_move_legal(&simple_move((2, 0), (4, 2))),
+                Err(IllegalMoveReason::PathBlocked)
+            );
+            assert_eq!(
+                state.check_move_legal(&simple_move((0, 0), (0, 1))),
+                Err(IllegalMoveReason::PathBlocked)
+            );
+        }
+
+        #[test]
+        fn leaves_king_in_check() {
+            let state = from_fen("4k3/8/8/8/8/4r3/4B3/4K3", Color::White);
+            assert_eq!(
+                state.check_move_legal(&simple_move((4, 1), (3, 2))),
+                Err(IllegalMoveReason::LeavesKingInCheck)
+            );
+        }
+
+        fn white_kingside_castle() -> ChessMove {
+            ChessMove::Castle(
+                action::Move {
+                    from_position: Position::new(4, 0).unwrap(),
+                    to_position: Position::new(6, 0).unwrap(),
+                },
+                action::Move {
+                    from_position: Position::new(7, 0).unwrap(),
+                    to_position: Position::new(5, 0).unwrap(),
+                },
+            )
+        }
+
+        #[test]
+        fn castle_through_check() {
+            let state = from_fen("4k3/8/8/8/8/8/5r2/4K2R", Color::White);
+            assert_eq!(
+                state.check_move_legal(&white_kingside_castle()),
+                Err(IllegalMoveReason::CastleThroughCheck)
+            );
+        }
+
+        #[test]
+        fn castle_out_of_check() {
+            let state = from_fen("4k3/8/8/8/8/8/4r3/4K2R", Color::White);
+            assert_eq!(
+                state.check_move_legal(&white_kingside_castle()),
+                Err(IllegalMoveReason::CastleOutOfCheck)
+            );
+        }
+
+        #[test]
+        fn castle_blocked() {
+            let state = from_fen("4k3/8/8/8/8/8/8/4KB1R", Color::White);
+            assert_eq!(
+                state.check_move_legal(&white_kingside_castle()),
+                Err(IllegalMoveReason::PathBlocked)
+            );
+        }
+
+        #[test]
+        fn no_castling_rights() {
+            let mut state = from_fen("4k3/8/8/8/8/8/8/4K2R", Color::White);
+            state.make_move(simple_move((7, 0), (7, 1))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            state.make_move(simple_move((7, 1), (7, 0))).unwrap();
+            state.make_move(simple_move((4, 6), (4, 7))).unwrap();
+            assert_eq!(
+                state.check_move_legal(&white_kingside_castle()),
+                Err(IllegalMoveReason::NoCastlingRights)
+            );
+        }
+    }
+
+    mod validate_move {
+        use super::*;
+
+        #[test]
+        fn legal() {
+            let state = GameState::new();
+            assert_eq!(state.validate_move(simple_move((6, 0), (5, 2))), Ok(()));
+        }
+
+        #[test]
+        fn leaves_king_in_check() {
+            let state = from_fen("4r1k1/8/8/8/8/8/4N3/4K3", Color::White);
+            assert_eq!(
+                state.validate_move(simple_move((4, 1), (2, 2))),
+                Err(MoveError::Illegal(IllegalMoveReason::LeavesKingInCheck))
+            );
+        }
+
+        #[test]
+        fn game_over() {
+            let state = from_fen("7k/6Q1/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(
+                state.validate_move(simple_move((7, 7), (6, 6))),
+                Err(MoveError::GameOver)
+            );
+        }
+    }
+
+    mod validate_moves {
+        use super::*;
+
+        #[test]
+        fn fools_mate() {
+            let state = GameState::new();
+            let moves = [
+                simple_move((5, 1), (5, 2)),
+                simple_move((4, 6), (4, 4)),
+                simple_move((6, 1), (6, 3)),
+                simple_move((3, 7), (7, 3)),
+            ];
+            assert_eq!(state.validate_moves(&moves), Ok(()));
+            let mut after_mate = moves.to_vec();
+            after_mate.push(simple_move((0, 1), (0, 2)));
+            assert_eq!(
+                state.validate_moves(&after_mate),
+                Err((4, MoveError::GameOver))
+            );
+            assert_eq!(state, GameState::new());
+        }
+
+        #[test]
+        fn first_illegal_move() {
+            let state = GameState::new();
+            let moves = [
+                simple_move((4, 1), (4, 3)),
+                simple_move((4, 6), (4, 4)),
+                simple_move((4, 3), (4, 4)),
+                simple_move((0, 0), (0, 5)),
+            ];
+            assert_eq!(
+                state.validate_moves(&moves),
+                Err((
+                    2,
+                    MoveError::Illegal(IllegalMoveReason::NotAMoveForThisPiece)
+                ))
+            );
+        }
+    }
+
+    mod checking_moves {
+        use super::*;
+
+        #[test]
+        fn rooks_and_knight() {
+            let state = from_fen("4k3/8/8/3N4/8/8/8/R3K2R", Color::White);
+            let mut result: Vec<Position> = state
+                .checking_moves()
+                .iter()
+                .map(|chess_move| chess_move.movement().to_position)
+                .collect();
+            result.sort();
+            let mut expected_result = vec![
+                Position::new(0, 7).unwrap(),
+                Position::new(7, 7).unwrap(),
+                Position::new(5, 5).unwrap(),
+                Position::new(2, 6).unwrap(),
+            ];
+            expected_result.sort();
+            assert_eq!(result, expected_result);
+        }
+
+        #[test]
+        fn start_position() {
+            assert!(GameState::new().checking_moves().is_empty());
+        }
+    }
+
+    mod moves_capturing {
+        use super::*;
+
+        #[test]
+        fn attackers_of_pawn() {
+            let state = from_fen("4k3/8/8/3p4/1N2P3/8/8/4K3", Color::White);
+            let mut result: Vec<Position> = state
+                .moves_capturing(Position::new(3, 4).unwrap())
+                .iter()
+                .map(|chess_move| chess_move.movement().from_position)
+                .collect();
+            result.sort();
+            assert_eq!(
+                result,
+                vec![Position::new(1, 3).unwrap(), Position::new(4, 3).unwrap()]
+            );
+        }
+
+        #[test]
+        fn en_passant() {
+            let mut state = from_fen("4k3/8/8/8/5p2/8/4P3/4K3", Color::White);
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            let result = state.moves_capturing(Position::new(4, 3).unwrap());
+            assert_eq!(result.len(), 1);
+            assert_eq!(
+                result[0].movement().to_position,
+                Position::new(4, 2).unwrap()
+            );
+        }
+
+        #[test]
+        fn nothing_to_take() {
+            let state = GameState::new();
+            assert!(state
+                .moves_capturing(Position::new(4, 6).unwrap())
+                .is_empty());
+        }
+    }
+
+    mod moves_defending {
+        use super::*;
+
+        #[test]
+        fn bishop_attacked_by_rook() {
+            let state = from_fen("3rk3/8/8/8/8/3B4/8/4K3", Color::White);
+            let moves = state.moves_defending(Position::new(3, 2).unwrap());
+            let bishop_moves = moves
+                .iter()
+                .filter(|chess_move| {
+                    chess_move.movement().from_position == Position::new(3, 2).unwrap()
+                })
+                .count();
+            assert_eq!(bishop_moves, 11);
+            let mut king_moves: Vec<Position> = moves
+                .iter()
+                .map(ChessMove::movement)
+                .filter(|movement| movement.from_position == Position::new(4, 0).unwrap())
+                .map(|movement| movement.to_position)
+                .collect();
+            king_moves.sort();
+            assert_eq!(
+                king_moves,
+                vec![Position::new(3, 1).unwrap(), Position::new(4, 1).unwrap()]
+            );
+        }
+
+        #[test]
+        fn take_attacker() {
+            let state = from_fen("4k3/8/8/8/8/3B4/4p3/3RK3", Color::White);
+            // The pawn on e2 attacks the rook on d1, taking it with the king or bishop saves the rook
+            let takes = state
+                .moves_defending(Position::new(3, 0).unwrap())
+                .into_iter()
+                .filter(|chess_move| chess_move.taken_position().is_some())
+                .count();
+            assert_eq!(takes, 2);
+        }
+
+        #[test]
+        fn not_hanging() {
+            let state = GameState::new();
+            assert!(state
+                .moves_defending(Position::new(3, 1).unwrap())
+                .is_empty());
+            assert!(state
+                .moves_defending(Position::new(3, 3).unwrap())
+                .is_empty());
+        }
+    }
+
+    mod smothered_mate_in_one {
+        use super::*;
+
+        #[test]
+        fn knight_to_f7() {
+            let state = from_fen("6rk/6pp/8/6N1/8/8/8/6K1", Color::White);
+            assert_eq!(
+                state.smothered_mate_in_one(),
+                Some(simple_move((6, 4), (5, 6)))
+            );
+        }
+
+        #[test]
+        fn mate_not_smothered() {
+            let state = from_fen("7k/7p/8/4N3/8/8/8/6RK", Color::White);
+            let mate = simple_move((4, 4), (5, 6));
+            let mut after = state.clone();
+            after.make_move(mate).unwrap();
+            assert_eq!(
+                after.status(),
+                GameStatus::Finished(GameResult::Checkmate(Color::White))
+            );
+            assert!(state.smothered_mate_in_one().is_none());
+        }
+
+        #[test]
+        fn check_not_mate() {
+            let state = from_fen("7k/6pp/8/6N1/8/8/8/6K1", Color::White);
+            assert!(state.smothered_mate_in_one().is_none());
+        }
+    }
+
+    mod avoids_stalemate {
+        use super::*;
+
+        #[test]
+        fn queen_stalemates() {
+            let state = from_fen("7k/8/5K2/8/8/8/8/6Q1", Color::White);
+            assert!(!state.avoids_stalemate(&simple_move((6, 0), (6, 5))));
+        }
+
+        #[test]
+        fn checkmate_is_not_stalemate() {
+            let state = from_fen("7k/8/5K2/8/8/8/8/6Q1", Color::White);
+            assert!(state.avoids_stalemate(&simple_move((6, 0), (6, 6))));
+        }
+
+        #[test]
+        fn quiet_move() {
+            let state = from_fen("7k/8/5K2/8/8/8/8/6Q1", Color::White);
+            assert!(state.avoids_stalemate(&simple_move((6, 0), (6, 1))));
+        }
+    }
+
+    mod is_quiet_position {
+        use super::*;
+
+        #[test]
+        fn winning_capture() {
+            let state = from_fen("4k3/8/8/3n4/4P3/8/8/4K3", Color::White);
+            assert!(!state.is_quiet_position());
+        }
+
+        #[test]
+        fn losing_capture() {
+            let state = from_fen("4k3/8/2p5/3p4/8/8/8/3QK3", Color::White);
+            assert!(state.is_quiet_position());
+        }
+
+        #[test]
+        fn in_check() {
+            let state = from_fen("4k3/8/8/8/8/8/8/r3K3", Color::White);
+            assert!(!state.is_quiet_position());
+        }
+    }
+
+    mod evaluate_move {
+        use super::*;
+
+        #[test]
+        fn blunder_found_with_depth() {
+            let state = from_fen("4k3/8/4p3/3p4/8/8/3Q4/4K3", Color::White);
+            let take_pawn = ChessMove::MoveWithTake(
+                action::Move {
+                    from_position: Position::new(3, 1).unwrap(),
+                    to_position: Position::new(3, 4).unwrap(),
+                },
+                action::Take {
+                    position: Position::new(3, 4).unwrap(),
+                },
+            );
+            let quiet = simple_move((3, 1), (3, 2));
+            // The recapture is found by the quiescence search even at depth 0
+            assert!(state.evaluate_move(&take_pawn, 0) < state.evaluate_move(&quiet, 0));
+            assert!(state.evaluate_move(&take_pawn, 1) < state.evaluate_move(&quiet, 1));
+            // Only black's pawn is left, give or take the bonus for where the pieces stand
+            assert!((-150..-50).contains(&state.evaluate_move(&take_pawn, 1)));
+        }
+
+        #[test]
+        fn impossible_move() {
+            let state = GameState::new();
+            assert!(state.evaluate_move(&simple_move((3, 3), (3, 4)), 1) < -search::MATE_SCORE);
+        }
+    }
+
+    mod zobrist {
+        use super::*;
+
+        #[test]
+        fn transposition() {
+            let mut knights_first = GameState::new();
+            knights_first
+                .make_move(simple_move((6, 0), (5, 2)))
+                .unwrap();
+            knights_first
+                .make_move(simple_move((6, 7), (5, 5)))
+                .unwrap();
+            knights_first
+                .make_move(simple_move((1, 0), (2, 2)))
+                .unwrap();
+            let mut queenside_first = GameState::new();
+            queenside_first
+                .make_move(simple_move((1, 0), (2, 2)))
+                .unwrap();
+            queenside_first
+                .make_move(simple_move((6, 7), (5, 5)))
+                .unwrap();
+            queenside_first
+                .make_move(simple_move((6, 0), (5, 2)))
+                .unwrap();
+            assert_eq!(knights_first.zobrist(), queenside_first.zobrist());
+            assert_ne!(knights_first.zobrist(), GameState::new().zobrist());
+        }
+
+        #[test]
+        fn matches_from_scratch() {
+            let mut state = from_fen("r3k3/1P6/8/8/5p2/8/4P3/R3K2R", Color::White);
+            let moves = [
+                simple_move((4, 1), (4, 3)),
+                ChessMove::MoveWithTake(
+                    action::Move {
+                        from_position: Position::new(5, 3).unwrap(),
+                        to_position: Position::new(4, 2).unwrap(),
+                    },
+                    action::Take {
+                        position: Position::new(4, 3).unwrap(),
+                    },
+                ),
+                ChessMove::Castle(
+                    action::Move {
+                        from_position: Position::new(4, 0).unwrap(),
+                        to_position: Position::new(6, 0).unwrap(),
+                    },
+                    action::Move {
+                        from_position: Position::new(7, 0).unwrap(),
+                        to_position: Position::new(5, 0).unwrap(),
+                    },
+                ),
+                simple_move((4, 7), (3, 7)),
+                ChessMove::PromoteWithTake(
+                    action::Move {
+                        from_position: Position::new(1, 6).unwrap(),
+                        to_position: Position::new(0, 7).unwrap(),
+                    },
+                    action::Take {
+                        position: Position::new(0, 7).unwrap(),
+                    },
+                    action::Promote {
+                        position: Position::new(0, 7).unwrap(),
+                        piece_type: PieceType::Queen,
+                    },
+                ),
+            ];
+            let mut hashes = vec![state.zobrist()];
+            for chess_move in moves {
+                state.make_move(chess_move).unwrap();
+                assert_eq!(state.zobrist(), crate::zobrist::zobrist_hash(&state));
+                hashes.push(state.zobrist());
+            }
+            while state.undo().is_some() {
+                hashes.pop();
+                assert_eq!(Some(&state.zobrist()), hashes.last());
+            }
+        }
+    }
+
+    mod last_move_san {
+        use super::*;
+
+        #[test]
+        fn scholars_mate() {
+            let mut state = GameState::new();
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            state.make_move(simple_move((4, 6), (4, 4))).unwrap();
+            state.make_move(simple_move((3, 0), (7, 4))).unwrap();
+            assert_eq!(state.last_move_san(), Some("Qh5".to_string()));
+            state.make_move(simple_move((1, 7), (2, 5))).unwrap();
+            state.make_move(simple_move((5, 0), (2, 3))).unwrap();
+            state.make_move(simple_move((6, 7), (5, 5))).unwrap();
+            assert_eq!(state.last_move_san(), Some("Nf6".to_string()));
+            state
+                .make_move(ChessMove::MoveWithTake(
+                    action::Move {
+                        from_position: Position::new(7, 4).unwrap(),
+                        to_position: Position::new(5, 6).unwrap(),
+                    },
+                    action::Take {
+                        position: Position::new(5, 6).unwrap(),
+                    },
+                ))
+                .unwrap();
+            assert_eq!(state.last_move_san(), Some("Qxf7#".to_string()));
+        }
+    }
+
+    mod uci_move_list {
+        use super::*;
+
+        #[test]
+        fn after_e4_e5_nf3() {
+            let mut state = GameState::new();
+            assert!(state.uci_move_list().is_empty());
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            state.make_move(simple_move((4, 6), (4, 4))).unwrap();
+            state.make_move(simple_move((6, 0), (5, 2))).unwrap();
+            assert_eq!(state.uci_move_list(), vec!["e2e4", "e7e5", "g1f3"]);
+            state.undo();
+            assert_eq!(state.uci_move_list(), vec!["e2e4", "e7e5"]);
+        }
+    }
+
+    mod status {
+        use super::*;
+
+        #[test]
+        fn checkmate() {
+            let state = from_fen("7k/6Q1/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::Checkmate(Color::White))
+            );
+        }
+
+        #[test]
+        fn stalemate() {
+            let state = from_fen("7k/5Q2/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::Draw(DrawReason::Stalemate))
+            );
+        }
+
+        #[test]
+        fn threefold_is_claimable() {
+            let mut state = GameState::new();
+            shuffle_knights(&mut state, 1);
+            assert!(!state.can_claim_threefold());
+            assert_eq!(state.claim_draw(), Err(ClaimError::NotClaimable));
+            shuffle_knights(&mut state, 1);
+            assert!(state.can_claim_threefold());
+            assert_eq!(state.status(), GameStatus::InProgress);
+            assert_eq!(state.claim_draw(), Ok(()));
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::Draw(DrawReason::ThreefoldRepetition))
+            );
+            assert_eq!(state.claim_draw(), Err(ClaimError::GameOver));
+            state.undo();
+            assert_eq!(state.status(), GameStatus::InProgress);
+        }
+
+        #[test]
+        fn undo_removes_repetition() {
+            let mut state = GameState::new();
+            shuffle_knights(&mut state, 2);
+            assert!(state.can_claim_threefold());
+            state.undo();
+            assert!(!state.can_claim_threefold());
+            state.make_move(simple_move((5, 5), (6, 7))).unwrap();
+            assert!(state.can_claim_threefold());
+        }
+
+        #[test]
+        fn pawn_move_resets_repetition() {
+            let mut state = GameState::new();
+            shuffle_knights(&mut state, 1);
+            state.make_move(simple_move((4, 1), (4, 2))).unwrap();
+            state.make_move(simple_move((4, 6), (4, 5))).unwrap();
+            shuffle_knights(&mut state, 1);
+            assert!(!state.can_claim_threefold());
+            shuffle_knights(&mut state, 1);
+            assert!(state.can_claim_threefold());
+        }
+
+        #[test]
+        fn fivefold_is_automatic() {
+            let mut state = GameState::new();
+            shuffle_knights(&mut state, 3);
+            assert_eq!(state.status(), GameStatus::InProgress);
+            shuffle_knights(&mut state, 1);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::Draw(DrawReason::FivefoldRepetition))
+            );
+        }
+
+        #[test]
+        fn move_rules() {
+            let mut state = from_fen("4k3/8/8/8/8/8/4P3/R3K3", Color::White);
+            state.make_move(simple_move((0, 0), (0, 1))).unwrap();
+            assert_eq!(state.halfmove_clock(), 1);
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            assert_eq!(state.halfmove_clock(), 2);
+            state.make_move(simple_move((4, 1), (4, 2))).unwrap();
+            assert_eq!(state.halfmove_clock(), 0);
+
+            state.halfmove_clock = 100;
+            assert_eq!(state.status(), GameStatus::InProgress);
+            assert_eq!(state.claim_draw(), Ok(()));
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::Draw(DrawReason::FiftyMoveRule))
+            );
+            state.draw_claim = None;
+            state.halfmove_clock = 150;
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::Draw(DrawReason::SeventyFiveMoveRule))
+            );
+        }
+    }
+
+    mod can_claim_draw {
+        use super::*;
+
+        #[test]
+        fn threefold_repetition() {
+            let mut state = GameState::new();
+            assert!(!state.can_claim_draw());
+            shuffle_knights(&mut state, 1);
+            assert!(!state.can_claim_draw());
+            shuffle_knights(&mut state, 1);
+            assert!(state.can_claim_draw());
+            state.undo();
+            assert!(!state.can_claim_draw());
+        }
+
+        #[test]
+        fn fifty_move_rule() {
+            let mut state = from_fen("4k3/8/8/8/8/8/4P3/R3K3", Color::White);
+            state.halfmove_clock = 99;
+            assert!(!state.can_claim_draw());
+            state.halfmove_clock = 100;
+            assert!(state.can_claim_draw());
+        }
+    }
+
+    mod reversible_move_count {
+        use super::*;
+
+        #[test]
+        fn pawn_move_and_capture() {
+            let mut state = from_fen("4k3/8/8/3p4/8/8/4P3/1N2K3", Color::White);
+            state.make_move(simple_move((1, 0), (2, 2))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            assert_eq!(state.reversible_move_count(), 2);
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            assert_eq!(state.reversible_move_count(), 0);
+            state.make_move(simple_move((4, 6), (4, 7))).unwrap();
+            assert_eq!(state.reversible_move_count(), 1);
+            state
+                .make_move(ChessMove::MoveWithTake(
+                    action::Move {
+                        from_position: Position::new(4, 3).unwrap(),
+                        to_position: Position::new(3, 4).unwrap(),
+                    },
+                    action::Take {
+                        position: Position::new(3, 4).unwrap(),
+                    },
+                ))
+                .unwrap();
+            assert_eq!(state.reversible_move_count(), 0);
+        }
+
+        #[test]
+        fn losing_castling_rights() {
+            let mut state = from_fen("4k3/8/8/8/8/8/8/4K2R", Color::White);
+            state.make_move(simple_move((7, 0), (7, 1))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            // The rook has moved, so castling kingside was lost
+            assert_eq!(state.halfmove_clock(), 2);
+            assert_eq!(state.reversible_move_count(), 1);
+        }
+
+        #[test]
+        fn castle_move() {
+            let mut state = from_fen("4k3/8/8/8/8/8/8/4K2R", Color::White);
+            state
+                .make_move(ChessMove::Castle(
+                    action::Move {
+                        from_position: Position::new(4, 0).unwrap(),
+                        to_position: Position::new(6, 0).unwrap(),
+                    },
+                    action::Move {
+                        from_position: Position::new(7, 0).unwrap(),
+                        to_position: Position::new(5, 0).unwrap(),
+                    },
+                ))
+                .unwrap();
+            assert_eq!(state.halfmove_clock(), 1);
+            assert_eq!(state.reversible_move_count(), 0);
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            assert_eq!(state.reversible_move_count(), 1);
+            state.undo();
+            state.undo();
+            assert_eq!(state.reversible_move_count(), 0);
+        }
+    }
+
+    mod atomic {
+        use super::*;
+
+        #[test]
+        fn capture_explodes_neighbours() {
+            let mut state =
+                from_fen("4k3/8/8/2pnb3/3Q4/8/8/4K3", Color::White).with_variant(Variant::Atomic);
+            let before = state.clone();
+            state.make_move(take((3, 3), (3, 4))).unwrap();
+            // The queen, knight and bishop are gone, but the pawn survives
+            assert!(state
+                .board()
+                .same_position(&Board::from_fen("4k3/8/8/2p5/8/8/8/4K3").unwrap()));
+            assert_eq!(state.zobrist(), crate::zobrist::zobrist_hash(&state));
+            state.undo();
+            assert_eq!(state, before);
+        }
+
+        #[test]
+        fn standard_capture_does_not_explode() {
+            let mut state = from_fen("4k3/8/8/2pnb3/3Q4/8/8/4K3", Color::White);
+            state.make_move(take((3, 3), (3, 4))).unwrap();
+            assert_eq!(state.board().iter_pieces().count(), 5);
+        }
+
+        #[test]
+        fn exploding_king_wins() {
+            let mut state =
+                from_fen("3qk3/8/8/8/8/8/8/3RK3", Color::White).with_variant(Variant::Atomic);
+            assert_eq!(state.validate_move(take((3, 0), (3, 7))), Ok(()));
+            state.make_move(take((3, 0), (3, 7))).unwrap();
+            assert_eq!(state.board().king_position(Color::Black), None);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::KingExploded(Color::White))
+            );
+            assert_eq!(state.game_end(), Some(GameEnd::Checkmate));
+        }
+
+        #[test]
+        fn king_cannot_capture() {
+            let state =
+                from_fen("4k3/8/8/8/8/8/4p3/4K3", Color::White).with_variant(Variant::Atomic);
+            assert!(!state
+                .legal_moves(Position::new(4, 0).unwrap())
+                .unwrap()
+                .contains(&take((4, 0), (4, 1))));
+        }
+
+        #[test]
+        fn own_king_in_blast() {
+            let state =
+                from_fen("4k3/8/8/8/8/8/4p3/4K1N1", Color::White).with_variant(Variant::Atomic);
+            assert_eq!(
+                state.check_move_legal(&take((6, 0), (4, 1))),
+                Err(IllegalMoveReason::LeavesKingInCheck)
+            );
+        }
+
+        #[test]
+        fn touching_kings_cannot_check() {
+            let state =
+                from_fen("8/8/8/8/8/8/4k3/r3K3", Color::White).with_variant(Variant::Atomic);
+            assert_eq!(state.all_legal_moves().len(), 4);
+        }
+
+        #[test]
+        fn explosion_removes_castling_rights() {
+            let mut state =
+                from_fen("4k3/8/8/8/4B3/8/6n1/4K2R", Color::White).with_variant(Variant::Atomic);
+            assert!(state.castling_rights().white_kingside);
+            // The rook is caught in the explosion without ever moving
+            state.make_move(take((4, 3), (6, 1))).unwrap();
+            assert_eq!(state.board()[Position::new(7, 0).unwrap()], None);
+            assert!(!state.castling_rights().white_kingside);
+            assert_eq!(state.zobrist(), crate::zobrist::zobrist_hash(&state));
+        }
+    }
+
+    mod antichess {
+        use super::*;
+
+        fn antichess(placement: &str, turn: Color) -> GameState {
+            from_fen(placement, turn).with_variant(Variant::Antichess)
+        }
+
+        fn only_move(state: &GameState) -> ChessMove {
+            let moves = state.all_legal_moves();
+            assert_eq!(moves.len(), 1, "{moves:?}");
+            moves[0]
+        }
+
+        #[test]
+        fn forced_capture_chain() {
+            let mut state = antichess("7k/8/8/p6r/8/8/8/RK6", Color::White);
+            // White must take the pawn, then black must take the rook
+            let rook_takes = only_move(&state);
+            assert_eq!(rook_takes.taken_position(), Position::new(0, 4).ok());
+            state.make_move(rook_takes).unwrap();
+            let rook_takes_back = only_move(&state);
+            assert_eq!(rook_takes_back.taken_position(), Position::new(0, 4).ok());
+            state.make_move(rook_takes_back).unwrap();
+            // Nothing can be taken, so the king is free to move anywhere
+            assert_eq!(state.all_legal_moves().len(), 5);
+            assert!(state
+                .all_legal_moves()
+                .iter()
+                .all(|chess_move| chess_move.taken_position().is_none()));
+        }
+
+        #[test]
+        fn capture_required() {
+            let state = antichess("4k3/8/8/3p4/4P3/8/8/4K3", Color::White);
+            assert_eq!(
+                state.check_move_legal(&simple_move((4, 3), (4, 4))),
+                Err(IllegalMoveReason::CaptureRequired)
+            );
+            assert_eq!(
+                state.legal_moves(Position::new(4, 0).unwrap()).unwrap(),
+                vec![]
+            );
+        }
+
+        #[test]
+        fn king_can_be_taken() {
+            let state = antichess("4k3/8/8/8/8/8/8/r3K3", Color::Black);
+            let takes_king = only_move(&state);
+            assert_eq!(takes_king.taken_position(), Position::new(4, 0).ok());
+            assert_eq!(state.check_move_legal(&takes_king), Ok(()));
+        }
+
+        #[test]
+        fn no_castling() {
+            let state = antichess("r3k2r/8/8/8/8/8/8/R3K2R", Color::White);
+            assert!(!state
+                .all_legal_moves()
+                .iter()
+                .any(|chess_move| matches!(chess_move, ChessMove::Castle(_, _))));
+            assert_eq!(
+                state.check_move_legal(&ChessMove::Castle(
+                    action::Move {
+                        from_position: Position::new(4, 0).unwrap(),
+                        to_position: Position::new(6, 0).unwrap(),
+                    },
+                    action::Move {
+                        from_position: Position::new(7, 0).unwrap(),
+                        to_position: Position::new(5, 0).unwrap(),
+                    },
+                )),
+                Err(IllegalMoveReason::NotAMoveForThisPiece)
+            );
+        }
+
+        #[test]
+        fn losing_every_piece_wins() {
+            let board = Board::from_pieces([(
+                Position::new(7, 7).unwrap(),
+                Piece::new(Color::Black, PieceType::King),
+            )]);
+            let state = GameState::from_board(board, Color::White).with_variant(Variant::Antichess);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::GivenAway(Color::White))
+            );
+        }
+
+        #[test]
+        fn no_moves_left_wins() {
+            let board = Board::from_pieces([
+                (
+                    Position::new(0, 1).unwrap(),
+                    Piece::new(Color::White, PieceType::Pawn),
+                ),
+                (
+                    Position::new(0, 2).unwrap(),
+                    Piece::new(Color::Black, PieceType::Pawn),
+                ),
+            ]);
+            let state =
+                GameState::from_board(board.clone(), Color::White).with_variant(Variant::Antichess);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::GivenAway(Color::White))
+            );
+            let state = GameState::from_board(board, Color::Black).with_variant(Variant::Antichess);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::GivenAway(Color::Black))
+            );
+        }
+
+        #[test]
+        fn promote_to_king() {
+            let mut state = antichess("4k3/1P6/8/8/8/8/8/4K3", Color::White);
+            let promotion = ChessMove::Promote(
+                action::Move {
+                    from_position: Position::new(1, 6).unwrap(),
+                    to_position: Position::new(1, 7).unwrap(),
+                },
+                action::Promote {
+                    position: Position::new(1, 7).unwrap(),
+                    piece_type: PieceType::King,
+                },
+            );
+            assert_eq!(state.check_move_legal(&promotion), Ok(()));
+            state.make_move(promotion).unwrap();
+            assert_eq!(
+                state.board()[Position::new(1, 7).unwrap()].map(|piece| piece.piece_type),
+                Some(PieceType::King)
+            );
+        }
+    }
+
+    mod crazyhouse {
+        use super::*;
+
+        fn crazyhouse(placement: &str, turn: Color) -> GameState {
+            from_fen(placement, turn).with_variant(Variant::Crazyhouse)
+        }
+
+        fn place(square: (u8, u8), color: Color, piece_type: PieceType) -> ChessMove {
+            ChessMove::Drop(action::Place {
+                position: Position::new(square.0, square.1).unwrap(),
+                color,
+                piece_type,
+            })
+        }
+
+        #[test]
+        fn capture_goes_to_hand() {
+            let mut state = crazyhouse("4k3/8/8/3n4/4P3/8/8/4K3", Color::White);
+            let before = state.clone();
+            state.make_move(take((4, 3), (3, 4))).unwrap();
+            assert_eq!(
+                state.hand(),
+                &HashMap::from([((Color::White, PieceType::Knight), 1)])
+            );
+            assert_eq!(state.zobrist(), crate::zobrist::zobrist_hash(&state));
+            state.undo();
+            assert_eq!(state, before);
+        }
+
+        #[test]
+        fn pawn_drop_resets_halfmove_clock() {
+            let mut state = crazyhouse("4k3/8/8/3p4/4P3/8/8/4K3", Color::White);
+            state.make_move(take((4, 3), (3, 4))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            assert_eq!(state.halfmove_clock(), 1);
+            state
+                .make_move(place((0, 2), Color::White, PieceType::Pawn))
+                .unwrap();
+            assert_eq!(state.halfmove_clock(), 0);
+
+            let mut state = crazyhouse("4k3/8/8/3n4/4P3/8/8/4K3", Color::White);
+            state.make_move(take((4, 3), (3, 4))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            state
+                .make_move(place((0, 2), Color::White, PieceType::Knight))
+                .unwrap();
+            assert_eq!(state.halfmove_clock(), 2);
+        }
+
+        #[test]
+        fn standard_capture_keeps_hand_empty() {
+            let mut state = from_fen("4k3/8/8/3n4/4P3/8/8/4K3", Color::White);
+            state.make_move(take((4, 3), (3, 4))).unwrap();
+            assert!(state.hand().is_empty());
+            assert!(!state
+                .all_legal_moves()
+                .iter()
+                .any(|chess_move| matches!(chess_move, ChessMove::Drop(_))));
+        }
+
+        #[test]
+        fn promoted_piece_demoted() {
+            let mut state = crazyhouse("3rk3/2P5/8/8/8/8/8/4K3", Color::White);
+            state
+                .make_move(ChessMove::PromoteWithTake(
+                    action::Move {
+                        from_position: Position::new(2, 6).unwrap(),
+                        to_position: Position::new(3, 7).unwrap(),
+                    },
+                    action::Take {
+                        position: Position::new(3, 7).unwrap(),
+                    },
+                    action::Promote {
+                        position: Position::new(3, 7).unwrap(),
+                        piece_type: PieceType::Queen,
+                    },
+                ))
+                .unwrap();
+            state.make_move(take((4, 7), (3, 7))).unwrap();
+            assert_eq!(
+                state.hand(),
+                &HashMap::from([
+                    ((Color::White, PieceType::Rook), 1),
+                    ((Color::Black, PieceType::Pawn), 1)
+                ])
+            );
+        }
+
+        #[test]
+        fn drop_moves() {
+            let mut state = crazyhouse("4k3/8/8/8/8/8/3p4/4K3", Color::White);
+            state.make_move(take((4, 0), (3, 1))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            // Pawns can be dropped on every empty square of the middle six ranks
+            let drops: Vec<ChessMove> = state
+                .all_legal_moves()
+                .into_iter()
+                .filter(|chess_move| matches!(chess_move, ChessMove::Drop(_)))
+                .collect();
+            assert_eq!(drops.len(), 46);
+            assert_eq!(
+                state.check_move_legal(&place((0, 7), Color::White, PieceType::Pawn)),
+                Err(IllegalMoveReason::NotAMoveForThisPiece)
+            );
+            assert_eq!(
+                state.check_move_legal(&place((3, 1), Color::White, PieceType::Pawn)),
+                Err(IllegalMoveReason::NotAMoveForThisPiece)
+            );
+            assert_eq!(
+                state.check_move_legal(&place((4, 3), Color::White, PieceType::Knight)),
+                Err(IllegalMoveReason::NotInHand(PieceType::Knight))
+            );
+        }
+
+        #[test]
+        fn drop_and_undo() {
+            let mut state = crazyhouse("4k3/8/8/8/8/8/3p4/4K3", Color::White);
+            state.make_move(take((4, 0), (3, 1))).unwrap();
+            state.make_move(simple_move((4, 7), (4, 6))).unwrap();
+            let before = state.clone();
+            let e4 = place((4, 3), Color::White, PieceType::Pawn);
+            assert_eq!(state.check_move_legal(&e4), Ok(()));
+            state.make_move(e4).unwrap();
+            assert_eq!(
+                state.board()[Position::new(4, 3).unwrap()].map(|piece| piece.piece_type),
+                Some(PieceType::Pawn)
+            );
+            assert!(state.hand().is_empty());
+            assert_eq!(state.zobrist(), crate::zobrist::zobrist_hash(&state));
+            assert_eq!(state.undo(), Some(e4));
+            assert_eq!(state, before);
+            // Nothing left to drop after the pawn is used
+            state.make_move(e4).unwrap();
+            assert!(state.make_move(e4).is_err());
+        }
+
+        #[test]
+        fn drop_blocks_check() {
+            let mut state = crazyhouse("4k3/8/8/3n4/4P3/8/8/r3K3", Color::White);
+            state.make_move(take((4, 3), (3, 4))).unwrap();
+            state.make_move(simple_move((0, 0), (0, 1))).unwrap();
+            state.make_move(simple_move((3, 4), (3, 5))).unwrap();
+            state.make_move(simple_move((0, 1), (0, 0))).unwrap();
+            // The knight can be dropped between the rook and the king
+            assert!(state.all_legal_moves().contains(&place(
+                (2, 0),
+                Color::White,
+                PieceType::Knight
+            )));
+            assert_eq!(
+                state.check_move_legal(&place((4, 3), Color::White, PieceType::Knight)),
+                Err(IllegalMoveReason::LeavesKingInCheck)
+            );
+        }
+    }
+
+    mod promotion_candidates {
+        use super::*;
+
+        #[test]
+        fn match_generated_promotions() {
+            for variant in [Variant::Standard, Variant::Antichess] {
+                let state = from_fen("4k3/1P6/8/8/8/8/8/4K3", Color::White).with_variant(variant);
+                let promotions: Vec<PieceType> = state
+                    .legal_moves(Position::new(1, 6).unwrap())
+                    .unwrap()
+                    .into_iter()
+                    .filter_map(|chess_move| match chess_move {
+                        ChessMove::Promote(_, promote) => Some(promote.piece_type),
+                        _ => None,
+                    })
+                    .collect();
+                assert_eq!(promotions, state.promotion_candidates());
+            }
+        }
+    }
+
+    mod three_check {
+        use super::*;
+
+        fn three_check() -> GameState {
+            from_fen("4k3/8/8/8/8/8/8/R3K3", Color::White).with_variant(Variant::ThreeCheck)
+        }
+
+        /// Plays Ra8+ Ke7 Ra7+ Ke6 Ra6+.
+        fn give_three_checks(state: &mut GameState) {
+            for chess_move in [
+                simple_move((0, 0), (0, 7)),
+                simple_move((4, 7), (4, 6)),
+                simple_move((0, 7), (0, 6)),
+                simple_move((4, 6), (4, 5)),
+                simple_move((0, 6), (0, 5)),
+            ] {
+                state.make_move(chess_move).unwrap();
+            }
+        }
+
+        #[test]
+        fn third_check_wins() {
+            let mut state = three_check();
+            give_three_checks(&mut state);
+            assert_eq!(state.checks_given(Color::White), 3);
+            assert_eq!(state.checks_given(Color::Black), 0);
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::ThreeChecks(Color::White))
+            );
+            state.undo();
+            assert_eq!(state.checks_given(Color::White), 2);
+            assert_eq!(state.status(), GameStatus::InProgress);
+        }
+
+        #[test]
+        fn standard_does_not_count() {
+            let mut state = from_fen("4k3/8/8/8/8/8/8/R3K3", Color::White);
+            give_three_checks(&mut state);
+            assert_eq!(state.checks_given(Color::White), 0);
+            assert_eq!(state.status(), GameStatus::InProgress);
+        }
+
+        #[test]
+        fn undo_and_hash() {
+            let mut state = three_check();
+            let before = state.clone();
+            state.make_move(simple_move((0, 0), (0, 7))).unwrap();
+            assert_eq!(state.zobrist(), crate::zobrist::zobrist_hash(&state));
+            state.undo();
+            assert_eq!(state, before);
+        }
+
+        #[test]
+        fn checks_part_of_repetition() {
+            let mut state = three_check();
+            let start = state.clone();
+            // Ra8+ Kd7 Ra1 Ke8 repeats the position, but white has now given check
+            for chess_move in [
+                simple_move((0, 0), (0, 7)),
+                simple_move((4, 7), (3, 6)),
+                simple_move((0, 7), (0, 0)),
+                simple_move((3, 6), (4, 7)),
+            ] {
+                state.make_move(chess_move).unwrap();
+            }
+            assert!(state.board().same_position(start.board()));
+            assert_ne!(state.zobrist(), start.zobrist());
+        }
+    }
+
+    mod king_of_the_hill {
+        use super::*;
+
+        #[test]
+        fn king_reaches_center() {
+            let mut state =
+                from_fen("4k3/8/8/8/8/4K3/8/8", Color::White).with_variant(Variant::KingOfTheHill);
+            assert_eq!(state.status(), GameStatus::InProgress);
+            state.make_move(simple_move((4, 2), (4, 3))).unwrap();
+            assert_eq!(
+                state.status(),
+                GameStatus::Finished(GameResult::HillReached(Color::White))
+            );
+        }
+
+        #[test]
+        fn standard_continues() {
+            let mut state = from_fen("4k3/8/8/8/8/4K3/8/8", Color::White);
+            state.make_move(simple_move((4, 2), (4, 3))).unwrap();
+            assert_eq!(state.status(), GameStatus::InProgress);
+        }
+
+        #[test]
+        fn moves_unchanged() {
+            let state = from_fen("4k3/8/8/8/8/4K3/8/8", Color::White);
+            assert_eq!(
+                state
+                    .clone()
+                    .with_variant(Variant::KingOfTheHill)
+                    .all_legal_moves()
+                    .len(),
+                state.all_legal_moves().len()
+            );
+        }
+    }
+
+    mod move_count {
+        use super::*;
+
+        #[test]
+        fn after_e4_e5() {
+            let mut state = GameState::new();
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            assert_eq!(state.fullmove_number(), 1);
+            assert_eq!(state.ply_count(), 1);
+            state.make_move(simple_move((4, 6), (4, 4))).unwrap();
+            assert_eq!(state.fullmove_number(), 2);
+            assert_eq!(state.ply_count(), 2);
+        }
+
+        #[test]
+        fn consistent_with_undo() {
+            let mut state = GameState::new();
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            state.make_move(simple_move((4, 6), (4, 4))).unwrap();
+            state.make_move(simple_move((6, 0), (5, 2))).unwrap();
+            state.undo();
+            assert_eq!(state.fullmove_number(), 2);
+            assert_eq!(state.ply_count(), 2);
+            state.undo();
+            assert_eq!(state.fullmove_number(), 1);
+            assert_eq!(state.ply_count(), 1);
+            assert_eq!(state.turn(), Color::Black);
+        }
+
+        #[test]
+        fn failed_move_not_counted() {
+            let mut state = GameState::new();
+            assert!(state.make_move(simple_move((4, 3), (4, 4))).is_err());
+            assert_eq!(state.ply_count(), 0);
+            assert_eq!(state.turn(), Color::White);
+        }
+    }
+}
+
+#[cfg(test)]
+mod game_tests {
+    use super::*;
+
+    fn simple_move(from: (u8, u8), to: (u8, u8)) -> ChessMove {
+        ChessMove::Move(action::Move {
+            from_position: Position::new(from.0, from.1).unwrap(),
+            to_position: Position::new(to.0, to.1).unwrap(),
+        })
+    }
+
+    mod play {
+        use super::*;
+
+        #[test]
+        fn illegal_move_rejected() {
+            let mut game = Game::new();
+            assert_eq!(
+                game.play(simple_move((4, 1), (4, 4))),
+                Err(MoveError::Illegal(IllegalMoveReason::NotAMoveForThisPiece))
+            );
+            assert_eq!(game, Game::new());
+        }
+
+        #[test]
+        fn rejected_after_resignation() {
+            let mut game = Game::new();
+            game.resign(Color::White).unwrap();
+            assert_eq!(
+                game.play(simple_move((4, 1), (4, 3))),
+                Err(MoveError::GameOver)
+            );
+        }
+    }
+
+    mod from_fen {
+        use super::*;
+        use crate::error::BoardError;
+
+        #[test]
+        fn rights_come_from_fen() {
+            let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1").unwrap();
+            assert_eq!(game.state().turn(), Color::White);
+            assert_eq!(game.status(), GameStatus::InProgress);
+            let castles = game
+                .state()
+                .all_legal_moves()
+                .into_iter()
+                .filter(|chess_move| matches!(chess_move, ChessMove::Castle(_, _)))
+                .count();
+            assert_eq!(castles, 1);
+        }
+
+        #[test]
+        fn finished_position() {
+            let game = Game::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+            assert_eq!(
+                game.status(),
+                GameStatus::Finished(GameResult::Checkmate(Color::White))
+            );
+        }
+
+        #[test]
+        fn invalid() {
+            assert_eq!(
+                Game::from_fen("8/8/8/8/8/8/8/8 w - - 0 1"),
+                Err(FenError::IllegalPosition(BoardError::KingCount(
+                    Color::White,
+                    0
+                )))
+            );
+            assert_eq!(
+                Game::from_fen("4k3/8/8/8/8/8/8/4K3"),
+                Err(FenError::FieldCount(1))
+            );
+        }
+    }
+
+    mod from_position {
+        use super::*;
+
+        #[test]
+        fn keeps_state() {
+            let mut state = GameState::new();
+            state.make_move(simple_move((4, 1), (4, 3))).unwrap();
+            let game = Game::from_position(state.clone());
+            assert_eq!(game.state(), &state);
+            assert_eq!(game.state().turn(), Color::Black);
+            assert_eq!(game.status(), GameStatus::InProgress);
+        }
+    }
+
+    mod resign {
+        use super::*;
+
+        #[test]
+        fn opponent_wins() {
+            let mut game = Game::new();
+            game.resign(Color::White).unwrap();
+            assert_eq!(
+                game.status(),
+                GameStatus::Finished(GameResult::Resignation(Color::Black))
+            );
+            assert_eq!(game.resign(Color::Black), Err(GameEndError::GameOver));
+        }
+    }
+
+    mod draw_offer {
+        use super::*;
+
+        #[test]
+        fn accepted() {
+            let mut game = Game::new();
+            game.offer_draw(Color::White).unwrap();
+            assert_eq!(
+                game.accept_draw(Color::White),
+                Err(GameEndError::NoDrawOffer(Color::White))
+            );
+            game.accept_draw(Color::Black).unwrap();
+            assert_eq!(
+                game.status(),
+                GameStatus::Finished(GameResult::Draw(DrawReason::Agreement))
+            );
+            assert_eq!(
+                game.play(simple_move((4, 1), (4, 3))),
+                Err(MoveError::GameOver)
+            );
+        }
+
+        #[test]
+        fn declined_by_moving() {
+            let mut game = Game::new();
+            game.play(simple_move((4, 1), (4, 3))).unwrap();
+            game.offer_draw(Color::White).unwrap();
+            game.play(simple_move((4, 6), (4, 4))).unwrap();
+            assert_eq!(game.draw_offer(), None);
+            assert_eq!(
+                game.accept_draw(Color::Black),
+                Err(GameEndError::NoDrawOffer(Color::Black))
+            );
+        }
+
+        #[test]
+        fn offer_stands_after_own_move() {
+            let mut game = Game::new();
+            game.offer_draw(Color::White).unwrap();
+            game.play(simple_move((4, 1), (4, 3))).unwrap();
+            assert_eq!(game.draw_offer(), Some(Color::White));
+            game.accept_draw(Color::Black).unwrap();
+            assert_eq!(
+                game.status(),
+                GameStatus::Finished(GameResult::Draw(DrawReason::Agreement))
+            );
+        }
+    }
+}
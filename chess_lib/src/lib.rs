@@ -3,4 +3,10 @@
 
 pub mod board;
 pub mod error;
+pub mod game;
+pub mod notation;
+pub mod perft;
 pub mod piece;
+pub mod search;
+pub mod variant;
+pub mod zobrist;
@@ -0,0 +1,567 @@
+use crate::board::{mailbox::Board, ChessMove, Position};
+use crate::error::EpdError;
+use crate::game::{CastlingRights, GameState};
+use crate::piece::{Color, PieceType};
+use log::debug;
+use std::collections::HashMap;
+
+/// Returns the algebraic name of `position`, e.g. "e4".
+pub(crate) fn square_name(position: Position) -> String {
+    format!("{}{}", char::from(b'a' + position.x()), position.y() + 1)
+}
+
+/// Returns the position with the algebraic name `name`, e.g. "e4", or `None` if it is not a square.
+pub(crate) fn parse_square(name: &str) -> Option<Position> {
+    let &[file, rank] = name.as_bytes() else {
+        return None;
+    };
+    Position::new(file.checked_sub(b'a')?, rank.checked_sub(b'1')?).ok()
+}
+
+/// Returns the color given by the side to move field of FEN or EPD, "w" or "b".
+pub(crate) fn parse_turn(field: &str) -> Option<Color> {
+    match field {
+        "w" => Some(Color::White),
+        "b" => Some(Color::Black),
+        _ => None,
+    }
+}
+
+/// Returns the castling rights given by the castling field of FEN or EPD, e.g. `KQkq` or `-`.
+pub(crate) fn parse_castling(field: &str) -> Option<CastlingRights> {
+    let mut castling_rights = CastlingRights {
+        white_kingside: false,
+        white_queenside: false,
+        black_kingside: false,
+        black_queenside: false,
+    };
+    if field == "-" {
+        return Some(castling_rights);
+    }
+    for right in field.chars() {
+        match right {
+            'K' => castling_rights.white_kingside = true,
+            'Q' => castling_rights.white_queenside = true,
+            'k' => castling_rights.black_kingside = true,
+            'q' => castling_rights.black_queenside = true,
+            _ => return None,
+        }
+    }
+    Some(castling_rights)
+}
+
+/// Returns the standard algebraic notation (SAN) of `chess_move`, played by the side to move in `state`.
+///
+/// The piece is only disambiguated by file and/or rank when another legal move of the same piece type reaches the same square.
+/// Pinned pieces cannot legally make the move, so are never disambiguated against.
+/// Check and checkmate are marked with "+" and "#", and drops are written as the piece, "@" and the square, e.g. "N@f3" or "P@e4".
+/// Does not check that the move is legal.
+///
+/// # Parameters
+/// * `state`: The state before the move is played.
+/// * `chess_move`: The move to write.
+///
+/// ```
+/// use chess_lib::{board::{*, action}, game::GameState, notation};
+///
+/// let state = GameState::new();
+/// let nf3 = ChessMove::Move(action::Move {
+///     from_position: Position::new(6, 0).unwrap(),
+///     to_position: Position::new(5, 2).unwrap(),
+/// });
+/// assert_eq!(notation::to_san(&state, &nf3), "Nf3");
+/// ```
+#[must_use]
+pub fn to_san(state: &GameState, chess_move: &ChessMove) -> String {
+    let movement = chess_move.movement();
+    let mut san = if let ChessMove::Castle(king_movement, _) = chess_move {
+        if king_movement.to_position.x() > king_movement.from_position.x() {
+            "O-O".to_string()
+        } else {
+            "O-O-O".to_string()
+        }
+    } else if let ChessMove::Drop(place) = chess_move {
+        format!("{}@{}", place.piece_type, square_name(place.position))
+    } else {
+        let piece_type = chess_move
+            .moving_piece(state.board())
+            .unwrap_or(PieceType::Pawn);
+        let mut san = String::new();
+        if piece_type == PieceType::Pawn {
+            if chess_move.taken_position().is_some() {
+                san.push(char::from(b'a' + movement.from_position.x()));
+            }
+        } else {
+            san.push_str(&piece_type.to_string());
+            san.push_str(&disambiguation(state, chess_move, piece_type));
+        }
+        if chess_move.taken_position().is_some() {
+            san.push('x');
+        }
+        san.push_str(&square_name(movement.to_position));
+        if let ChessMove::Promote(_, promote) | ChessMove::PromoteWithTake(_, _, promote) =
+            chess_move
+        {
+            san.push('=');
+            san.push_str(&promote.piece_type.to_string());
+        }
+        san
+    };
+    let mut after = state.clone();
+    if after.make_move(*chess_move).is_ok() && after.is_check() {
+        san.push(if after.all_legal_moves().is_empty() {
+            '#'
+        } else {
+            '+'
+        });
+    }
+    san
+}
+
+/// Returns `chess_move` in the long algebraic notation used by UCI, e.g. "e2e4" or "e7e8q".
+///
+/// Castling is written as the king's movement and promotions end with the lowercase letter of the piece promoted to.
+/// Drops are written the same as in standard algebraic notation, e.g. "N@f3".
+///
+/// # Parameters
+/// * `chess_move`: The move to write.
+///
+/// ```
+/// use chess_lib::{board::{*, action}, notation};
+///
+/// let e4 = ChessMove::Move(action::Move {
+///     from_position: Position::new(4, 1).unwrap(),
+///     to_position: Position::new(4, 3).unwrap(),
+/// });
+/// assert_eq!(notation::to_uci(&e4), "e2e4");
+/// ```
+#[must_use]
+pub fn to_uci(chess_move: &ChessMove) -> String {
+    if let ChessMove::Drop(place) = chess_move {
+        return format!("{}@{}", place.piece_type, square_name(place.position));
+    }
+    let movement = chess_move.movement();
+    let mut uci = square_name(movement.from_position) + &square_name(movement.to_position);
+    if let ChessMove::Promote(_, promote) | ChessMove::PromoteWithTake(_, _, promote) = chess_move {
+        uci.push_str(&promote.piece_type.to_string().to_lowercase());
+    }
+    uci
+}
+
+/// Returns the legal move for the side to move in `state` written as `san` in standard algebraic notation, or `None` if there is no such move.
+///
+/// Check and checkmate marks and annotations such as "!" or "?" are ignored, and castling may be written with zeros.
+///
+/// # Parameters
+/// * `state`: The state the move is played from.
+/// * `san`: The move in standard algebraic notation.
+///
+/// ```
+/// use chess_lib::{board::*, game::GameState, notation};
+///
+/// let nf3 = notation::parse_san(&GameState::new(), "Nf3").unwrap();
+/// assert_eq!(nf3.movement().to_position, Position::new(5, 2).unwrap());
+/// assert!(notation::parse_san(&GameState::new(), "Nf4").is_none());
+/// ```
+#[must_use]
+pub fn parse_san(state: &GameState, san: &str) -> Option<ChessMove> {
+    let strip = |san: &str| {
+        san.trim()
+            .trim_end_matches(['+', '#', '!', '?'])
+            .replace('0', "O")
+    };
+    let san = strip(san);
+    state
+        .all_legal_moves()
+        .into_iter()
+        .find(|chess_move| strip(&to_san(state, chess_move)) == san)
+}
+
+/// Parses a line of Extended Position Description (EPD), as used by engine test suites.
+///
+/// The first four fields are the piece placement, side to move, castling rights and en passant target square, as in FEN.
+/// They are followed by operations separated by ";", each an opcode and its operand, e.g. `bm Qg6; id "WAC.001";`.
+/// Fields may be separated by any amount of whitespace, and a ";" inside a quoted operand does not end the operation.
+/// Operations are returned keyed on their opcode, with any quotes around the operand removed. A best move given by `bm` can be read with [`parse_san`].
+///
+/// # Parameters
+/// * `line`: The EPD line to parse.
+/// # Errors
+/// * Returns [`EpdError::FieldCount`] if there are fewer than four fields.
+/// * Returns [`EpdError::Placement`] if the piece placement is invalid.
+/// * Returns [`EpdError::InvalidTurn`], [`EpdError::InvalidCastling`] or [`EpdError::InvalidEnPassant`] if the other fields are invalid.
+///
+/// ```
+/// use chess_lib::notation;
+///
+/// let (state, operations) = notation::parse_epd(
+///     r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";"#,
+/// ).unwrap();
+/// assert_eq!(operations["id"], "WAC.001");
+/// assert!(notation::parse_san(&state, &operations["bm"]).is_some());
+/// ```
+pub fn parse_epd(line: &str) -> Result<(GameState, HashMap<String, String>), EpdError> {
+    debug!("Parsing EPD {line}");
+    let mut fields = line.split_whitespace();
+    let mut next_field = |count| fields.next().ok_or(EpdError::FieldCount(count));
+    let placement = next_field(0)?;
+    let turn = next_field(1)?;
+    let castling = next_field(2)?;
+    let en_passant = next_field(3)?;
+    let operations = skip_fields(line, 4);
+
+    let board = Board::from_fen(placement)?;
+    let turn = parse_turn(turn).ok_or_else(|| EpdError::InvalidTurn(turn.to_string()))?;
+    let castling_rights =
+        parse_castling(castling).ok_or_else(|| EpdError::InvalidCastling(castling.to_string()))?;
+    let en_passant = if en_passant == "-" {
+        None
+    } else {
+        Some(
+            parse_square(en_passant)
+                .ok_or_else(|| EpdError::InvalidEnPassant(en_passant.to_string()))?,
+        )
+    };
+
+    let operations = split_operations(operations)
+        .into_iter()
+        .map(str::trim)
+        .filter(|operation| !operation.is_empty())
+        .map(|operation| {
+            let (opcode, operand) = operation
+                .split_once(char::is_whitespace)
+                .unwrap_or((operation, ""));
+            (
+                opcode.to_string(),
+                operand.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Ok((
+        GameState::from_parts(board, turn, castling_rights, en_passant),
+        operations,
+    ))
+}
+
+/// Returns what is left of `text` after its first `count` whitespace separated fields.
+fn skip_fields(text: &str, count: usize) -> &str {
+    let mut rest = text;
+    for _ in 0..count {
+        rest = rest.trim_start();
+        rest = &rest[rest.find(char::is_whitespace).unwrap_or(rest.len())..];
+    }
+    rest
+}
+
+/// Splits the operations of an EPD line on ";", ignoring any ";" inside double quotes.
+fn split_operations(text: &str) -> Vec<&str> {
+    let mut operations = vec![];
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (index, character) in text.char_indices() {
+        match character {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                operations.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    operations.push(&text[start..]);
+    operations
+}
+
+/// Returns the file and/or rank needed to tell `chess_move` apart from other legal moves of the same piece type to the same square.
+fn disambiguation(state: &GameState, chess_move: &ChessMove, piece_type: PieceType) -> String {
+    let movement = chess_move.movement();
+    let others: Vec<Position> = state
+        .all_legal_moves()
+        .iter()
+        .filter(|other| other.moving_piece(state.board()) == Some(piece_type))
+        .map(ChessMove::movement)
+        .filter(|other| {
+            other.to_position == movement.to_position
+                && other.from_position != movement.from_position
+        })
+        .map(|other| other.from_position)
+        .collect();
+    let file = char::from(b'a' + movement.from_position.x()).to_string();
+    let rank = (movement.from_position.y() + 1).to_string();
+    if others.is_empty() {
+        String::new()
+    } else if others
+        .iter()
+        .all(|other| other.x() != movement.from_position.x())
+    {
+        file
+    } else if others
+        .iter()
+        .all(|other| other.y() != movement.from_position.y())
+    {
+        rank
+    } else {
+        file + &rank
+    }
+}
+
+#[cfg(test)]
+mod notation_tests {
+    use super::*;
+    use crate::board::{action, mailbox::Board};
+    use crate::piece::Color;
+
+    fn simple_move(from: (u8, u8), to: (u8, u8)) -> ChessMove {
+        ChessMove::Move(action::Move {
+            from_position: Position::new(from.0, from.1).unwrap(),
+            to_position: Position::new(to.0, to.1).unwrap(),
+        })
+    }
+
+    fn from_fen(placement: &str, turn: Color) -> GameState {
+        GameState::from_board(Board::from_fen(placement).unwrap(), turn)
+    }
+
+    mod to_san {
+        use super::*;
+
+        #[test]
+        fn pawn_moves() {
+            let state = from_fen("4k3/8/8/3p4/4P3/8/8/4K3", Color::White);
+            assert_eq!(to_san(&state, &simple_move((4, 3), (4, 4))), "e5");
+            let take = ChessMove::MoveWithTake(
+                action::Move {
+                    from_position: Position::new(4, 3).unwrap(),
+                    to_position: Position::new(3, 4).unwrap(),
+                },
+                action::Take {
+                    position: Position::new(3, 4).unwrap(),
+                },
+            );
+            assert_eq!(to_san(&state, &take), "exd5");
+        }
+
+        #[test]
+        fn disambiguation() {
+            let state = from_fen("4k3/8/8/8/8/8/8/R4RK1", Color::White);
+            assert_eq!(to_san(&state, &simple_move((0, 0), (3, 0))), "Rad1");
+            let state = from_fen("4k3/8/8/8/R7/8/8/R3K3", Color::White);
+            assert_eq!(to_san(&state, &simple_move((0, 0), (0, 1))), "R1a2");
+            let state = from_fen("k7/8/8/8/8/2Q1Q3/8/2Q1K3", Color::White);
+            assert_eq!(to_san(&state, &simple_move((2, 2), (3, 1))), "Qc3d2");
+        }
+
+        #[test]
+        fn pinned_piece_not_disambiguated() {
+            let state = from_fen("4k3/8/8/b7/8/8/3N3N/4K3", Color::White);
+            assert_eq!(to_san(&state, &simple_move((7, 1), (5, 2))), "Nf3");
+            let state = from_fen("4k3/8/8/8/8/8/3N3N/4K3", Color::White);
+            assert_eq!(to_san(&state, &simple_move((7, 1), (5, 2))), "Nhf3");
+        }
+
+        #[test]
+        fn castling() {
+            let state = from_fen("4k3/8/8/8/8/8/8/R3K2R", Color::White);
+            let castle = ChessMove::Castle(
+                action::Move {
+                    from_position: Position::new(4, 0).unwrap(),
+                    to_position: Position::new(2, 0).unwrap(),
+                },
+                action::Move {
+                    from_position: Position::new(0, 0).unwrap(),
+                    to_position: Position::new(3, 0).unwrap(),
+                },
+            );
+            assert_eq!(to_san(&state, &castle), "O-O-O");
+        }
+
+        #[test]
+        fn promotion_with_check() {
+            let state = from_fen("4k3/1P6/8/8/8/8/8/4K3", Color::White);
+            let promote = ChessMove::Promote(
+                action::Move {
+                    from_position: Position::new(1, 6).unwrap(),
+                    to_position: Position::new(1, 7).unwrap(),
+                },
+                action::Promote {
+                    position: Position::new(1, 7).unwrap(),
+                    piece_type: PieceType::Queen,
+                },
+            );
+            assert_eq!(to_san(&state, &promote), "b8=Q+");
+        }
+
+        #[test]
+        fn checkmate() {
+            let state = from_fen("6k1/5ppp/8/8/8/8/8/R5K1", Color::White);
+            assert_eq!(to_san(&state, &simple_move((0, 0), (0, 7))), "Ra8#");
+        }
+
+        #[test]
+        fn drop() {
+            let state = from_fen("4k3/8/8/8/8/8/8/4K3", Color::White);
+            let knight_drop = ChessMove::Drop(action::Place {
+                position: Position::new(5, 2).unwrap(),
+                color: Color::White,
+                piece_type: PieceType::Knight,
+            });
+            assert_eq!(to_san(&state, &knight_drop), "N@f3");
+            assert_eq!(to_uci(&knight_drop), "N@f3");
+        }
+    }
+
+    mod parse_san {
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let state = from_fen(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                Color::White,
+            );
+            for chess_move in state.all_legal_moves() {
+                assert_eq!(
+                    parse_san(&state, &to_san(&state, &chess_move)),
+                    Some(chess_move)
+                );
+            }
+        }
+
+        #[test]
+        fn marks_and_zeros() {
+            let state = from_fen("4k3/8/8/8/8/8/8/R3K2R", Color::White);
+            assert!(matches!(
+                parse_san(&state, "0-0-0!"),
+                Some(ChessMove::Castle(_, _))
+            ));
+            let state = from_fen("6k1/5ppp/8/8/8/8/8/R5K1", Color::White);
+            assert_eq!(parse_san(&state, "Ra8"), Some(simple_move((0, 0), (0, 7))));
+        }
+
+        #[test]
+        fn ambiguous_or_illegal() {
+            let state = from_fen("4k3/8/8/8/8/8/8/R4RK1", Color::White);
+            assert_eq!(parse_san(&state, "Rd1"), None);
+            assert_eq!(parse_san(&state, "Qd1"), None);
+        }
+    }
+
+    mod parse_epd {
+        use super::*;
+
+        #[test]
+        fn win_at_chess() {
+            let (state, operations) = parse_epd(
+                r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";"#,
+            )
+            .unwrap();
+            assert_eq!(state.turn(), Color::White);
+            assert_eq!(operations.len(), 2);
+            assert_eq!(operations["id"], "WAC.001");
+            assert_eq!(
+                parse_san(&state, &operations["bm"]),
+                Some(simple_move((6, 2), (6, 5)))
+            );
+        }
+
+        #[test]
+        fn castling_and_en_passant() {
+            let (state, operations) =
+                parse_epd("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3").unwrap();
+            assert!(operations.is_empty());
+            assert_eq!(state.turn(), Color::Black);
+            assert_eq!(state.en_passant(), Some(Position::new(4, 2).unwrap()));
+            let castling_rights = state.castling_rights();
+            assert!(castling_rights.white_kingside);
+            assert!(!castling_rights.white_queenside);
+            assert!(!castling_rights.black_kingside);
+            assert!(castling_rights.black_queenside);
+        }
+
+        #[test]
+        fn repeated_whitespace() {
+            let (state, operations) =
+                parse_epd("  4k3/8/8/8/8/8/8/4K3  b \t -   -  bm Kd7;  id  \"spaces\" ;").unwrap();
+            assert_eq!(state.turn(), Color::Black);
+            assert_eq!(operations.len(), 2);
+            assert_eq!(operations["bm"], "Kd7");
+            assert_eq!(operations["id"], "spaces");
+        }
+
+        #[test]
+        fn quoted_semicolon() {
+            let (_, operations) =
+                parse_epd(r#"4k3/8/8/8/8/8/8/4K3 w - - c0 "draw; by agreement"; id "test";"#)
+                    .unwrap();
+            assert_eq!(operations.len(), 2);
+            assert_eq!(operations["c0"], "draw; by agreement");
+            assert_eq!(operations["id"], "test");
+        }
+
+        #[test]
+        fn invalid_fields() {
+            assert_eq!(
+                parse_epd("4k3/8/8/8/8/8/8/4K3 w -").unwrap_err(),
+                EpdError::FieldCount(3)
+            );
+            assert_eq!(
+                parse_epd("4k3/8/8/8/8/8/8/4K3 x - -").unwrap_err(),
+                EpdError::InvalidTurn("x".to_string())
+            );
+            assert_eq!(
+                parse_epd("4k3/8/8/8/8/8/8/4K3 w KX -").unwrap_err(),
+                EpdError::InvalidCastling("KX".to_string())
+            );
+            assert_eq!(
+                parse_epd("4k3/8/8/8/8/8/8/4K3 w - e9").unwrap_err(),
+                EpdError::InvalidEnPassant("e9".to_string())
+            );
+            assert!(matches!(
+                parse_epd("4k3/8/8/8/8/8/8 w - -").unwrap_err(),
+                EpdError::Placement(_)
+            ));
+        }
+    }
+
+    mod to_uci {
+        use super::*;
+
+        #[test]
+        fn simple() {
+            assert_eq!(to_uci(&simple_move((6, 0), (5, 2))), "g1f3");
+        }
+
+        #[test]
+        fn castling() {
+            let castle = ChessMove::Castle(
+                action::Move {
+                    from_position: Position::new(4, 7).unwrap(),
+                    to_position: Position::new(6, 7).unwrap(),
+                },
+                action::Move {
+                    from_position: Position::new(7, 7).unwrap(),
+                    to_position: Position::new(5, 7).unwrap(),
+                },
+            );
+            assert_eq!(to_uci(&castle), "e8g8");
+        }
+
+        #[test]
+        fn promotion() {
+            let promote = ChessMove::PromoteWithTake(
+                action::Move {
+                    from_position: Position::new(1, 1).unwrap(),
+                    to_position: Position::new(0, 0).unwrap(),
+                },
+                action::Take {
+                    position: Position::new(0, 0).unwrap(),
+                },
+                action::Promote {
+                    position: Position::new(0, 0).unwrap(),
+                    piece_type: PieceType::Knight,
+                },
+            );
+            assert_eq!(to_uci(&promote), "b2a1n");
+        }
+    }
+}
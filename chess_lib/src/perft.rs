@@ -0,0 +1,164 @@
+use crate::game::GameState;
+use log::debug;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+/// Counts the positions reached by playing every sequence of `depth` legal moves from `state`.
+///
+/// Comparing these counts with known values is the standard way of testing a move generator.
+///
+/// # Parameters
+/// * `state`: The position to start from.
+/// * `depth`: The number of half moves to play.
+///
+/// ```
+/// use chess_lib::{game::GameState, perft::perft};
+///
+/// assert_eq!(perft(&GameState::new(), 2), 400);
+/// ```
+#[must_use]
+pub fn perft(state: &GameState, depth: u32) -> u64 {
+    let nodes = perft_from(&mut state.clone(), depth);
+    debug!("Perft {depth} found {nodes} nodes");
+    nodes
+}
+
+/// Same as [`perft`], but caches the count for each position in `table` so transpositions are only counted once.
+///
+/// Entries are keyed on the Zobrist hash of the position, see [`GameState::zobrist`], and store the depth the count was made at along with the count.
+/// A count is only reused when it was made at the same depth, since counts at different depths are unrelated.
+/// The table can be reused between calls to share counts, and a different position with the same hash would give a wrong count, though this is very unlikely.
+///
+/// # Parameters
+/// * `state`: The position to start from.
+/// * `depth`: The number of half moves to play.
+/// * `table`: Cached counts, keyed on hash, with the depth and count as the value.
+///
+/// ```
+/// use chess_lib::{game::GameState, perft::perft_hashed};
+/// use std::collections::HashMap;
+///
+/// let mut table = HashMap::new();
+/// assert_eq!(perft_hashed(&GameState::new(), 3, &mut table), 8902);
+/// ```
+#[must_use]
+pub fn perft_hashed<S: BuildHasher>(
+    state: &GameState,
+    depth: u32,
+    table: &mut HashMap<u64, (u32, u64), S>,
+) -> u64 {
+    let nodes = perft_hashed_from(&mut state.clone(), depth, table);
+    debug!(
+        "Hashed perft {depth} found {nodes} nodes with {} cached positions",
+        table.len()
+    );
+    nodes
+}
+
+fn perft_hashed_from<S: BuildHasher>(
+    state: &mut GameState,
+    depth: u32,
+    table: &mut HashMap<u64, (u32, u64), S>,
+) -> u64 {
+    if depth <= 1 {
+        return perft_from(state, depth);
+    }
+    if let Some(&(cached_depth, nodes)) = table.get(&state.zobrist()) {
+        if cached_depth == depth {
+            return nodes;
+        }
+    }
+    let mut nodes = 0;
+    for chess_move in state.all_legal_moves() {
+        if state.make_move(chess_move).is_ok() {
+            nodes += perft_hashed_from(state, depth - 1, table);
+            state.undo();
+        }
+    }
+    table.insert(state.zobrist(), (depth, nodes));
+    nodes
+}
+
+fn perft_from(state: &mut GameState, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if depth == 1 {
+        return state.legal_move_count() as u64;
+    }
+    let mut nodes = 0;
+    for chess_move in state.all_legal_moves() {
+        if state.make_move(chess_move).is_ok() {
+            nodes += perft_from(state, depth - 1);
+            state.undo();
+        }
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod perft_tests {
+    use super::*;
+    use crate::board::mailbox::Board;
+    use crate::piece::Color;
+
+    fn from_fen(placement: &str, turn: Color) -> GameState {
+        GameState::from_board(Board::from_fen(placement).unwrap(), turn)
+    }
+
+    mod perft {
+        use super::*;
+
+        #[test]
+        fn start_position() {
+            let state = GameState::new();
+            assert_eq!(perft(&state, 0), 1);
+            assert_eq!(perft(&state, 1), 20);
+            assert_eq!(perft(&state, 2), 400);
+            assert_eq!(perft(&state, 3), 8902);
+        }
+
+        #[test]
+        fn kiwipete() {
+            let state = from_fen(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                Color::White,
+            );
+            assert_eq!(perft(&state, 1), 48);
+            assert_eq!(perft(&state, 2), 2039);
+        }
+
+        #[test]
+        fn en_passant_and_pins() {
+            let state = from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8", Color::White);
+            assert_eq!(perft(&state, 1), 14);
+            assert_eq!(perft(&state, 2), 191);
+            assert_eq!(perft(&state, 3), 2812);
+        }
+    }
+
+    mod perft_hashed {
+        use super::*;
+
+        #[test]
+        fn matches_perft() {
+            let state = from_fen(
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                Color::White,
+            );
+            let mut table = HashMap::new();
+            assert_eq!(perft_hashed(&state, 3, &mut table), perft(&state, 3));
+            assert!(!table.is_empty());
+        }
+
+        #[test]
+        fn shared_table() {
+            let state = GameState::new();
+            let mut table = HashMap::new();
+            assert_eq!(perft_hashed(&state, 4, &mut table), 197_281);
+            // Counts cached at depth 4 must not be reused at other depths
+            assert_eq!(perft_hashed(&state, 3, &mut table), 8902);
+            assert_eq!(perft_hashed(&state, 4, &mut table), 197_281);
+        }
+    }
+}
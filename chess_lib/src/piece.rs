@@ -1,12 +1,65 @@
+use crate::board::Offset;
 use std::fmt::Display;
 
 /// Chess piece colors.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+///
+/// Defaults to white, the side to move first.
+///
+/// ```
+/// use chess_lib::piece::Color;
+///
+/// assert_eq!(Color::default(), Color::White);
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
 pub enum Color {
+    #[default]
     White = 1,
     Black = -1,
 }
 
+impl Color {
+    /// Returns the opposing color.
+    ///
+    /// ```
+    /// use chess_lib::piece::Color;
+    ///
+    /// assert_eq!(Color::White.opposite(), Color::Black);
+    /// assert_eq!(Color::Black.opposite(), Color::White);
+    /// ```
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+
+    /// Returns the index of this color in arrays with an entry for each color, white first.
+    #[must_use]
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Color::White => 0,
+            Color::Black => 1,
+        }
+    }
+
+    /// Returns the offset of one square in the direction this color's pawns advance.
+    ///
+    /// ```
+    /// use chess_lib::{board::Offset, piece::Color};
+    ///
+    /// assert_eq!(Color::White.forward_offset(), Offset::new(0, 1).unwrap());
+    /// assert_eq!(Color::Black.forward_offset(), Offset::new(0, -1).unwrap());
+    /// ```
+    #[must_use]
+    pub fn forward_offset(self) -> Offset {
+        match self {
+            Color::White => Offset { x: 0, y: 1 },
+            Color::Black => Offset { x: 0, y: -1 },
+        }
+    }
+}
+
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -21,7 +74,7 @@ impl Display for Color {
 }
 
 /// Piece types.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
 #[allow(clippy::module_name_repetitions)]
 pub enum PieceType {
     Pawn,
@@ -32,6 +85,29 @@ pub enum PieceType {
     King,
 }
 
+impl PieceType {
+    /// Conventional material value of the piece type in centipawns.
+    ///
+    /// The king is given a value larger than all other material combined, so it always compares as the most valuable piece.
+    ///
+    /// ```
+    /// use chess_lib::piece::PieceType;
+    ///
+    /// assert_eq!(PieceType::Pawn.value(), 100);
+    /// assert!(PieceType::Queen.value() < PieceType::King.value());
+    /// ```
+    #[must_use]
+    pub fn value(self) -> i32 {
+        match self {
+            PieceType::Pawn => 100,
+            PieceType::Knight | PieceType::Bishop => 300,
+            PieceType::Rook => 500,
+            PieceType::Queen => 900,
+            PieceType::King => 10_000,
+        }
+    }
+}
+
 impl Display for PieceType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -50,7 +126,20 @@ impl Display for PieceType {
 }
 
 /// Chess piece.
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+///
+/// Pieces, colors and piece types can all be used as hash map keys, e.g. for tables keyed on the piece.
+///
+/// ```
+/// use chess_lib::piece::*;
+/// use std::collections::HashMap;
+///
+/// let mut counts = HashMap::new();
+/// for piece_type in [PieceType::Pawn, PieceType::Pawn, PieceType::Knight] {
+///     *counts.entry(Piece::new(Color::White, piece_type)).or_insert(0) += 1;
+/// }
+/// assert_eq!(counts[&Piece::new(Color::White, PieceType::Pawn)], 2);
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
 pub struct Piece {
     pub color: Color,
     pub piece_type: PieceType,
@@ -69,6 +158,98 @@ impl Piece {
             moved: false,
         }
     }
+
+    /// Creates an unmoved pawn of `color`, short for [`Piece::new`] with [`PieceType::Pawn`].
+    ///
+    /// ```
+    /// use chess_lib::piece::*;
+    ///
+    /// assert_eq!(Piece::pawn(Color::Black), Piece::new(Color::Black, PieceType::Pawn));
+    /// ```
+    #[must_use]
+    pub fn pawn(color: Color) -> Self {
+        Self::new(color, PieceType::Pawn)
+    }
+
+    /// Creates an unmoved piece from its FEN character.
+    ///
+    /// Uppercase letters are white pieces and lowercase letters are black pieces. Returns `None` if the character does not represent a piece.
+    ///
+    /// ```
+    /// use chess_lib::piece::*;
+    ///
+    /// assert_eq!(Piece::from_fen_char('N'), Some(Piece::new(Color::White, PieceType::Knight)));
+    /// assert_eq!(Piece::from_fen_char('q'), Some(Piece::new(Color::Black, PieceType::Queen)));
+    /// assert_eq!(Piece::from_fen_char('x'), None);
+    /// ```
+    #[must_use]
+    pub fn from_fen_char(c: char) -> Option<Self> {
+        let color = if c.is_ascii_uppercase() {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let piece_type = match c.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'q' => PieceType::Queen,
+            'k' => PieceType::King,
+            _ => return None,
+        };
+        Some(Self::new(color, piece_type))
+    }
+
+    /// Returns the Unicode chess symbol of the piece, e.g. '♘' for a white knight.
+    ///
+    /// ```
+    /// use chess_lib::piece::*;
+    ///
+    /// assert_eq!(Piece::new(Color::White, PieceType::Knight).to_unicode(), '♘');
+    /// assert_eq!(Piece::new(Color::Black, PieceType::Queen).to_unicode(), '♛');
+    /// ```
+    #[must_use]
+    pub fn to_unicode(self) -> char {
+        match (self.color, self.piece_type) {
+            (Color::White, PieceType::Pawn) => '♙',
+            (Color::White, PieceType::Knight) => '♘',
+            (Color::White, PieceType::Bishop) => '♗',
+            (Color::White, PieceType::Rook) => '♖',
+            (Color::White, PieceType::Queen) => '♕',
+            (Color::White, PieceType::King) => '♔',
+            (Color::Black, PieceType::Pawn) => '♟',
+            (Color::Black, PieceType::Knight) => '♞',
+            (Color::Black, PieceType::Bishop) => '♝',
+            (Color::Black, PieceType::Rook) => '♜',
+            (Color::Black, PieceType::Queen) => '♛',
+            (Color::Black, PieceType::King) => '♚',
+        }
+    }
+
+    /// Returns the FEN character of the piece, uppercase for white and lowercase for black.
+    ///
+    /// ```
+    /// use chess_lib::piece::*;
+    ///
+    /// assert_eq!(Piece::new(Color::White, PieceType::Knight).to_fen_char(), 'N');
+    /// assert_eq!(Piece::new(Color::Black, PieceType::Queen).to_fen_char(), 'q');
+    /// ```
+    #[must_use]
+    pub fn to_fen_char(self) -> char {
+        let c = match self.piece_type {
+            PieceType::Pawn => 'P',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Rook => 'R',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        };
+        match self.color {
+            Color::White => c,
+            Color::Black => c.to_ascii_lowercase(),
+        }
+    }
 }
 
 impl Display for Piece {
@@ -0,0 +1,704 @@
+use crate::board::{mailbox::Board, ChessMove};
+use crate::game::GameState;
+use crate::piece::Color;
+use log::{debug, trace};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::cmp::Reverse;
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Score given to delivering checkmate, the side that is mated scores the negation.
+///
+/// Mates found closer to the root are scored slightly further from zero, so the search prefers the quickest mate.
+pub const MATE_SCORE: i32 = 100_000;
+
+/// Bound larger than any score the search can return.
+pub(crate) const INFINITY: i32 = 1_000_000;
+
+/// Options controlling how the search is run.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct SearchOptions {
+    /// The number of half moves to search, at least 1.
+    pub depth: u32,
+    /// How much the engine dislikes draws, in centipawns.
+    ///
+    /// Drawn positions score `-contempt` for the side the engine is playing, so a positive contempt makes the engine avoid draws and a negative contempt makes it seek them.
+    /// Stalemate, repeating a position and reaching the fifty move rule are all scored as draws.
+    pub contempt: i32,
+}
+
+impl SearchOptions {
+    /// Creates search options with the given depth and no contempt.
+    #[must_use]
+    pub fn new(depth: u32) -> Self {
+        Self { depth, contempt: 0 }
+    }
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+/// The outcome of a search.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct SearchResult {
+    /// The best move for the side to move.
+    pub best_move: ChessMove,
+    /// The score of the best move for the side to move, in centipawns.
+    pub score: i32,
+    /// The principal variation, the line of play expected from both sides, starting with the best move.
+    ///
+    /// May be shorter than the search depth if the game ends or the search stops early down the line.
+    pub pv: Vec<ChessMove>,
+}
+
+/// State shared by every node of a single search.
+struct SearchContext {
+    /// The color the engine is playing, i.e. the side to move at the root.
+    engine: Color,
+    contempt: i32,
+}
+
+impl SearchContext {
+    /// Returns the score of a drawn position with `turn` to move.
+    fn draw_score(&self, turn: Color) -> i32 {
+        if turn == self.engine {
+            -self.contempt
+        } else {
+            self.contempt
+        }
+    }
+}
+
+/// Statically evaluates the position from the perspective of the side to move.
+///
+/// The evaluation is the material and piece placement of the side to move minus that of the opponent, in centipawns, see [`Board::evaluate`](crate::board::mailbox::Board::evaluate).
+///
+/// ```
+/// use chess_lib::{game::GameState, search};
+///
+/// assert_eq!(search::evaluate(&GameState::new()), 0);
+/// ```
+#[must_use]
+pub fn evaluate(state: &GameState) -> i32 {
+    state.board().evaluate(state.turn())
+}
+
+/// Sorts `moves` so captures come first, ordered by most valuable victim then least valuable attacker (MVV-LVA).
+///
+/// Searching likely good captures first lets alpha-beta pruning cut off more of the tree. Quiet moves are left after the captures in their original order.
+/// Values are taken from [`PieceType::value`](crate::piece::PieceType::value).
+///
+/// # Parameters
+/// * `board`: The board the moves are to be made on, before any of them are made.
+/// * `moves`: The moves to sort.
+///
+/// ```
+/// use chess_lib::{board::mailbox::Board, game::GameState, piece::Color, search};
+///
+/// let board = Board::from_fen("4k3/8/8/3q4/4P3/8/8/4K3").unwrap();
+/// let state = GameState::from_board(board, Color::White);
+/// let mut moves = state.all_legal_moves();
+/// search::order_moves(state.board(), &mut moves);
+/// assert!(moves[0].taken_position().is_some());
+/// ```
+pub fn order_moves(board: &Board, moves: &mut [ChessMove]) {
+    moves.sort_by_cached_key(|chess_move| {
+        let Some(victim) = chess_move
+            .taken_position()
+            .and_then(|position| board[position])
+        else {
+            return (Reverse(0), 0);
+        };
+        let attacker = board[chess_move.movement().from_position]
+            .map_or(0, |attacker| attacker.piece_type.value());
+        (Reverse(victim.piece_type.value()), attacker)
+    });
+}
+
+/// Searches `depth` half moves ahead, returning the score of the position for the side to move.
+///
+/// Uses negamax with alpha-beta pruning. Only scores strictly between `alpha` and `beta` are exact, scores outside the window are bounds.
+/// The state is returned to how it started once the search is complete.
+///
+/// # Parameters
+/// * `state`: The position to search.
+/// * `depth`: The number of half moves to search.
+/// * `alpha`: The score the side to move is already guaranteed.
+/// * `beta`: The score the opponent is already guaranteed, negated.
+pub fn negamax(state: &mut GameState, depth: u32, alpha: i32, beta: i32) -> i32 {
+    let context = SearchContext {
+        engine: state.turn(),
+        contempt: 0,
+    };
+    negamax_with(state, depth, alpha, beta, &context, &mut vec![])
+}
+
+fn negamax_with(
+    state: &mut GameState,
+    depth: u32,
+    mut alpha: i32,
+    beta: i32,
+    context: &SearchContext,
+    pv: &mut Vec<ChessMove>,
+) -> i32 {
+    pv.clear();
+    // A position repeated once is treated as a draw, since whoever wanted it repeated can keep repeating it
+    if state.repetition_count() >= 2 || state.halfmove_clock() >= 100 {
+        return context.draw_score(state.turn());
+    }
+    if depth == 0 {
+        return quiescence(state, alpha, beta);
+    }
+    let mut moves = state.all_legal_moves();
+    if moves.is_empty() {
+        return if state.is_check() {
+            -MATE_SCORE - i32::try_from(depth).unwrap_or(0)
+        } else {
+            context.draw_score(state.turn())
+        };
+    }
+    order_moves(state.board(), &mut moves);
+    let mut child_pv = vec![];
+    for chess_move in moves {
+        if state.make_move(chess_move).is_err() {
+            continue;
+        }
+        let score = -negamax_with(state, depth - 1, -beta, -alpha, context, &mut child_pv);
+        state.undo();
+        if score >= beta {
+            trace!("Beta cutoff at depth {depth} with score {score}");
+            return beta;
+        }
+        if score > alpha {
+            alpha = score;
+            pv.clear();
+            pv.push(chess_move);
+            pv.append(&mut child_pv);
+        }
+    }
+    alpha
+}
+
+/// Searches captures only until the position is quiet, returning the score of the position for the side to move.
+///
+/// Used at the end of the main search so it does not stop in the middle of an exchange, e.g. just after taking a defended piece.
+/// The side to move may also choose not to capture, so the static evaluation is a lower bound on the score.
+/// Captures that lose material according to [`Board::see`] are not searched, apart from promotions.
+/// Checkmate and stalemate are not detected. The state is returned to how it started once the search is complete.
+///
+/// # Parameters
+/// * `state`: The position to search.
+/// * `alpha`: The score the side to move is already guaranteed.
+/// * `beta`: The score the opponent is already guaranteed, negated.
+pub fn quiescence(state: &mut GameState, mut alpha: i32, beta: i32) -> i32 {
+    let stand_pat = evaluate(state);
+    if stand_pat >= beta {
+        return beta;
+    }
+    alpha = alpha.max(stand_pat);
+    let mut captures = state.capture_moves();
+    captures.retain(|chess_move| {
+        matches!(chess_move, ChessMove::PromoteWithTake(_, _, _))
+            || chess_move
+                .taken_position()
+                .is_some_and(|target| state.board().see(target, state.turn()) >= 0)
+    });
+    order_moves(state.board(), &mut captures);
+    for chess_move in captures {
+        if state.make_move(chess_move).is_err() {
+            continue;
+        }
+        let score = -quiescence(state, -beta, -alpha);
+        state.undo();
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// Returns the best move for the side to move, searching `depth` half moves ahead.
+///
+/// Returns `None` if the side to move has no legal moves.
+///
+/// # Parameters
+/// * `state`: The position to search.
+/// * `depth`: The number of half moves to search, at least 1.
+#[must_use]
+pub fn best_move(state: &GameState, depth: u32) -> Option<ChessMove> {
+    search(state, &SearchOptions::new(depth)).map(|result| result.best_move)
+}
+
+/// Returns the best move for the side to move along with its score and principal variation, searching with the given options.
+///
+/// Returns `None` if the side to move has no legal moves.
+///
+/// # Parameters
+/// * `state`: The position to search.
+/// * `options`: The depth and other parameters of the search.
+///
+/// ```
+/// use chess_lib::{game::GameState, search::{self, SearchOptions}};
+///
+/// let options = SearchOptions { depth: 2, contempt: 50 };
+/// let result = search::search(&GameState::new(), &options).unwrap();
+/// assert_eq!(result.pv[0], result.best_move);
+/// ```
+#[must_use]
+pub fn search(state: &GameState, options: &SearchOptions) -> Option<SearchResult> {
+    search_root(state, *options, None)
+}
+
+/// Returns the best move for the side to move, searching up to `max_depth` half moves ahead with iterative deepening.
+///
+/// Searches at depth 1, then 2, and so on up to `max_depth`, trying the best move from the previous depth first so more of the tree is pruned.
+/// The best move from the deepest completed search is returned. Returns `None` if the side to move has no legal moves or `max_depth` is 0.
+///
+/// # Parameters
+/// * `state`: The position to search.
+/// * `max_depth`: The deepest search to run.
+///
+/// ```
+/// use chess_lib::{game::GameState, search};
+///
+/// assert!(search::best_move_timed(&GameState::new(), 2).is_some());
+/// ```
+#[must_use]
+pub fn best_move_timed(state: &GameState, max_depth: u32) -> Option<ChessMove> {
+    let mut best = None;
+    for depth in 1..=max_depth {
+        best = search_root(state, SearchOptions::new(depth), best).map(|result| result.best_move);
+        debug!("Iterative deepening finished depth {depth} with {best:?}");
+    }
+    best
+}
+
+/// Same as [`best_move`], but the moves at the root are searched in parallel on the rayon thread pool.
+///
+/// Each move is searched on its own copy of `state`. The best score found so far is shared between threads and used as the bound for moves searched afterwards, so less of the tree is pruned than in a single threaded search.
+/// Moves are searched just below the shared bound, so a move that is as good as the best is scored exactly, and the first of equally good moves in search order is returned, the same move as [`best_move`].
+/// Only available with the `parallel` feature.
+///
+/// # Parameters
+/// * `state`: The position to search.
+/// * `depth`: The number of half moves to search, at least 1.
+///
+/// ```
+/// use chess_lib::{game::GameState, search};
+///
+/// let state = GameState::new();
+/// assert_eq!(search::best_move_parallel(&state, 2), search::best_move(&state, 2));
+/// ```
+#[cfg(feature = "parallel")]
+#[must_use]
+pub fn best_move_parallel(state: &GameState, depth: u32) -> Option<ChessMove> {
+    let context = SearchContext {
+        engine: state.turn(),
+        contempt: 0,
+    };
+    let mut moves = state.all_legal_moves();
+    order_moves(state.board(), &mut moves);
+    let best_score = AtomicI32::new(-INFINITY);
+    let best = moves
+        .par_iter()
+        .enumerate()
+        .filter_map(|(index, &chess_move)| {
+            let mut state = state.clone();
+            state.make_move(chess_move).ok()?;
+            let alpha = best_score.load(Ordering::Relaxed) - 1;
+            let score = -negamax_with(
+                &mut state,
+                depth.saturating_sub(1),
+                -INFINITY,
+                -alpha,
+                &context,
+                &mut vec![],
+            );
+            best_score.fetch_max(score, Ordering::Relaxed);
+            Some((score, Reverse(index), chess_move))
+        })
+        .max_by_key(|&(score, index, _)| (score, index))
+        .map(|(_, _, chess_move)| chess_move);
+    debug!("Best parallel move at depth {depth} is {best:?}");
+    best
+}
+
+/// Searches every legal move at the root, trying `first` before the others if it is one of them.
+fn search_root(
+    state: &GameState,
+    options: SearchOptions,
+    first: Option<ChessMove>,
+) -> Option<SearchResult> {
+    let mut state = state.clone();
+    let context = SearchContext {
+        engine: state.turn(),
+        contempt: options.contempt,
+    };
+    let mut moves = state.all_legal_moves();
+    order_moves(state.board(), &mut moves);
+    if let Some(index) = moves
+        .iter()
+        .position(|chess_move| Some(*chess_move) == first)
+    {
+        moves.swap(0, index);
+    }
+    let mut best: Option<SearchResult> = None;
+    let mut child_pv = vec![];
+    for chess_move in moves {
+        if state.make_move(chess_move).is_err() {
+            continue;
+        }
+        let alpha = best.as_ref().map_or(-INFINITY, |best| best.score);
+        let score = -negamax_with(
+            &mut state,
+            options.depth.saturating_sub(1),
+            -INFINITY,
+            -alpha,
+            &context,
+            &mut child_pv,
+        );
+        state.undo();
+        if best.is_none() || score > alpha {
+            let mut pv = vec![chess_move];
+            pv.append(&mut child_pv);
+            best = Some(SearchResult {
+                best_move: chess_move,
+                score,
+                pv,
+            });
+        }
+    }
+    debug!("Best result with {options:?} is {best:?}");
+    best
+}
+
+#[cfg(test)]
+mod search_tests {
+    use super::*;
+    use crate::piece::Color;
+
+    fn from_fen(placement: &str, turn: Color) -> GameState {
+        GameState::from_board(Board::from_fen(placement).unwrap(), turn)
+    }
+
+    mod evaluate {
+        use super::*;
+
+        #[test]
+        fn material_advantage() {
+            let state = from_fen("4k3/8/8/8/8/8/8/3QK3", Color::White);
+            // A queen is worth 900, less 5 for standing on d1
+            assert_eq!(evaluate(&state), 895);
+            let state = from_fen("4k3/8/8/8/8/8/8/3QK3", Color::Black);
+            assert_eq!(evaluate(&state), -895);
+        }
+
+        #[test]
+        fn color_symmetry() {
+            let board = Board::from_fen("r3k3/pp3p2/8/3N4/8/8/PPP5/4K2R").unwrap();
+            let white = GameState::from_board(board.clone(), Color::White);
+            let black = GameState::from_board(board.color_flipped(), Color::Black);
+            assert_eq!(evaluate(&white), evaluate(&black));
+        }
+    }
+
+    mod order_moves {
+        use super::*;
+
+        fn index_of(moves: &[ChessMove], from: (u8, u8), to: (u8, u8)) -> usize {
+            moves
+                .iter()
+                .position(|chess_move| {
+                    let movement = chess_move.movement();
+                    movement.from_position == crate::board::Position::new(from.0, from.1).unwrap()
+                        && movement.to_position == crate::board::Position::new(to.0, to.1).unwrap()
+                })
+                .unwrap()
+        }
+
+        #[test]
+        fn victim_before_attacker() {
+            let state = from_fen("4k3/p7/8/3q4/4P3/8/8/Q3K3", Color::White);
+            let mut moves = state.all_legal_moves();
+            order_moves(state.board(), &mut moves);
+            let pawn_takes_queen = index_of(&moves, (4, 3), (3, 4));
+            let queen_takes_pawn = index_of(&moves, (0, 0), (0, 6));
+            assert_eq!(pawn_takes_queen, 0);
+            assert!(pawn_takes_queen < queen_takes_pawn);
+        }
+
+        #[test]
+        fn least_valuable_attacker_first() {
+            let state = from_fen("4k3/8/8/3r4/4P3/8/8/3RK3", Color::White);
+            let mut moves = state.all_legal_moves();
+            order_moves(state.board(), &mut moves);
+            assert!(index_of(&moves, (4, 3), (3, 4)) < index_of(&moves, (3, 0), (3, 4)));
+        }
+
+        #[test]
+        fn quiet_moves_after_captures() {
+            let state = from_fen("4k3/p7/8/3q4/4P3/8/8/Q3K3", Color::White);
+            let mut moves = state.all_legal_moves();
+            let quiet: Vec<ChessMove> = moves
+                .iter()
+                .copied()
+                .filter(|chess_move| chess_move.taken_position().is_none())
+                .collect();
+            order_moves(state.board(), &mut moves);
+            let captures = moves.len() - quiet.len();
+            assert!(moves[..captures]
+                .iter()
+                .all(|chess_move| chess_move.taken_position().is_some()));
+            assert_eq!(moves[captures..], quiet);
+        }
+    }
+
+    mod negamax {
+        use super::*;
+
+        #[test]
+        fn stalemate() {
+            let mut state = from_fen("7k/5Q2/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(negamax(&mut state, 2, -INFINITY, INFINITY), 0);
+        }
+
+        #[test]
+        fn checkmated() {
+            let mut state = from_fen("7k/6Q1/6K1/8/8/8/8/8", Color::Black);
+            assert!(negamax(&mut state, 1, -INFINITY, INFINITY) <= -MATE_SCORE);
+        }
+
+        #[test]
+        fn restores_state() {
+            let mut state = GameState::new();
+            negamax(&mut state, 2, -INFINITY, INFINITY);
+            assert_eq!(state, GameState::new());
+        }
+    }
+
+    mod quiescence {
+        use super::*;
+
+        #[test]
+        fn sees_recapture() {
+            // White has just taken a pawn defended by another pawn with the queen
+            let mut state = from_fen("4k3/8/4p3/3Q4/8/8/8/4K3", Color::Black);
+            assert!(evaluate(&state) < -500);
+            assert!(quiescence(&mut state, -INFINITY, INFINITY) > 0);
+        }
+
+        #[test]
+        fn avoids_horizon_blunder() {
+            let state = from_fen("4k3/8/4p3/3p4/8/8/3Q4/4K3", Color::White);
+            let chess_move = best_move(&state, 1).unwrap();
+            assert_ne!(
+                chess_move.movement().to_position,
+                crate::board::Position::new(3, 4).unwrap()
+            );
+        }
+
+        #[test]
+        fn skips_losing_captures() {
+            // Taking the defended pawn loses the queen, so standing pat is best
+            let mut state = from_fen("4k3/8/4p3/3p4/8/8/3Q4/4K3", Color::White);
+            let stand_pat = evaluate(&state);
+            assert_eq!(quiescence(&mut state, -INFINITY, INFINITY), stand_pat);
+        }
+
+        #[test]
+        fn restores_state() {
+            let mut state = from_fen("4k3/8/4p3/3p4/8/8/3Q4/4K3", Color::White);
+            let before = state.clone();
+            quiescence(&mut state, -INFINITY, INFINITY);
+            assert_eq!(state, before);
+        }
+    }
+
+    mod best_move {
+        use super::*;
+
+        #[test]
+        fn mate_in_one() {
+            let state = from_fen("6k1/5ppp/8/8/8/8/8/R5K1", Color::White);
+            let chess_move = best_move(&state, 2).unwrap();
+            assert_eq!(
+                chess_move.movement().to_position,
+                crate::board::Position::new(0, 7).unwrap()
+            );
+        }
+
+        #[test]
+        fn no_moves() {
+            let state = from_fen("7k/6Q1/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(best_move(&state, 2), None);
+        }
+    }
+
+    mod search {
+        use super::*;
+        use crate::game::{GameResult, GameStatus};
+
+        #[test]
+        fn mate_in_two_pv() {
+            let state = from_fen("6k1/8/8/8/8/8/1R6/R5K1", Color::White);
+            let result = search(&state, &SearchOptions::new(4)).unwrap();
+            assert!(result.score >= MATE_SCORE);
+            assert_eq!(result.pv.len(), 3);
+            assert_eq!(result.pv[0], result.best_move);
+            let mut after = state.clone();
+            for chess_move in result.pv {
+                after.make_move(chess_move).unwrap();
+            }
+            assert_eq!(
+                after.status(),
+                GameStatus::Finished(GameResult::Checkmate(Color::White))
+            );
+        }
+
+        #[test]
+        fn no_moves() {
+            let state = from_fen("7k/6Q1/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(search(&state, &SearchOptions::new(2)), None);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    mod best_move_parallel {
+        use super::*;
+
+        #[test]
+        fn matches_single_threaded() {
+            for (placement, turn) in [
+                (
+                    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R",
+                    Color::White,
+                ),
+                ("4k3/8/4p3/3p4/8/8/3Q4/4K3", Color::White),
+                ("6k1/5ppp/8/8/8/8/8/R5K1", Color::White),
+            ] {
+                let state = from_fen(placement, turn);
+                assert_eq!(best_move_parallel(&state, 3), best_move(&state, 3));
+            }
+        }
+
+        #[test]
+        fn no_moves() {
+            let state = from_fen("7k/6Q1/6K1/8/8/8/8/8", Color::Black);
+            assert_eq!(best_move_parallel(&state, 2), None);
+        }
+    }
+
+    mod best_move_timed {
+        use super::*;
+
+        #[test]
+        fn mate_in_one() {
+            let state = from_fen("6k1/5ppp/8/8/8/8/8/R5K1", Color::White);
+            let chess_move = best_move_timed(&state, 3).unwrap();
+            assert_eq!(
+                chess_move.movement().to_position,
+                crate::board::Position::new(0, 7).unwrap()
+            );
+        }
+
+        #[test]
+        fn matches_fixed_depth() {
+            let state = from_fen("4k3/8/4p3/3p4/8/8/3Q4/4K3", Color::White);
+            assert_eq!(best_move_timed(&state, 1), best_move(&state, 1));
+        }
+
+        #[test]
+        fn no_depth() {
+            assert_eq!(best_move_timed(&GameState::new(), 0), None);
+        }
+    }
+
+    mod contempt {
+        use super::*;
+        use crate::board::{action, Position};
+
+        #[test]
+        fn stalemate_scored_for_engine() {
+            let mut state = from_fen("7k/5Q2/6K1/8/8/8/8/8", Color::Black);
+            let context = SearchContext {
+                engine: Color::White,
+                contempt: 50,
+            };
+            assert_eq!(
+                negamax_with(&mut state, 1, -INFINITY, INFINITY, &context, &mut vec![]),
+                50
+            );
+            let context = SearchContext {
+                engine: Color::Black,
+                contempt: 50,
+            };
+            assert_eq!(
+                negamax_with(&mut state, 1, -INFINITY, INFINITY, &context, &mut vec![]),
+                -50
+            );
+        }
+
+        #[test]
+        fn repetition() {
+            let knight_move = |from: (u8, u8), to: (u8, u8)| {
+                ChessMove::Move(action::Move {
+                    from_position: Position::new(from.0, from.1).unwrap(),
+                    to_position: Position::new(to.0, to.1).unwrap(),
+                })
+            };
+            let mut state = GameState::new();
+            for _ in 0..2 {
+                state.make_move(knight_move((6, 0), (5, 2))).unwrap();
+                state.make_move(knight_move((6, 7), (5, 5))).unwrap();
+                state.make_move(knight_move((5, 2), (6, 0))).unwrap();
+                state.make_move(knight_move((5, 5), (6, 7))).unwrap();
+            }
+            state.make_move(knight_move((6, 0), (5, 2))).unwrap();
+            state.make_move(knight_move((6, 7), (5, 5))).unwrap();
+            // Moving the knight back repeats the position
+            let repeat = knight_move((5, 2), (6, 0));
+            let best = |contempt| {
+                let options = SearchOptions { depth: 2, contempt };
+                search(&state, &options).map(|result| result.best_move)
+            };
+            assert_eq!(best(-1000), Some(repeat));
+            assert_ne!(best(1000), Some(repeat));
+        }
+
+        #[test]
+        fn avoids_stalemate() {
+            // Taking the rook stalemates black
+            let state = from_fen("k7/2r5/8/2Q5/8/8/8/7K", Color::White);
+            let stalemate = ChessMove::MoveWithTake(
+                action::Move {
+                    from_position: Position::new(2, 4).unwrap(),
+                    to_position: Position::new(2, 6).unwrap(),
+                },
+                action::Take {
+                    position: Position::new(2, 6).unwrap(),
+                },
+            );
+            let options = SearchOptions {
+                depth: 2,
+                contempt: -1000,
+            };
+            assert_eq!(
+                search(&state, &options).map(|result| result.best_move),
+                Some(stalemate)
+            );
+            let options = SearchOptions {
+                depth: 2,
+                contempt: 0,
+            };
+            assert_ne!(
+                search(&state, &options).map(|result| result.best_move),
+                Some(stalemate)
+            );
+        }
+    }
+}
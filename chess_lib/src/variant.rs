@@ -0,0 +1,99 @@
+use crate::piece::PieceType;
+
+/// Rules a game of chess can be played under.
+///
+/// Every variant uses the standard board and pieces, but changes how moves are played or how the game is won.
+///
+/// ```
+/// use chess_lib::{game::GameState, variant::Variant};
+///
+/// let state = GameState::new().with_variant(Variant::Atomic);
+/// assert_eq!(state.variant(), Variant::Atomic);
+/// ```
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Every capture causes an explosion, removing the capturing piece, the captured piece and every other piece except pawns next to them, see [`Board::explosion`](crate::board::mailbox::Board::explosion).
+    ///
+    /// Exploding the enemy king wins the game, so a move that explodes your own king is illegal and kings cannot capture.
+    /// Kings next to each other cannot give check, since taking the other king would explode both.
+    Atomic,
+    /// Also known as giveaway. Taking is compulsory, and a player wins by losing all their pieces or having no moves left, see [`Board::antichess_legal_moves`](crate::board::mailbox::Board::antichess_legal_moves).
+    ///
+    /// The king is an ordinary piece that can be taken, so there is no check or castling, and pawns can promote to a king.
+    Antichess,
+    /// Captured pieces join the capturing player's hand, and can be dropped back onto any empty square instead of moving, see [`ChessMove::Drop`](crate::board::ChessMove::Drop).
+    ///
+    /// Promoted pieces go back to being pawns when captured, and pawns cannot be dropped on the first or last rank.
+    Crazyhouse,
+    /// Giving check for the third time wins the game, as well as checkmate, see [`GameState::checks_given`](crate::game::GameState::checks_given).
+    ThreeCheck,
+    /// Moving your king onto one of the four center squares d4, d5, e4 or e5 wins the game, as well as checkmate.
+    KingOfTheHill,
+}
+
+impl Variant {
+    /// Returns the piece types a pawn can promote to, in the order promotions are generated.
+    ///
+    /// Standard chess allows a queen, rook, bishop or knight, and antichess also allows a king.
+    ///
+    /// ```
+    /// use chess_lib::{piece::PieceType, variant::Variant};
+    ///
+    /// assert_eq!(Variant::Standard.promotion_candidates().len(), 4);
+    /// assert!(Variant::Antichess.promotion_candidates().contains(&PieceType::King));
+    /// ```
+    #[must_use]
+    pub fn promotion_candidates(self) -> &'static [PieceType] {
+        match self {
+            Variant::Antichess => &[
+                PieceType::Queen,
+                PieceType::Rook,
+                PieceType::Bishop,
+                PieceType::Knight,
+                PieceType::King,
+            ],
+            _ => &[
+                PieceType::Queen,
+                PieceType::Rook,
+                PieceType::Bishop,
+                PieceType::Knight,
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod variant_tests {
+    use super::*;
+
+    mod promotion_candidates {
+        use super::*;
+
+        #[test]
+        fn standard() {
+            assert_eq!(
+                Variant::Standard.promotion_candidates(),
+                [
+                    PieceType::Queen,
+                    PieceType::Rook,
+                    PieceType::Bishop,
+                    PieceType::Knight
+                ]
+            );
+            assert_eq!(
+                Variant::Atomic.promotion_candidates(),
+                Variant::Standard.promotion_candidates()
+            );
+        }
+
+        #[test]
+        fn antichess_king() {
+            assert_eq!(
+                Variant::Antichess.promotion_candidates().last(),
+                Some(&PieceType::King)
+            );
+        }
+    }
+}
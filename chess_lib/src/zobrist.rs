@@ -0,0 +1,197 @@
+use crate::board::Position;
+use crate::game::{CastlingRights, GameState};
+use crate::piece::{Color, Piece, PieceType};
+use std::sync::OnceLock;
+
+/// Seed the random keys are generated from.
+///
+/// Hashes only depend on the seed, so are the same on every run of the same version of the crate.
+pub const ZOBRIST_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Random keys that are combined with XOR to make a Zobrist hash.
+struct ZobristKeys {
+    /// Indexed by color, piece type, then square.
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    /// White kingside, white queenside, black kingside, black queenside.
+    castling: [u64; 4],
+    /// Indexed by the file of the en passant target square.
+    en_passant: [u64; 8],
+    /// Indexed by color, piece type, then the number of pieces in hand minus one.
+    hand: [[[u64; 16]; 6]; 2],
+    /// Indexed by color, then the number of checks given minus one.
+    checks: [[u64; 3]; 2],
+}
+
+impl ZobristKeys {
+    /// Generates the keys from [`ZOBRIST_SEED`], so hashes are the same on every run.
+    fn generate() -> Self {
+        let mut state = ZOBRIST_SEED;
+        let mut next = || {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let mut keys = Self {
+            pieces: [[[0; 64]; 6]; 2],
+            black_to_move: 0,
+            castling: [0; 4],
+            en_passant: [0; 8],
+            hand: [[[0; 16]; 6]; 2],
+            checks: [[0; 3]; 2],
+        };
+        for key in keys.pieces.iter_mut().flatten().flatten() {
+            *key = next();
+        }
+        keys.black_to_move = next();
+        for key in &mut keys.castling {
+            *key = next();
+        }
+        for key in &mut keys.en_passant {
+            *key = next();
+        }
+        // Generated last so the other keys are the same as before pieces in hand were hashed
+        for key in keys.hand.iter_mut().flatten().flatten() {
+            *key = next();
+        }
+        for key in keys.checks.iter_mut().flatten() {
+            *key = next();
+        }
+        keys
+    }
+}
+
+/// Returns the keys, generating them the first time they are needed.
+fn keys() -> &'static ZobristKeys {
+    static KEYS: OnceLock<ZobristKeys> = OnceLock::new();
+    KEYS.get_or_init(ZobristKeys::generate)
+}
+
+/// Returns the key for `piece` standing on `position`.
+///
+/// Whether the piece has moved is not part of the key, castling rights are hashed separately.
+#[must_use]
+pub fn piece_key(piece: Piece, position: Position) -> u64 {
+    keys().pieces[piece.color.index()][piece.piece_type as usize]
+        [usize::from(position.y() * 8 + position.x())]
+}
+
+/// Returns the key included in the hash when black is to move.
+#[must_use]
+pub fn black_to_move_key() -> u64 {
+    keys().black_to_move
+}
+
+/// Returns the combined key for every castling right in `castling_rights`.
+#[must_use]
+pub fn castling_key(castling_rights: CastlingRights) -> u64 {
+    [
+        castling_rights.white_kingside,
+        castling_rights.white_queenside,
+        castling_rights.black_kingside,
+        castling_rights.black_queenside,
+    ]
+    .into_iter()
+    .zip(keys().castling)
+    .filter(|(allowed, _)| *allowed)
+    .fold(0, |hash, (_, key)| hash ^ key)
+}
+
+/// Returns the key for the file of the en passant target square, or 0 if there is none.
+#[must_use]
+pub fn en_passant_key(en_passant: Option<Position>) -> u64 {
+    en_passant.map_or(0, |target| keys().en_passant[usize::from(target.x())])
+}
+
+/// Returns the key for `color` holding `count` pieces of `piece_type` in hand, or 0 if they hold none.
+///
+/// Counts above 16 share the key for 16, which can never be reached in a real game.
+#[must_use]
+pub fn hand_key(color: Color, piece_type: PieceType, count: u8) -> u64 {
+    match count {
+        0 => 0,
+        count => keys().hand[color.index()][piece_type as usize][usize::from(count.min(16) - 1)],
+    }
+}
+
+/// Returns the key for `color` having given check `count` times in three-check, or 0 if they have given none.
+///
+/// Counts above 3 share the key for 3, since the game is over by then.
+#[must_use]
+pub fn checks_key(color: Color, count: u8) -> u64 {
+    match count {
+        0 => 0,
+        count => keys().checks[color.index()][usize::from(count.min(3) - 1)],
+    }
+}
+
+/// Computes the Zobrist hash of `state` from scratch.
+///
+/// The hash covers the pieces on the board, the side to move, castling rights, the en passant target square, any pieces in hand and the number of checks given.
+/// [`GameState`] keeps its hash up to date as moves are played, see [`GameState::zobrist`], this is the reference it is checked against.
+///
+/// ```
+/// use chess_lib::{game::GameState, zobrist};
+///
+/// let state = GameState::new();
+/// assert_eq!(zobrist::zobrist_hash(&state), state.zobrist());
+/// ```
+#[must_use]
+pub fn zobrist_hash(state: &GameState) -> u64 {
+    let mut hash = state
+        .board()
+        .iter_pieces()
+        .fold(0, |hash, (position, piece)| {
+            hash ^ piece_key(piece, position)
+        });
+    if state.turn() == Color::Black {
+        hash ^= black_to_move_key();
+    }
+    for (&(color, piece_type), &count) in state.hand() {
+        hash ^= hand_key(color, piece_type, count);
+    }
+    for color in [Color::White, Color::Black] {
+        hash ^= checks_key(color, state.checks_given(color));
+    }
+    hash ^ castling_key(state.castling_rights()) ^ en_passant_key(state.en_passant())
+}
+
+#[cfg(test)]
+mod zobrist_tests {
+    use super::*;
+    use crate::board::mailbox::Board;
+
+    mod zobrist_hash {
+        use super::*;
+
+        #[test]
+        fn stable() {
+            // Changes to the seed or the order keys are generated in change every hash, so must be deliberate
+            assert_eq!(zobrist_hash(&GameState::new()), 7_895_310_651_165_894_391);
+        }
+
+        #[test]
+        fn side_to_move() {
+            let white = GameState::from_board(Board::new(), Color::White);
+            let black = GameState::from_board(Board::new(), Color::Black);
+            assert_eq!(
+                zobrist_hash(&white) ^ zobrist_hash(&black),
+                black_to_move_key()
+            );
+        }
+
+        #[test]
+        fn castling_rights() {
+            let with_rights = GameState::from_board(
+                Board::from_fen("4k3/8/8/8/8/8/8/4K2R").unwrap(),
+                Color::White,
+            );
+            let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R").unwrap();
+            board[Position::new(7, 0).unwrap()].as_mut().unwrap().moved = true;
+            let without_rights = GameState::from_board(board, Color::White);
+            assert_ne!(zobrist_hash(&with_rights), zobrist_hash(&without_rights));
+        }
+    }
+}